@@ -76,6 +76,12 @@ pub struct WindowConfig {
   /// Whether the window should always be on top of other windows.
   #[serde(default)]
   pub always_on_top: bool,
+  /// Whether the window should be hidden from the taskbar.
+  #[serde(default)]
+  pub skip_taskbar: bool,
+  /// Whether the webview context menu is enabled or not.
+  #[serde(default = "default_context_menu")]
+  pub context_menu: bool,
 }
 
 fn default_window_label() -> String {
@@ -102,6 +108,10 @@ fn default_decorations() -> bool {
   true
 }
 
+fn default_context_menu() -> bool {
+  true
+}
+
 fn default_title() -> String {
   "Tauri App".to_string()
 }
@@ -127,10 +137,115 @@ impl Default for WindowConfig {
       visible: default_visible(),
       decorations: default_decorations(),
       always_on_top: false,
+      skip_taskbar: false,
+      context_menu: default_context_menu(),
     }
   }
 }
 
+/// A local accelerator, triggered while a window of the app is focused.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceleratorConfig {
+  /// The accelerator, modifiers and key separated by "+" e.g. `CmdOrControl+Shift+P`.
+  pub accelerator: String,
+  /// The event emitted to the focused window when the accelerator is triggered.
+  pub event: String,
+  /// The labels of the windows the accelerator is scoped to, all windows if not set.
+  #[serde(default)]
+  pub windows: Option<Vec<String>>,
+}
+
+/// A sidecar supervised by the app, started with it and restarted when it crashes.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarConfig {
+  /// The sidecar name, as declared on `tauri > bundle > externalBin`.
+  pub name: String,
+  /// The sidecar arguments.
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// Whether the sidecar is restarted when it exits or not.
+  #[serde(default = "default_sidecar_restart")]
+  pub restart: bool,
+  /// How many times the sidecar is restarted before giving up, unlimited if not set.
+  #[serde(default)]
+  pub max_restarts: Option<u32>,
+}
+
+fn default_sidecar_restart() -> bool {
+  true
+}
+
+/// An argument of a command allowed on the shell scope.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ShellAllowedArg {
+  /// A fixed argument.
+  Fixed(String),
+  /// A variable argument, which must fully match the validator regex.
+  Var {
+    /// The regex validating the argument.
+    validator: String,
+  },
+}
+
+/// A command the webview is allowed to execute.
+#[derive(PartialEq, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellAllowedCommand {
+  /// The name the webview refers to the command with.
+  pub name: String,
+  /// The program to execute, or the sidecar name if `sidecar` is set.
+  pub cmd: String,
+  /// The arguments the command must be executed with, in order.
+  #[serde(default)]
+  pub args: Vec<ShellAllowedArg>,
+  /// Whether the program is a sidecar or a system program.
+  #[serde(default)]
+  pub sidecar: bool,
+  /// The environment variables the webview can set on the command.
+  #[serde(default)]
+  pub env: Vec<String>,
+  /// Clears the environment variables inherited from the app.
+  #[serde(default)]
+  pub clear_env: bool,
+  /// The working directory of the command. Defaults to the app working directory.
+  pub cwd: Option<String>,
+}
+
+/// The shell allowlist, as used at runtime.
+#[derive(PartialEq, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellAllowlistConfig {
+  /// The commands the webview is allowed to execute.
+  #[serde(default)]
+  pub scope: Vec<ShellAllowedCommand>,
+}
+
+/// The file system allowlist, as used at runtime.
+#[derive(PartialEq, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FsAllowlistConfig {
+  /// The paths the webview can access with the file system API, with everything under them.
+  ///
+  /// A path can start with a base directory variable, e.g. `$APP/data` or `$DOCUMENT`.
+  /// All paths are accessible if not set.
+  pub scope: Option<Vec<String>>,
+}
+
+/// The allowlist options read at runtime. The API features are enabled by the CLI.
+#[derive(PartialEq, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AllowlistConfig {
+  /// The file system allowlist.
+  #[serde(default)]
+  pub fs: FsAllowlistConfig,
+  /// The shell allowlist.
+  #[serde(default)]
+  pub shell: ShellAllowlistConfig,
+}
+
 /// The Updater configuration object.
 #[derive(PartialEq, Deserialize, Debug, Clone)]
 #[serde(tag = "updater", rename_all = "camelCase")]
@@ -336,6 +451,15 @@ pub struct TauriConfig {
   /// The updater configuration.
   #[serde(default)]
   pub updater: UpdaterConfig,
+  /// The local accelerators.
+  #[serde(default)]
+  pub accelerators: Vec<AcceleratorConfig>,
+  /// The allowlist configuration.
+  #[serde(default)]
+  pub allowlist: AllowlistConfig,
+  /// The sidecars started with the app, in order.
+  #[serde(default)]
+  pub sidecars: Vec<SidecarConfig>,
 }
 
 impl Default for TauriConfig {
@@ -345,6 +469,9 @@ impl Default for TauriConfig {
       cli: None,
       bundle: BundleConfig::default(),
       updater: UpdaterConfig::default(),
+      accelerators: Vec::new(),
+      allowlist: AllowlistConfig::default(),
+      sidecars: Vec::new(),
     }
   }
 }
@@ -586,6 +713,8 @@ mod build {
       let visible = self.visible;
       let decorations = self.decorations;
       let always_on_top = self.always_on_top;
+      let skip_taskbar = self.skip_taskbar;
+      let context_menu = self.context_menu;
 
       literal_struct!(
         tokens,
@@ -607,7 +736,9 @@ mod build {
         maximized,
         visible,
         decorations,
-        always_on_top
+        always_on_top,
+        skip_taskbar,
+        context_menu
       );
     }
   }
@@ -717,6 +848,93 @@ mod build {
     }
   }
 
+  impl ToTokens for AcceleratorConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let accelerator = str_lit(&self.accelerator);
+      let event = str_lit(&self.event);
+      let windows = opt_vec_str_lit(self.windows.as_ref());
+
+      literal_struct!(tokens, AcceleratorConfig, accelerator, event, windows);
+    }
+  }
+
+  impl ToTokens for SidecarConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let name = str_lit(&self.name);
+      let args = vec_lit(&self.args, str_lit);
+      let restart = self.restart;
+      let max_restarts = opt_lit(self.max_restarts.as_ref());
+
+      literal_struct!(tokens, SidecarConfig, name, args, restart, max_restarts);
+    }
+  }
+
+  impl ToTokens for ShellAllowedArg {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let prefix = quote! { ::tauri::api::config::ShellAllowedArg };
+
+      tokens.append_all(match self {
+        Self::Fixed(arg) => {
+          let arg = str_lit(arg);
+          quote! { #prefix::Fixed(#arg) }
+        }
+        Self::Var { validator } => {
+          let validator = str_lit(validator);
+          quote! { #prefix::Var { validator: #validator } }
+        }
+      })
+    }
+  }
+
+  impl ToTokens for ShellAllowedCommand {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let name = str_lit(&self.name);
+      let cmd = str_lit(&self.cmd);
+      let args = vec_lit(&self.args, identity);
+      let sidecar = self.sidecar;
+      let env = vec_lit(&self.env, str_lit);
+      let clear_env = self.clear_env;
+      let cwd = opt_str_lit(self.cwd.as_ref());
+
+      literal_struct!(
+        tokens,
+        ShellAllowedCommand,
+        name,
+        cmd,
+        args,
+        sidecar,
+        env,
+        clear_env,
+        cwd
+      );
+    }
+  }
+
+  impl ToTokens for ShellAllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let scope = vec_lit(&self.scope, identity);
+
+      literal_struct!(tokens, ShellAllowlistConfig, scope);
+    }
+  }
+
+  impl ToTokens for FsAllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let scope = opt_vec_str_lit(self.scope.as_ref());
+
+      literal_struct!(tokens, FsAllowlistConfig, scope);
+    }
+  }
+
+  impl ToTokens for AllowlistConfig {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+      let fs = &self.fs;
+      let shell = &self.shell;
+
+      literal_struct!(tokens, AllowlistConfig, fs, shell);
+    }
+  }
+
   impl ToTokens for BuildConfig {
     fn to_tokens(&self, tokens: &mut TokenStream) {
       let dev_path = str_lit(&self.dev_path);
@@ -744,8 +962,21 @@ mod build {
       let cli = opt_lit(self.cli.as_ref());
       let bundle = &self.bundle;
       let updater = &self.updater;
+      let accelerators = vec_lit(&self.accelerators, identity);
+      let allowlist = &self.allowlist;
+      let sidecars = vec_lit(&self.sidecars, identity);
 
-      literal_struct!(tokens, TauriConfig, windows, cli, bundle, updater);
+      literal_struct!(
+        tokens,
+        TauriConfig,
+        windows,
+        cli,
+        bundle,
+        updater,
+        accelerators,
+        allowlist,
+        sidecars
+      );
     }
   }
 
@@ -817,6 +1048,8 @@ mod test {
         visible: true,
         decorations: true,
         always_on_top: false,
+        skip_taskbar: false,
+        context_menu: true,
       }],
       bundle: BundleConfig {
         identifier: String::from(""),
@@ -828,6 +1061,12 @@ mod test {
         pubkey: None,
         endpoints: None,
       },
+      accelerators: Vec::new(),
+      allowlist: AllowlistConfig {
+        fs: FsAllowlistConfig { scope: None },
+        shell: ShellAllowlistConfig { scope: Vec::new() },
+      },
+      sidecars: Vec::new(),
     };
 
     // create a build config
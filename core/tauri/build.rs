@@ -24,6 +24,12 @@ fn main() {
     fs_remove_file: { any(fs_all, feature = "fs-remove-file") },
     fs_rename_file: { any(fs_all, feature = "fs-rename-file") },
     fs_path: { any(fs_all, feature = "fs-path") },
+    fs_exists: { any(fs_all, feature = "fs-exists") },
+    fs_metadata: { any(fs_all, feature = "fs-metadata") },
+    fs_truncate_file: { any(fs_all, feature = "fs-truncate-file") },
+    fs_watch: { any(fs_all, feature = "fs-watch") },
+    fs_open_file: { any(fs_all, feature = "fs-open-file") },
+    fs_walk: { any(fs_all, feature = "fs-walk") },
 
     // window
     window_all: { any(api_all, feature = "window-all") },
@@ -49,7 +55,16 @@ fn main() {
     // notification
     notification_all: { any(api_all, feature = "notification-all") },
 
+    // clipboard
+    clipboard_all: { any(api_all, feature = "clipboard-all") },
+    clipboard_read_text: { any(clipboard_all, feature = "clipboard-read-text") },
+    clipboard_write_text: { any(clipboard_all, feature = "clipboard-write-text") },
+    clipboard: { any(clipboard_read_text, clipboard_write_text) },
+
     // global shortcut
-    global_shortcut_all: { any(api_all, feature = "global_shortcut-all") },
+    global_shortcut_all: { any(api_all, feature = "global-shortcut-all") },
+
+    // log
+    log_all: { any(api_all, feature = "log-all") },
   }
 }
@@ -120,6 +120,7 @@ if (!String.prototype.startsWith) {
             {
               callback: callback,
               error: error,
+              __invokeOrigin: window.location.protocol + "//" + window.location.host,
             },
             args
           )
@@ -132,6 +133,7 @@ if (!String.prototype.startsWith) {
               {
                 callback: callback,
                 error: error,
+                __invokeOrigin: window.location.protocol + "//" + window.location.host,
               },
               args
             )
@@ -228,7 +230,6 @@ if (!String.prototype.startsWith) {
     return window.__TAURI__
       .invoke('tauri', {
         __tauriModule: "Notification",
-        mainThread: true,
         message: {
           cmd: "requestNotificationPermission",
         },
@@ -297,7 +298,6 @@ if (!String.prototype.startsWith) {
   window.alert = function (message) {
     window.__TAURI__.invoke('tauri', {
       __tauriModule: "Dialog",
-      mainThread: true,
       message: {
         cmd: "messageDialog",
         message: message,
@@ -308,7 +308,6 @@ if (!String.prototype.startsWith) {
   window.confirm = function (message) {
     return window.__TAURI__.invoke('tauri', {
       __tauriModule: "Dialog",
-      mainThread: true,
       message: {
         cmd: "askDialog",
         message: message,
@@ -0,0 +1,534 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Local accelerators, triggered while a window of the application is focused.
+//!
+//! Unlike global shortcuts, local accelerators are captured by the webview, so they only trigger
+//! when one of the windows they are scoped to has the keyboard focus.
+
+use crate::{runtime::manager::WindowManager, Params, Window};
+use serde::Serialize;
+use std::{
+  collections::HashMap,
+  str::FromStr,
+  sync::{Arc, MutexGuard},
+};
+
+/// The command invoked by the webview when one of its accelerators is triggered.
+pub(crate) const ACCELERATOR_COMMAND: &str = "__accelerator";
+/// The command invoked by the webview on page load to get its current accelerators.
+pub(crate) const ACCELERATORS_COMMAND: &str = "__accelerators";
+
+/// A key combination, e.g. `CmdOrControl+Shift+P`.
+///
+/// `CmdOrControl` maps to `Command` on macOS and to `Control` elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Accelerator {
+  ctrl: bool,
+  alt: bool,
+  shift: bool,
+  meta: bool,
+  /// The key, as the `KeyboardEvent.code` the webview reports for it.
+  code: String,
+}
+
+impl FromStr for Accelerator {
+  type Err = crate::Error;
+
+  fn from_str(accelerator: &str) -> crate::Result<Self> {
+    let invalid = || crate::Error::InvalidAccelerator(accelerator.to_string());
+    let mut parsed = Accelerator {
+      ctrl: false,
+      alt: false,
+      shift: false,
+      meta: false,
+      code: String::new(),
+    };
+    for token in accelerator.split('+').map(str::trim) {
+      let modifier = match token.to_lowercase().as_str() {
+        "ctrl" | "control" => &mut parsed.ctrl,
+        "alt" | "option" => &mut parsed.alt,
+        "shift" => &mut parsed.shift,
+        "super" | "meta" | "cmd" | "command" => &mut parsed.meta,
+        "cmdorctrl" | "cmdorcontrol" | "commandorctrl" | "commandorcontrol" => {
+          if cfg!(target_os = "macos") {
+            &mut parsed.meta
+          } else {
+            &mut parsed.ctrl
+          }
+        }
+        _ => {
+          // a single key, after the modifiers
+          if !parsed.code.is_empty() {
+            return Err(invalid());
+          }
+          parsed.code = key_code(token).ok_or_else(invalid)?;
+          continue;
+        }
+      };
+      if *modifier || !parsed.code.is_empty() {
+        return Err(invalid());
+      }
+      *modifier = true;
+    }
+    if parsed.code.is_empty() {
+      return Err(invalid());
+    }
+    Ok(parsed)
+  }
+}
+
+/// Maps a key name to the `KeyboardEvent.code` of the key.
+fn key_code(key: &str) -> Option<String> {
+  let mut chars = key.chars();
+  if let (Some(c), None) = (chars.next(), chars.next()) {
+    return match c {
+      'a'..='z' | 'A'..='Z' => Some(format!("Key{}", c.to_ascii_uppercase())),
+      '0'..='9' => Some(format!("Digit{}", c)),
+      ',' => Some("Comma".into()),
+      '.' => Some("Period".into()),
+      '/' => Some("Slash".into()),
+      '-' => Some("Minus".into()),
+      '=' => Some("Equal".into()),
+      ';' => Some("Semicolon".into()),
+      _ => None,
+    };
+  }
+
+  let key = key.to_lowercase();
+  if let Some(n) = key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+    return if (1..=24).contains(&n) {
+      Some(format!("F{}", n))
+    } else {
+      None
+    };
+  }
+  let code = match key.as_str() {
+    "enter" | "return" => "Enter",
+    "escape" | "esc" => "Escape",
+    "space" => "Space",
+    "tab" => "Tab",
+    "backspace" => "Backspace",
+    "delete" => "Delete",
+    "insert" => "Insert",
+    "home" => "Home",
+    "end" => "End",
+    "pageup" => "PageUp",
+    "pagedown" => "PageDown",
+    "up" => "ArrowUp",
+    "down" => "ArrowDown",
+    "left" => "ArrowLeft",
+    "right" => "ArrowRight",
+    _ => return None,
+  };
+  Some(code.into())
+}
+
+/// What happens when an accelerator is triggered.
+enum Action<P: Params> {
+  /// Emits the event to the window, with the accelerator as payload.
+  Event(String),
+  /// Calls the handler with the window.
+  Handler(Arc<dyn Fn(Window<P>) + Send + Sync>),
+}
+
+struct Entry<P: Params> {
+  accelerator: Accelerator,
+  /// The labels of the windows the accelerator is scoped to, `None` for all windows.
+  windows: Option<Vec<P::Label>>,
+  action: Action<P>,
+}
+
+impl<P: Params> Entry<P> {
+  fn is_scoped_to(&self, label: &P::Label) -> bool {
+    self
+      .windows
+      .as_ref()
+      .map_or(true, |windows| windows.contains(label))
+  }
+
+  /// Whether both accelerators can be triggered on the same window by the same keys.
+  fn overlaps(&self, other: &Self) -> bool {
+    self.accelerator == other.accelerator
+      && match (&self.windows, &other.windows) {
+        (Some(windows), Some(others)) => windows.iter().any(|label| others.contains(label)),
+        _ => true,
+      }
+  }
+}
+
+/// The registered accelerators, keyed by the accelerator they were registered with.
+pub(crate) struct Accelerators<P: Params>(HashMap<String, Entry<P>>);
+
+impl<P: Params> Default for Accelerators<P> {
+  fn default() -> Self {
+    Self(Default::default())
+  }
+}
+
+/// An accelerator, as the webview matches it against its keyboard events.
+#[derive(Serialize)]
+pub(crate) struct ScriptAccelerator {
+  accelerator: String,
+  #[serde(flatten)]
+  keys: Accelerator,
+}
+
+/// Manages the local accelerators of the application.
+///
+/// Accelerators are identified by the string they were registered with, e.g.
+/// `CmdOrControl+Shift+P`, so unregistering one must use that same string. Registering an
+/// accelerator fails if it conflicts with another one on the same windows, or with a global
+/// shortcut.
+///
+/// The runtime doesn't support native menus yet, so accelerators are not shown in them. Use
+/// [`AcceleratorManager::registered_for`] to list them in the menus rendered by the webview instead.
+pub struct AcceleratorManager<P: Params> {
+  manager: WindowManager<P>,
+}
+
+impl<P: Params> Clone for AcceleratorManager<P> {
+  fn clone(&self) -> Self {
+    Self {
+      manager: self.manager.clone(),
+    }
+  }
+}
+
+impl<P: Params> AcceleratorManager<P> {
+  pub(crate) fn new(manager: WindowManager<P>) -> Self {
+    Self { manager }
+  }
+
+  fn accelerators(&self) -> MutexGuard<'_, Accelerators<P>> {
+    self
+      .manager
+      .inner
+      .accelerators
+      .lock()
+      .expect("poisoned accelerators")
+  }
+
+  /// Determines whether the given accelerator is registered or not.
+  pub fn is_registered(&self, accelerator: &str) -> bool {
+    self.accelerators().0.contains_key(accelerator)
+  }
+
+  /// The accelerators that can be triggered on the window with the given label, sorted.
+  pub fn registered_for(&self, label: &P::Label) -> Vec<String> {
+    let mut accelerators = self
+      .accelerators()
+      .0
+      .iter()
+      .filter(|(_, entry)| entry.is_scoped_to(label))
+      .map(|(accelerator, _)| accelerator.clone())
+      .collect::<Vec<_>>();
+    accelerators.sort();
+    accelerators
+  }
+
+  /// Whether a registered accelerator is triggered by the same keys as `accelerator`.
+  #[cfg(global_shortcut_all)]
+  pub(crate) fn conflicts(&self, accelerator: &Accelerator) -> bool {
+    self
+      .accelerators()
+      .0
+      .values()
+      .any(|entry| &entry.accelerator == accelerator)
+  }
+
+  /// Registers an accelerator on all windows, calling `handler` with the focused window every
+  /// time it is triggered.
+  pub fn register<F: Fn(Window<P>) + Send + Sync + 'static>(
+    &self,
+    accelerator: &str,
+    handler: F,
+  ) -> crate::Result<()> {
+    self.register_scoped(accelerator, None, Action::Handler(Arc::new(handler)))
+  }
+
+  /// Registers an accelerator on the windows with the given labels, or on all windows if `None`,
+  /// emitting `event` to the focused window every time it is triggered.
+  ///
+  /// The event payload is the accelerator.
+  pub fn register_event<E: Into<String>>(
+    &self,
+    accelerator: &str,
+    event: E,
+    windows: Option<Vec<P::Label>>,
+  ) -> crate::Result<()> {
+    self.register_scoped(accelerator, windows, Action::Event(event.into()))
+  }
+
+  /// Registers the accelerators declared in the config.
+  pub(crate) fn register_config(&self) -> crate::Result<()> {
+    for config in self.manager.config().tauri.accelerators.clone() {
+      let windows = config.windows.map(|labels| {
+        labels
+          .iter()
+          .map(|label| {
+            label
+              .parse()
+              .unwrap_or_else(|_| panic!("bad label found in config: {}", label))
+          })
+          .collect()
+      });
+      self.register_scoped(&config.accelerator, windows, Action::Event(config.event))?;
+    }
+    Ok(())
+  }
+
+  fn register_scoped(
+    &self,
+    accelerator: &str,
+    windows: Option<Vec<P::Label>>,
+    action: Action<P>,
+  ) -> crate::Result<()> {
+    let entry = Entry {
+      accelerator: accelerator.parse()?,
+      windows,
+      action,
+    };
+
+    #[cfg(global_shortcut_all)]
+    {
+      let shortcuts = self
+        .manager
+        .inner
+        .global_shortcuts
+        .lock()
+        .expect("poisoned global shortcuts");
+      if shortcuts.conflicts(&entry.accelerator) {
+        return Err(crate::Error::AcceleratorConflict(accelerator.to_string()));
+      }
+    }
+
+    {
+      let mut accelerators = self.accelerators();
+      if accelerators.0.contains_key(accelerator)
+        || accelerators.0.values().any(|other| other.overlaps(&entry))
+      {
+        return Err(crate::Error::AcceleratorConflict(accelerator.to_string()));
+      }
+      accelerators.0.insert(accelerator.to_string(), entry);
+    }
+    self.sync_windows()
+  }
+
+  /// Unregisters an accelerator.
+  pub fn unregister(&self, accelerator: &str) -> crate::Result<()> {
+    if self.accelerators().0.remove(accelerator).is_some() {
+      self.sync_windows()?;
+    }
+    Ok(())
+  }
+
+  /// Runs the action of the accelerator triggered on `window`.
+  ///
+  /// Any page loaded on the window can invoke the accelerator command, so the accelerators that
+  /// aren't scoped to the window are ignored.
+  pub(crate) fn trigger(&self, window: Window<P>, accelerator: &str) -> crate::Result<()> {
+    let action = match self.accelerators().0.get(accelerator) {
+      Some(entry) if entry.is_scoped_to(window.label()) => match &entry.action {
+        Action::Event(event) => Action::Event(event.clone()),
+        Action::Handler(handler) => Action::Handler(handler.clone()),
+      },
+      _ => return Ok(()),
+    };
+    // the lock is released, so the handler can register or unregister accelerators
+    match action {
+      Action::Event(event) => window.emit_internal(event, Some(accelerator)),
+      Action::Handler(handler) => {
+        handler(window);
+        Ok(())
+      }
+    }
+  }
+
+  /// The accelerators of the window with the given label, as the webview matches them.
+  pub(crate) fn scoped_to(&self, label: &P::Label) -> Vec<ScriptAccelerator> {
+    self
+      .accelerators()
+      .0
+      .iter()
+      .filter(|(_, entry)| entry.is_scoped_to(label))
+      .map(|(accelerator, entry)| ScriptAccelerator {
+        accelerator: accelerator.clone(),
+        keys: entry.accelerator.clone(),
+      })
+      .collect()
+  }
+
+  /// The script setting the accelerators of the window with the given label.
+  fn script(&self, label: &P::Label) -> crate::Result<String> {
+    Ok(format!(
+      "window.__TAURI_ACCELERATORS__ = {}",
+      serde_json::to_string(&self.scoped_to(label))?
+    ))
+  }
+
+  /// The script capturing the keyboard events of the webview to trigger its accelerators.
+  ///
+  /// The script runs again on every page load, so it requests the current accelerators instead
+  /// of inlining the ones registered when the window was created.
+  pub(crate) fn initialization_script() -> String {
+    format!(
+      r#"
+      function __tauriLoadAccelerators () {{
+        window.__TAURI__.invoke('{list_command}').then(function (accelerators) {{
+          window.__TAURI_ACCELERATORS__ = accelerators
+        }})
+      }}
+      if (window.rpc) {{
+        __tauriLoadAccelerators()
+      }} else {{
+        window.addEventListener('DOMContentLoaded', __tauriLoadAccelerators)
+      }}
+      window.addEventListener('keydown', function (e) {{
+        var accelerators = window.__TAURI_ACCELERATORS__ || []
+        for (var i = 0; i < accelerators.length; i++) {{
+          var a = accelerators[i]
+          if (a.code === e.code && a.ctrl === e.ctrlKey && a.alt === e.altKey && a.shift === e.shiftKey && a.meta === e.metaKey) {{
+            e.preventDefault()
+            e.stopPropagation()
+            window.__TAURI__.invoke('{command}', {{ accelerator: a.accelerator }})
+            return
+          }}
+        }}
+      }}, true)
+    "#,
+      list_command = ACCELERATORS_COMMAND,
+      command = ACCELERATOR_COMMAND,
+    )
+  }
+
+  /// Sends the registered accelerators to the open windows.
+  fn sync_windows(&self) -> crate::Result<()> {
+    let windows = self
+      .manager
+      .windows_lock()
+      .values()
+      .cloned()
+      .collect::<Vec<_>>();
+    for window in windows {
+      window.eval(&self.script(window.label())?)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Accelerator;
+  use crate::{
+    runtime::flavors::mock::{mock_context, MockDispatcher, MockRuntime},
+    Builder, Manager,
+  };
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn check_parse() {
+    let accelerator: Accelerator = "CmdOrControl+Shift+P".parse().expect("failed to parse");
+    assert!(accelerator.shift && !accelerator.alt);
+    assert_eq!(accelerator.meta, cfg!(target_os = "macos"));
+    assert_eq!(accelerator.ctrl, !cfg!(target_os = "macos"));
+    assert_eq!(accelerator.code, "KeyP");
+
+    assert_eq!(
+      "alt+f12".parse::<Accelerator>().unwrap(),
+      "Option+F12".parse::<Accelerator>().unwrap()
+    );
+    assert_eq!("Control+Up".parse::<Accelerator>().unwrap().code, "ArrowUp");
+
+    for invalid in &[
+      "Shift",
+      "Shift+Shift+P",
+      "P+Shift",
+      "Ctrl+P+Q",
+      "F25",
+      "Ctrl+NotAKey",
+    ] {
+      assert!(matches!(
+        invalid.parse::<Accelerator>(),
+        Err(crate::Error::InvalidAccelerator(accelerator)) if &accelerator == invalid
+      ));
+    }
+  }
+
+  #[test]
+  fn check_accelerators() {
+    let app = Builder::<String, String, _, MockRuntime>::new()
+      .build(mock_context())
+      .expect("failed to build mock app");
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+    let accelerators = app.accelerator_manager();
+
+    let triggered = Arc::new(Mutex::new(Vec::new()));
+    let triggered_ = triggered.clone();
+    accelerators
+      .register("CmdOrControl+Shift+P", move |window| {
+        triggered_.lock().unwrap().push(window.label().clone())
+      })
+      .expect("failed to register accelerator");
+    accelerators
+      .register_event("Alt+F4", "quit", Some(vec!["main".into()]))
+      .expect("failed to register accelerator");
+    accelerators
+      .register_event("F1", "help", Some(vec!["other".into()]))
+      .expect("failed to register accelerator");
+    assert!(accelerators.is_registered("Alt+F4"));
+    // a reloaded page requests its current accelerators
+    let list = mock.invoke("__accelerators", serde_json::json!({}));
+    let listed = mock.assert_invoke_ok(&list);
+    let listed = listed
+      .as_array()
+      .expect("accelerators aren't a list")
+      .iter()
+      .map(|accelerator| accelerator["accelerator"].clone())
+      .collect::<Vec<_>>();
+    assert!(listed.contains(&"Alt+F4".into()));
+    assert!(!listed.contains(&"F1".into()));
+    assert!(mock
+      .evaluated_scripts()
+      .iter()
+      .any(|script| script.contains("__TAURI_ACCELERATORS__") && script.contains("Alt+F4")));
+
+    // the same keys can't trigger two accelerators on the same window
+    assert!(matches!(
+      accelerators.register_event("Option+F4", "close", None),
+      Err(crate::Error::AcceleratorConflict(_))
+    ));
+    assert!(accelerators
+      .register_event("F1", "docs", Some(vec!["main".into()]))
+      .is_err());
+    accelerators
+      .register_event("Shift+F1", "docs", Some(vec!["main".into()]))
+      .expect("failed to register accelerator");
+    assert_eq!(
+      accelerators.registered_for(&"main".into()),
+      vec!["Alt+F4", "CmdOrControl+Shift+P", "Shift+F1"]
+    );
+
+    let invoke = mock.invoke(
+      "__accelerator",
+      serde_json::json!({ "accelerator": "Alt+F4" }),
+    );
+    mock.assert_invoke_ok(&invoke);
+    assert_eq!(mock.assert_emitted("quit"), "Alt+F4");
+    mock.invoke(
+      "__accelerator",
+      serde_json::json!({ "accelerator": "CmdOrControl+Shift+P" }),
+    );
+    assert_eq!(*triggered.lock().unwrap(), vec!["main".to_string()]);
+    // accelerators scoped to other windows are ignored
+    mock.invoke("__accelerator", serde_json::json!({ "accelerator": "F1" }));
+    mock.assert_not_emitted("help");
+
+    accelerators
+      .unregister("Alt+F4")
+      .expect("failed to unregister accelerator");
+    assert!(!accelerators.is_registered("Alt+F4"));
+  }
+}
@@ -29,19 +29,21 @@ pub fn current_binary() -> Option<PathBuf> {
   current_binary
 }
 
-/// Restart application
-pub fn restart_application(binary_to_start: Option<PathBuf>) {
-  let mut binary_path = binary_to_start;
-  // spawn new process
-  if binary_path.is_none() {
-    binary_path = current_binary();
-  }
-
-  if let Some(path) = binary_path {
-    Command::new(path)
-      .spawn()
-      .expect("application failed to start");
+/// Starts a new instance of the application.
+///
+/// Uses the current binary if `binary_to_start` is `None`.
+pub fn start_application(binary_to_start: Option<PathBuf>) -> crate::api::Result<()> {
+  if let Some(path) = binary_to_start.or_else(current_binary) {
+    Command::new(path).spawn()?;
   }
+  Ok(())
+}
 
+/// Restart application
+///
+/// The process exits immediately; prefer [`AppHandle::restart`](crate::AppHandle::restart)
+/// to let the application shut down gracefully.
+pub fn restart_application(binary_to_start: Option<PathBuf>) {
+  start_application(binary_to_start).expect("application failed to start");
   exit(0);
 }
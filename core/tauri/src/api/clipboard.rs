@@ -0,0 +1,273 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use arboard::{Clipboard, ImageData};
+use once_cell::sync::OnceCell;
+use std::{
+  borrow::Cow,
+  collections::hash_map::DefaultHasher,
+  hash::{Hash, Hasher},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Sender},
+    Arc, Mutex,
+  },
+  thread,
+  time::Duration,
+};
+
+type Job = Box<dyn FnOnce(&mut Option<Clipboard>) + Send>;
+
+/// Runs `f` on the clipboard thread.
+///
+/// The thread owns a single clipboard handle for the lifetime of the app: on Linux, the written
+/// contents are served by the handle and would be lost once it is dropped.
+fn with_clipboard<T, F>(f: F) -> crate::api::Result<T>
+where
+  T: Send + 'static,
+  F: FnOnce(&mut Clipboard) -> Result<T, arboard::Error> + Send + 'static,
+{
+  static JOBS: OnceCell<Mutex<Sender<Job>>> = OnceCell::new();
+  let jobs = JOBS.get_or_init(|| {
+    let (tx, rx) = channel::<Job>();
+    thread::spawn(move || {
+      let mut clipboard = None;
+      for job in rx {
+        job(&mut clipboard);
+      }
+    });
+    Mutex::new(tx)
+  });
+
+  let (tx, rx) = channel();
+  let job: Job = Box::new(move |clipboard| {
+    if clipboard.is_none() {
+      match Clipboard::new() {
+        Ok(c) => *clipboard = Some(c),
+        Err(e) => {
+          let _ = tx.send(Err(e));
+          return;
+        }
+      }
+    }
+    let _ = tx.send(f(clipboard.as_mut().unwrap()));
+  });
+  jobs
+    .lock()
+    .unwrap()
+    .send(job)
+    .expect("clipboard thread stopped");
+  rx.recv()
+    .expect("clipboard thread stopped")
+    .map_err(Into::into)
+}
+
+/// Runs `f` on the clipboard, mapping a missing content to `None`.
+fn read<T, F>(f: F) -> crate::api::Result<Option<T>>
+where
+  T: Send + 'static,
+  F: FnOnce(&mut Clipboard) -> Result<T, arboard::Error> + Send + 'static,
+{
+  match with_clipboard(f) {
+    Ok(value) => Ok(Some(value)),
+    Err(crate::api::Error::Clipboard(arboard::Error::ContentNotAvailable)) => Ok(None),
+    Err(e) => Err(e),
+  }
+}
+
+/// Reads the clipboard text, or `None` if the clipboard doesn't hold text.
+pub fn read_text() -> crate::api::Result<Option<String>> {
+  read(|clipboard| clipboard.get_text())
+}
+
+/// Writes text to the clipboard.
+pub fn write_text<S: Into<String>>(text: S) -> crate::api::Result<()> {
+  let text = text.into();
+  with_clipboard(move |clipboard| clipboard.set_text(text))
+}
+
+/// Reads the clipboard image as PNG bytes, or `None` if the clipboard doesn't hold an image.
+pub fn read_image() -> crate::api::Result<Option<Vec<u8>>> {
+  match read(|clipboard| clipboard.get_image())? {
+    Some(image) => encode_png(&image).map(Some),
+    None => Ok(None),
+  }
+}
+
+/// Writes an image to the clipboard from PNG bytes.
+pub fn write_image(png: &[u8]) -> crate::api::Result<()> {
+  let image = decode_png(png)?;
+  with_clipboard(move |clipboard| clipboard.set_image(image))
+}
+
+/// The clipboard content reported by [`watch`] when it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardChange {
+  /// The clipboard text, if any.
+  pub text: Option<String>,
+  /// Whether the clipboard holds an image, see [`read_image`].
+  pub has_image: bool,
+}
+
+/// A handle to a clipboard watcher started with [`watch`]. The watcher stops once it is dropped.
+pub struct ClipboardWatcher {
+  stop: Arc<AtomicBool>,
+}
+
+impl ClipboardWatcher {
+  /// Stops watching the clipboard.
+  pub fn stop(self) {}
+}
+
+impl Drop for ClipboardWatcher {
+  fn drop(&mut self) {
+    self.stop.store(true, Ordering::SeqCst);
+  }
+}
+
+/// A fingerprint of the clipboard content, to detect changes without keeping the images around.
+#[derive(PartialEq)]
+struct Snapshot {
+  text: Option<String>,
+  image: Option<u64>,
+}
+
+impl Snapshot {
+  fn read() -> crate::api::Result<Self> {
+    let text = read_text()?;
+    let image = read(|clipboard| clipboard.get_image())?.map(|image| {
+      let mut hasher = DefaultHasher::new();
+      (image.width, image.height, &image.bytes).hash(&mut hasher);
+      hasher.finish()
+    });
+    Ok(Self { text, image })
+  }
+}
+
+/// Polls the clipboard every `interval` on a background thread, calling `handler` when its text
+/// or image changes.
+///
+/// The operating systems don't notify clipboard changes in a portable way, so each poll reads the
+/// whole clipboard content; keep the interval reasonable when images are copied.
+pub fn watch<F: FnMut(ClipboardChange) + Send + 'static>(
+  interval: Duration,
+  mut handler: F,
+) -> ClipboardWatcher {
+  let stop = Arc::new(AtomicBool::new(false));
+  let stopped = stop.clone();
+  thread::spawn(move || {
+    let mut last = Snapshot::read().ok();
+    while !stopped.load(Ordering::SeqCst) {
+      thread::sleep(interval);
+      // the clipboard can be temporarily locked by another application
+      if let Ok(snapshot) = Snapshot::read() {
+        if last.as_ref() != Some(&snapshot) && !stopped.load(Ordering::SeqCst) {
+          handler(ClipboardChange {
+            text: snapshot.text.clone(),
+            has_image: snapshot.image.is_some(),
+          });
+        }
+        last = Some(snapshot);
+      }
+    }
+  });
+  ClipboardWatcher { stop }
+}
+
+fn png_error<E: std::fmt::Display>(error: E) -> crate::api::Error {
+  crate::api::Error::Png(error.to_string())
+}
+
+/// Encodes a RGBA clipboard image to PNG.
+fn encode_png(image: &ImageData<'_>) -> crate::api::Result<Vec<u8>> {
+  let mut png = Vec::new();
+  {
+    let mut encoder = png::Encoder::new(&mut png, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(png_error)?;
+    writer.write_image_data(&image.bytes).map_err(png_error)?;
+  }
+  Ok(png)
+}
+
+/// Decodes a PNG image to the RGBA format used by the clipboard.
+fn decode_png(png: &[u8]) -> crate::api::Result<ImageData<'static>> {
+  let mut decoder = png::Decoder::new(png);
+  decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+  let (info, mut reader) = decoder.read_info().map_err(png_error)?;
+  let mut buffer = vec![0; reader.output_buffer_size()];
+  reader.next_frame(&mut buffer).map_err(png_error)?;
+  buffer.truncate(info.buffer_size());
+
+  let rgba = match info.color_type {
+    png::ColorType::RGBA => buffer,
+    color_type => {
+      let mut rgba = Vec::with_capacity(info.width as usize * info.height as usize * 4);
+      for pixel in buffer.chunks(color_type.samples()) {
+        match pixel {
+          [gray] => rgba.extend_from_slice(&[*gray, *gray, *gray, u8::MAX]),
+          [gray, alpha] => rgba.extend_from_slice(&[*gray, *gray, *gray, *alpha]),
+          [red, green, blue] => rgba.extend_from_slice(&[*red, *green, *blue, u8::MAX]),
+          _ => {
+            return Err(png_error(format!(
+              "unsupported color type {:?}",
+              color_type
+            )))
+          }
+        }
+      }
+      rgba
+    }
+  };
+
+  Ok(ImageData {
+    width: info.width as usize,
+    height: info.height as usize,
+    bytes: Cow::Owned(rgba),
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn check_png_roundtrip() {
+    let image = ImageData {
+      width: 2,
+      height: 1,
+      bytes: Cow::Owned(vec![255, 0, 0, 255, 0, 0, 255, 128]),
+    };
+    let png = encode_png(&image).expect("failed to encode png");
+    let decoded = decode_png(&png).expect("failed to decode png");
+    assert_eq!(decoded.width, 2);
+    assert_eq!(decoded.height, 1);
+    assert_eq!(decoded.bytes, image.bytes);
+  }
+
+  #[test]
+  fn check_png_to_rgba() {
+    let mut png = Vec::new();
+    {
+      let mut encoder = png::Encoder::new(&mut png, 2, 1);
+      encoder.set_color(png::ColorType::Grayscale);
+      encoder.set_depth(png::BitDepth::Eight);
+      let mut writer = encoder.write_header().expect("failed to write header");
+      writer
+        .write_image_data(&[0, 200])
+        .expect("failed to write image");
+    }
+    let decoded = decode_png(&png).expect("failed to decode png");
+    assert_eq!(&decoded.bytes[..], &[0, 0, 0, 255, 200, 200, 200, 255]);
+  }
+
+  #[test]
+  fn check_invalid_png() {
+    assert!(matches!(
+      decode_png(b"not a png"),
+      Err(crate::api::Error::Png(_))
+    ));
+  }
+}
@@ -3,27 +3,38 @@
 // SPDX-License-Identifier: MIT
 
 use std::{
-  io::{BufRead, BufReader, Write},
-  process::{Command as StdCommand, Stdio},
+  collections::HashMap,
+  io::{self, BufRead, BufReader, Read, Write},
+  path::{Path, PathBuf},
+  process::{Command as StdCommand, ExitStatus, Stdio},
   sync::Arc,
+  thread::sleep,
+  time::{Duration, Instant},
 };
 
 #[cfg(unix)]
-use std::os::unix::process::ExitStatusExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
-use crate::api::private::async_runtime::{channel, spawn, Receiver, RwLock};
+/// How often a terminating process is checked for exit.
+const TERMINATE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The size of the chunks read from the output of commands in raw mode.
+const RAW_CHUNK_SIZE: usize = 4096;
+
+use crate::api::private::async_runtime::{channel, spawn, Receiver, RwLock, Sender};
+pub use encoding_rs::Encoding;
 use os_pipe::{pipe, PipeWriter};
 use serde::Serialize;
 use shared_child::SharedChild;
 use tauri_utils::platform;
 
 /// Payload for the `Terminated` command event.
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TerminatedPayload {
   /// Exit code of the process.
   pub code: Option<i32>,
@@ -31,6 +42,18 @@ pub struct TerminatedPayload {
   pub signal: Option<i32>,
 }
 
+impl From<ExitStatus> for TerminatedPayload {
+  fn from(status: ExitStatus) -> Self {
+    Self {
+      code: status.code(),
+      #[cfg(windows)]
+      signal: None,
+      #[cfg(unix)]
+      signal: status.signal(),
+    }
+  }
+}
+
 /// A event sent to the command callback.
 #[derive(Serialize)]
 #[serde(tag = "event", content = "payload")]
@@ -39,21 +62,95 @@ pub enum CommandEvent {
   Stderr(String),
   /// Stdout line.
   Stdout(String),
+  /// Stderr bytes, if the command output is read in raw mode.
+  StderrRaw(Vec<u8>),
+  /// Stdout bytes, if the command output is read in raw mode.
+  StdoutRaw(Vec<u8>),
   /// An error happened.
   Error(String),
   /// Command process terminated.
   Terminated(TerminatedPayload),
 }
 
+/// How the output of a command is read.
+#[derive(Debug, Clone, Copy)]
+enum OutputMode {
+  /// Lines, decoded with the encoding.
+  Text(&'static Encoding),
+  /// Chunks of bytes, as they are read.
+  Raw,
+}
+
+impl OutputMode {
+  fn encoding(self) -> &'static Encoding {
+    match self {
+      Self::Text(encoding) => encoding,
+      Self::Raw => encoding_rs::UTF_8,
+    }
+  }
+}
+
+/// The output of a finished command, see [`Command::output`].
+#[derive(Debug)]
+pub struct Output {
+  /// How the process terminated.
+  pub status: TerminatedPayload,
+  /// The bytes written to stdout.
+  pub stdout: Vec<u8>,
+  /// The bytes written to stderr.
+  pub stderr: Vec<u8>,
+  encoding: &'static Encoding,
+}
+
+impl Output {
+  /// Whether the process exited successfully or not.
+  pub fn success(&self) -> bool {
+    self.status.code == Some(0)
+  }
+
+  /// The stdout, decoded with the encoding of the command.
+  pub fn stdout_text(&self) -> String {
+    decode(self.encoding, &self.stdout)
+  }
+
+  /// The stderr, decoded with the encoding of the command.
+  pub fn stderr_text(&self) -> String {
+    decode(self.encoding, &self.stderr)
+  }
+}
+
+/// Decodes `bytes`, replacing the malformed sequences.
+fn decode(encoding: &'static Encoding, bytes: &[u8]) -> String {
+  encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
 macro_rules! get_std_command {
   ($self: ident) => {{
     let mut command = StdCommand::new($self.program);
     command.args(&$self.args);
+    if $self.env_clear {
+      command.env_clear();
+    }
+    command.envs(&$self.env);
+    if let Some(current_dir) = &$self.current_dir {
+      command.current_dir(current_dir);
+    }
     command.stdout(Stdio::piped());
     command.stdin(Stdio::piped());
     command.stderr(Stdio::piped());
     #[cfg(windows)]
     command.creation_flags(CREATE_NO_WINDOW);
+    // the process leads its own group, so its descendants can be signaled along with it
+    #[cfg(unix)]
+    unsafe {
+      command.pre_exec(|| {
+        if libc::setpgid(0, 0) == 0 {
+          Ok(())
+        } else {
+          Err(io::Error::last_os_error())
+        }
+      });
+    }
     command
   }};
 }
@@ -62,6 +159,10 @@ macro_rules! get_std_command {
 pub struct Command {
   program: String,
   args: Vec<String>,
+  env_clear: bool,
+  env: HashMap<String, String>,
+  current_dir: Option<PathBuf>,
+  output_mode: OutputMode,
 }
 
 /// Child spawned.
@@ -76,9 +177,87 @@ impl CommandChild {
     self.stdin_writer.write_all(buf)?;
     Ok(())
   }
-  /// Send a kill signal to the child.
+  /// Kills the process and its descendants.
   pub fn kill(self) -> crate::api::Result<()> {
-    self.inner.kill()?;
+    self.kill_tree()?;
+    Ok(())
+  }
+
+  /// Kills the process without waiting for it, for when the application is exiting.
+  ///
+  /// On Unix its descendants are killed along with it, on Windows only the process itself is.
+  pub(crate) fn kill_now(&self) {
+    #[cfg(unix)]
+    let _ = self.signal_group(libc::SIGKILL);
+    #[cfg(windows)]
+    let _ = self.inner.kill();
+  }
+
+  /// Asks the process and its descendants to terminate, killing them if the process is still
+  /// running after `timeout`.
+  ///
+  /// On Unix the process group receives `SIGTERM`, on Windows the process tree is closed with
+  /// `taskkill`.
+  pub fn terminate(self, timeout: Duration) -> crate::api::Result<()> {
+    self.terminate_tree()?;
+    let deadline = Instant::now() + timeout;
+    while self.inner.try_wait()?.is_none() && Instant::now() < deadline {
+      sleep(TERMINATE_POLL_INTERVAL);
+    }
+    // the descendants may outlive the process
+    self.kill_tree()?;
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  fn terminate_tree(&self) -> io::Result<()> {
+    self.signal_group(libc::SIGTERM)
+  }
+
+  #[cfg(unix)]
+  fn kill_tree(&self) -> io::Result<()> {
+    self.signal_group(libc::SIGKILL)
+  }
+
+  #[cfg(unix)]
+  fn signal_group(&self, signal: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::kill(-(self.pid() as libc::pid_t), signal) } == 0 {
+      return Ok(());
+    }
+    match io::Error::last_os_error() {
+      // the whole group already exited
+      e if e.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+      e => Err(e),
+    }
+  }
+
+  #[cfg(windows)]
+  fn terminate_tree(&self) -> io::Result<()> {
+    self.taskkill(false)
+  }
+
+  #[cfg(windows)]
+  fn kill_tree(&self) -> io::Result<()> {
+    self.taskkill(true)?;
+    // taskkill fails silently once the process exited, so make sure it did
+    match self.inner.kill() {
+      Err(e) if self.inner.try_wait()?.is_none() => Err(e),
+      _ => Ok(()),
+    }
+  }
+
+  #[cfg(windows)]
+  fn taskkill(&self, force: bool) -> io::Result<()> {
+    let mut command = StdCommand::new("taskkill");
+    command.args(&["/T", "/PID", &self.pid().to_string()]);
+    if force {
+      command.arg("/F");
+    }
+    command
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .creation_flags(CREATE_NO_WINDOW)
+      .status()?;
     Ok(())
   }
 
@@ -94,6 +273,10 @@ impl Command {
     Self {
       program: program.into(),
       args: Default::default(),
+      env_clear: false,
+      env: Default::default(),
+      current_dir: None,
+      output_mode: OutputMode::Text(encoding_rs::UTF_8),
     }
   }
 
@@ -118,8 +301,42 @@ impl Command {
     self
   }
 
+  /// Clears the environment variables inherited from the app, before applying [`Command::envs`].
+  pub fn env_clear(mut self) -> Self {
+    self.env_clear = true;
+    self
+  }
+
+  /// Sets environment variables of the process.
+  pub fn envs(mut self, env: HashMap<String, String>) -> Self {
+    self.env.extend(env);
+    self
+  }
+
+  /// Sets the working directory of the process.
+  pub fn current_dir<P: AsRef<Path>>(mut self, current_dir: P) -> Self {
+    self.current_dir.replace(current_dir.as_ref().to_path_buf());
+    self
+  }
+
+  /// Reads the output as lines decoded with the given encoding, instead of UTF-8.
+  ///
+  /// The lines are split on `\n`, so the encoding must be ASCII compatible.
+  pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+    self.output_mode = OutputMode::Text(encoding);
+    self
+  }
+
+  /// Reads the output as raw chunks of bytes, emitted as [`CommandEvent::StdoutRaw`] and
+  /// [`CommandEvent::StderrRaw`] instead of lines.
+  pub fn raw_output(mut self) -> Self {
+    self.output_mode = OutputMode::Raw;
+    self
+  }
+
   /// Spawns the command.
   pub fn spawn(self) -> crate::api::Result<(Receiver<CommandEvent>, CommandChild)> {
+    let output_mode = self.output_mode;
     let mut command = get_std_command!(self);
     let (stdout_reader, stdout_writer) = pipe()?;
     let (stderr_reader, stderr_writer) = pipe()?;
@@ -135,44 +352,26 @@ impl Command {
 
     let (tx, rx) = channel(1);
 
-    let tx_ = tx.clone();
-    let guard_ = guard.clone();
-    spawn(async move {
-      let _lock = guard_.read().await;
-      let reader = BufReader::new(stdout_reader);
-      for line in reader.lines() {
-        let _ = match line {
-          Ok(line) => tx_.send(CommandEvent::Stdout(line)).await,
-          Err(e) => tx_.send(CommandEvent::Error(e.to_string())).await,
-        };
-      }
-    });
-
-    let tx_ = tx.clone();
-    let guard_ = guard.clone();
-    spawn(async move {
-      let _lock = guard_.read().await;
-      let reader = BufReader::new(stderr_reader);
-      for line in reader.lines() {
-        let _ = match line {
-          Ok(line) => tx_.send(CommandEvent::Stderr(line)).await,
-          Err(e) => tx_.send(CommandEvent::Error(e.to_string())).await,
-        };
-      }
-    });
+    spawn_reader(
+      stdout_reader,
+      output_mode,
+      tx.clone(),
+      guard.clone(),
+      (CommandEvent::Stdout, CommandEvent::StdoutRaw),
+    );
+    spawn_reader(
+      stderr_reader,
+      output_mode,
+      tx.clone(),
+      guard.clone(),
+      (CommandEvent::Stderr, CommandEvent::StderrRaw),
+    );
 
     spawn(async move {
       let _ = match child_.wait() {
         Ok(status) => {
           guard.write().await;
-          tx.send(CommandEvent::Terminated(TerminatedPayload {
-            code: status.code(),
-            #[cfg(windows)]
-            signal: None,
-            #[cfg(unix)]
-            signal: status.signal(),
-          }))
-          .await
+          tx.send(CommandEvent::Terminated(status.into())).await
         }
         Err(e) => {
           guard.write().await;
@@ -189,6 +388,66 @@ impl Command {
       },
     ))
   }
+
+  /// Executes the command, waiting for it to finish and collecting all of its output.
+  pub fn output(self) -> crate::api::Result<Output> {
+    let encoding = self.output_mode.encoding();
+    let output = get_std_command!(self).output()?;
+    Ok(Output {
+      status: output.status.into(),
+      stdout: output.stdout,
+      stderr: output.stderr,
+      encoding,
+    })
+  }
+}
+
+/// Sends the output read from `reader` as events built by `events`, for text and raw output.
+fn spawn_reader<R: Read + Send + 'static>(
+  reader: R,
+  output_mode: OutputMode,
+  tx: Sender<CommandEvent>,
+  guard: Arc<RwLock<()>>,
+  events: (fn(String) -> CommandEvent, fn(Vec<u8>) -> CommandEvent),
+) {
+  spawn(async move {
+    let _lock = guard.read().await;
+    let mut reader = BufReader::new(reader);
+    loop {
+      let mut buf = Vec::new();
+      let read = match output_mode {
+        OutputMode::Text(_) => reader.read_until(b'\n', &mut buf),
+        OutputMode::Raw => {
+          buf.resize(RAW_CHUNK_SIZE, 0);
+          reader.read(&mut buf)
+        }
+      };
+      let event = match read {
+        // the last line is sent even if it doesn't end with a new line
+        Ok(0) => break,
+        Ok(n) => match output_mode {
+          OutputMode::Text(encoding) => {
+            if buf.ends_with(b"\n") {
+              buf.pop();
+              if buf.ends_with(b"\r") {
+                buf.pop();
+              }
+            }
+            (events.0)(decode(encoding, &buf))
+          }
+          OutputMode::Raw => {
+            buf.truncate(n);
+            (events.1)(buf)
+          }
+        },
+        Err(e) => {
+          let _ = tx.send(CommandEvent::Error(e.to_string())).await;
+          break;
+        }
+      };
+      let _ = tx.send(event).await;
+    }
+  });
 }
 
 // tests for the commands functions.
@@ -239,4 +498,85 @@ mod test {
       }
     });
   }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn test_cmd_options() {
+    let output = Command::new("/bin/sh")
+      .args(&["-c", "printf \"$GREETING\" && pwd"])
+      .env_clear()
+      .envs(
+        vec![("GREETING".to_string(), "hello ".to_string())]
+          .into_iter()
+          .collect(),
+      )
+      .current_dir("test/api")
+      .output()
+      .unwrap();
+    assert!(output.success());
+    let stdout = output.stdout_text();
+    assert!(stdout.starts_with("hello /") && stdout.trim_end().ends_with("test/api"));
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn test_cmd_encoding() {
+    // "café" in latin-1, without a trailing new line
+    let cmd = Command::new("printf")
+      .args(&["caf\\351"])
+      .encoding(Encoding::for_label(b"latin1").unwrap());
+    let (mut rx, _) = cmd.spawn().unwrap();
+
+    crate::api::private::async_runtime::block_on(async move {
+      let mut lines = Vec::new();
+      while let Some(event) = rx.recv().await {
+        if let CommandEvent::Stdout(line) = event {
+          lines.push(line);
+        }
+      }
+      assert_eq!(lines, vec!["café".to_string()]);
+    });
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn test_cmd_raw_output() {
+    let cmd = Command::new("printf").args(&["\\000\\377"]).raw_output();
+    let (mut rx, _) = cmd.spawn().unwrap();
+
+    crate::api::private::async_runtime::block_on(async move {
+      let mut stdout = Vec::new();
+      while let Some(event) = rx.recv().await {
+        if let CommandEvent::StdoutRaw(bytes) = event {
+          stdout.extend(bytes);
+        }
+      }
+      assert_eq!(stdout, vec![0, 255]);
+    });
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_cmd_terminate_tree() {
+    // the shell ignores SIGTERM, so it must be killed once the timeout expires
+    let cmd = Command::new("/bin/sh").args(&["-c", "trap '' TERM; sleep 30 & echo $!; wait"]);
+    let (mut rx, child) = cmd.spawn().unwrap();
+
+    crate::api::private::async_runtime::block_on(async move {
+      let grandchild = match rx.recv().await {
+        Some(CommandEvent::Stdout(pid)) => pid,
+        _ => panic!("expected the pid of the grandchild"),
+      };
+      child.terminate(Duration::from_millis(200)).unwrap();
+
+      while let Some(event) = rx.recv().await {
+        if let CommandEvent::Terminated(payload) = event {
+          assert_eq!(payload.signal, Some(libc::SIGKILL));
+        }
+      }
+      // the grandchild is gone, or a zombie waiting to be reaped
+      let stat = std::fs::read_to_string(format!("/proc/{}/stat", grandchild)).unwrap_or_default();
+      assert!(stat.is_empty() || stat.contains(") Z "));
+    });
+  }
 }
@@ -2,10 +2,17 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+//! Native file and message dialogs.
+//!
+//! The dialogs block the thread showing them until they are closed, running a nested event loop
+//! so the windows stay responsive. On Linux and macOS they must be shown on the main thread,
+//! e.g. with [`Window::run_on_main_thread`](crate::Window::run_on_main_thread).
+
 use std::path::{Path, PathBuf};
 
-use rfd::FileDialog;
-use tinyfiledialogs::{message_box_ok, message_box_yes_no, MessageBoxIcon, YesNo};
+use raw_window_handle::HasRawWindowHandle;
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageLevel};
+use serde::Deserialize;
 
 /// The file dialog builder.
 /// Constructs file picker dialogs that can select single/multiple files or directories.
@@ -30,6 +37,40 @@ impl FileDialogBuilder {
     self
   }
 
+  /// Set the file name initially proposed by the dialog.
+  pub fn set_file_name(mut self, file_name: impl AsRef<str>) -> Self {
+    self.0 = self.0.set_file_name(file_name.as_ref());
+    self
+  }
+
+  /// Set the starting path of the dialog: its directory if it is a directory,
+  /// otherwise its parent directory and its file name.
+  pub fn set_default_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+    let path = path.as_ref();
+    if path.is_dir() {
+      return self.set_directory(path);
+    }
+    if let Some(file_name) = path.file_name() {
+      self = self.set_file_name(file_name.to_string_lossy());
+    }
+    match path.parent() {
+      Some(parent) if !parent.as_os_str().is_empty() => self.set_directory(parent),
+      _ => self,
+    }
+  }
+
+  /// Set the title of the dialog.
+  pub fn set_title(mut self, title: impl AsRef<str>) -> Self {
+    self.0 = self.0.set_title(title.as_ref());
+    self
+  }
+
+  /// Set the window the dialog is modal to. Only supported on macOS and Windows.
+  pub fn set_parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
+    self.0 = self.0.set_parent(parent);
+    self
+  }
+
   /// Pick one file.
   pub fn pick_file(self) -> Option<PathBuf> {
     self.0.pick_file()
@@ -51,6 +92,94 @@ impl FileDialogBuilder {
   }
 }
 
+/// The kind of a message dialog, usually shown with its icon.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageKind {
+  /// An informational message.
+  Info,
+  /// A warning.
+  Warning,
+  /// An error.
+  Error,
+}
+
+impl Default for MessageKind {
+  fn default() -> Self {
+    Self::Info
+  }
+}
+
+impl From<MessageKind> for MessageLevel {
+  fn from(kind: MessageKind) -> Self {
+    match kind {
+      MessageKind::Info => Self::Info,
+      MessageKind::Warning => Self::Warning,
+      MessageKind::Error => Self::Error,
+    }
+  }
+}
+
+/// The buttons of a message dialog.
+///
+/// Custom labels are not supported: they need `rfd` 0.10, which doesn't build on Rust 1.51.
+#[derive(Debug, Clone, Copy)]
+pub enum MessageDialogButtons {
+  /// An "Ok" button.
+  Ok,
+  /// "Ok" and "Cancel" buttons.
+  OkCancel,
+  /// "Yes" and "No" buttons.
+  YesNo,
+}
+
+impl From<MessageDialogButtons> for MessageButtons {
+  fn from(buttons: MessageDialogButtons) -> Self {
+    match buttons {
+      MessageDialogButtons::Ok => Self::Ok,
+      MessageDialogButtons::OkCancel => Self::OkCancel,
+      MessageDialogButtons::YesNo => Self::YesNo,
+    }
+  }
+}
+
+/// The message dialog builder.
+pub struct MessageDialogBuilder(MessageDialog);
+
+impl MessageDialogBuilder {
+  /// Creates a message dialog with an "Ok" button.
+  pub fn new(title: impl AsRef<str>, message: impl AsRef<str>) -> Self {
+    Self(
+      MessageDialog::new()
+        .set_title(title.as_ref())
+        .set_description(message.as_ref()),
+    )
+  }
+
+  /// Set the kind of the message.
+  pub fn kind(mut self, kind: MessageKind) -> Self {
+    self.0 = self.0.set_level(kind.into());
+    self
+  }
+
+  /// Set the buttons of the dialog.
+  pub fn buttons(mut self, buttons: MessageDialogButtons) -> Self {
+    self.0 = self.0.set_buttons(buttons.into());
+    self
+  }
+
+  /// Set the window the dialog is modal to. Only supported on Windows.
+  pub fn parent<W: HasRawWindowHandle>(mut self, parent: &W) -> Self {
+    self.0 = self.0.set_parent(parent);
+    self
+  }
+
+  /// Shows the dialog, returning `true` if the "Ok" or "Yes" button was pressed.
+  pub fn show(self) -> bool {
+    self.0.show()
+  }
+}
+
 /// Response for the ask dialog
 pub enum AskResponse {
   /// User confirmed.
@@ -61,18 +190,25 @@ pub enum AskResponse {
 
 /// Displays a dialog with a message and an optional title with a "yes" and a "no" button
 pub fn ask(title: impl AsRef<str>, message: impl AsRef<str>) -> AskResponse {
-  match message_box_yes_no(
-    title.as_ref(),
-    message.as_ref(),
-    MessageBoxIcon::Question,
-    YesNo::No,
-  ) {
-    YesNo::Yes => AskResponse::Yes,
-    YesNo::No => AskResponse::No,
+  let confirmed = MessageDialogBuilder::new(title, message)
+    .buttons(MessageDialogButtons::YesNo)
+    .show();
+  if confirmed {
+    AskResponse::Yes
+  } else {
+    AskResponse::No
   }
 }
 
+/// Displays a dialog with a message and an optional title with an "ok" and a "cancel" button.
+/// Returns `true` if the user confirmed.
+pub fn confirm(title: impl AsRef<str>, message: impl AsRef<str>) -> bool {
+  MessageDialogBuilder::new(title, message)
+    .buttons(MessageDialogButtons::OkCancel)
+    .show()
+}
+
 /// Displays a message dialog
 pub fn message(title: impl AsRef<str>, message: impl AsRef<str>) {
-  message_box_ok(title.as_ref(), message.as_ref(), MessageBoxIcon::Info);
+  MessageDialogBuilder::new(title, message).show();
 }
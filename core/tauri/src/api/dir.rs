@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use serde::Serialize;
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use serde::{Deserialize, Serialize};
 use std::{
   fs::{self, metadata},
   path::{Path, PathBuf},
@@ -58,6 +59,142 @@ pub fn read_dir<P: AsRef<Path>>(path: P, recursive: bool) -> crate::api::Result<
   Result::Ok(files_and_dirs)
 }
 
+/// The options of the [`walk`] function.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WalkOptions {
+  /// The maximum depth to descend into. The root directory entries have depth 1.
+  pub max_depth: Option<usize>,
+  /// Whether hidden files and directories should be included.
+  pub hidden: bool,
+  /// Whether the `.gitignore`, `.ignore` and global git exclude rules should be respected.
+  pub gitignore: bool,
+  /// Whether symbolic links should be followed.
+  pub follow_links: bool,
+  /// Glob patterns, relative to the root directory, of the files to include.
+  /// All files are included if empty. Directories are always traversed.
+  /// Matching files are included even if they are hidden or ignored.
+  pub include: Vec<String>,
+  /// Glob patterns, relative to the root directory, of the files and directories to exclude.
+  pub exclude: Vec<String>,
+  /// Whether the metadata of each entry should be read.
+  pub metadata: bool,
+}
+
+impl Default for WalkOptions {
+  fn default() -> Self {
+    Self {
+      max_depth: None,
+      hidden: false,
+      gitignore: true,
+      follow_links: false,
+      include: Vec::new(),
+      exclude: Vec::new(),
+      metadata: false,
+    }
+  }
+}
+
+/// An entry yielded by the [`walk`] function.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkEntry {
+  /// The path to this entry.
+  pub path: PathBuf,
+  /// The name of this entry (file name with extension or directory name)
+  pub name: Option<String>,
+  /// The depth of this entry, relative to the root directory.
+  pub depth: usize,
+  /// Whether this entry is a directory.
+  pub is_dir: bool,
+  /// Whether this entry is a symbolic link.
+  pub is_symlink: bool,
+  /// The entry metadata, if requested with [`WalkOptions::metadata`].
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub metadata: Option<crate::api::file::Metadata>,
+}
+
+/// A lazy iterator over the entries of a directory tree, created by [`walk`].
+pub struct Walk {
+  inner: ignore::Walk,
+  metadata: bool,
+}
+
+impl Iterator for Walk {
+  type Item = crate::api::Result<WalkEntry>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      let entry = match self.inner.next()? {
+        Ok(entry) => entry,
+        Err(e) => return Some(Err(e.into())),
+      };
+      // skip the root directory
+      if entry.depth() == 0 {
+        continue;
+      }
+      let file_type = entry.file_type();
+      let metadata = if self.metadata {
+        match crate::api::file::metadata(entry.path()) {
+          Ok(metadata) => Some(metadata),
+          Err(e) => return Some(Err(e)),
+        }
+      } else {
+        None
+      };
+      return Some(Ok(WalkEntry {
+        name: Some(entry.file_name().to_string_lossy().to_string()),
+        depth: entry.depth(),
+        is_dir: file_type.map(|t| t.is_dir()).unwrap_or(false),
+        is_symlink: entry.path_is_symlink(),
+        metadata,
+        path: entry.into_path(),
+      }));
+    }
+  }
+}
+
+/// Walks a directory tree lazily, yielding its entries (excluding the root directory itself).
+///
+/// # Example
+/// ```no_run
+/// use tauri::api::dir::{walk, WalkOptions};
+///
+/// let options = WalkOptions {
+///   include: vec!["*.rs".into()],
+///   max_depth: Some(3),
+///   ..Default::default()
+/// };
+/// for entry in walk("/path/to/dir", &options).expect("invalid walk options") {
+///   println!("{:?}", entry);
+/// }
+/// ```
+pub fn walk<P: AsRef<Path>>(path: P, options: &WalkOptions) -> crate::api::Result<Walk> {
+  let path = path.as_ref();
+
+  let mut overrides = OverrideBuilder::new(path);
+  for glob in &options.include {
+    overrides.add(glob)?;
+  }
+  for glob in &options.exclude {
+    overrides.add(&format!("!{}", glob))?;
+  }
+
+  let mut builder = WalkBuilder::new(path);
+  builder
+    .standard_filters(options.gitignore)
+    .require_git(false)
+    .hidden(!options.hidden)
+    .follow_links(options.follow_links)
+    .max_depth(options.max_depth)
+    .overrides(overrides.build()?);
+
+  Ok(Walk {
+    inner: builder.build(),
+    metadata: options.metadata,
+  })
+}
+
 /// Runs a closure with a temp dir argument.
 pub fn with_temp_dir<F: FnOnce(&tempfile::TempDir)>(callback: F) -> crate::api::Result<()> {
   let dir = tempdir()?;
@@ -186,6 +323,55 @@ mod test {
     }
   }
 
+  #[test]
+  // check the walk function with filters
+  fn check_walk() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src").join("nested")).unwrap();
+    fs::write(dir.path().join("src").join("main.rs"), "").unwrap();
+    fs::write(dir.path().join("src").join("nested").join("lib.rs"), "").unwrap();
+    fs::write(dir.path().join("src").join("notes.txt"), "").unwrap();
+    fs::write(dir.path().join(".hidden.rs"), "").unwrap();
+
+    let names = |options: &WalkOptions| {
+      let mut names = walk(dir.path(), options)
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.name.unwrap())
+        .collect::<Vec<_>>();
+      names.sort();
+      names
+    };
+
+    assert_eq!(
+      names(&WalkOptions::default()),
+      vec!["lib.rs", "main.rs", "notes.txt"]
+    );
+    assert_eq!(
+      names(&WalkOptions {
+        include: vec!["*.rs".into()],
+        hidden: true,
+        ..Default::default()
+      }),
+      vec![".hidden.rs", "lib.rs", "main.rs"]
+    );
+    assert_eq!(
+      names(&WalkOptions {
+        exclude: vec!["nested".into()],
+        ..Default::default()
+      }),
+      vec!["main.rs", "notes.txt"]
+    );
+    assert_eq!(
+      names(&WalkOptions {
+        max_depth: Some(2),
+        ..Default::default()
+      }),
+      vec!["main.rs", "notes.txt"]
+    );
+  }
+
   #[test]
   // test the with_temp_dir function
   fn check_test_dir() {
@@ -47,6 +47,10 @@ pub enum Error {
   /// Ignore error.
   #[error("failed to walkdir: {0}")]
   Ignore(#[from] ignore::Error),
+  /// File system watcher error.
+  #[cfg(fs_watch)]
+  #[error("failed to watch path: {0}")]
+  Watch(#[from] notify::Error),
   /// ZIP error.
   #[error("{0}")]
   Zip(#[from] zip::result::ZipError),
@@ -65,7 +69,32 @@ pub enum Error {
   #[cfg(global_shortcut_all)]
   #[error("shortcut error: {0}")]
   Shortcut(#[from] tauri_hotkey::Error),
+  /// Clipboard error.
+  #[cfg(clipboard)]
+  #[error("clipboard error: {0}")]
+  Clipboard(#[from] arboard::Error),
+  /// Invalid PNG image.
+  #[cfg(clipboard)]
+  #[error("invalid PNG image: {0}")]
+  Png(String),
   /// Shell error.
   #[error("shell error: {0}")]
   Shell(String),
+  /// The shell scope config is invalid.
+  #[error("invalid shell scope: {0}")]
+  InvalidScope(String),
+  /// The program is not on the shell scope.
+  #[error("program `{0}` not allowed on the shell scope")]
+  ProgramNotAllowed(String),
+  /// The arguments of a command don't match the shell scope.
+  #[error("invalid arguments for command `{command}`: {reason}")]
+  InvalidArgs {
+    /// The name of the command.
+    command: String,
+    /// Why the arguments were rejected.
+    reason: String,
+  },
+  /// The path is not on the file system scope.
+  #[error("path `{0}` not allowed on the fs scope")]
+  PathNotAllowed(std::path::PathBuf),
 }
@@ -2,13 +2,85 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+mod archive;
 mod extract;
 mod file_move;
+mod scope;
+#[cfg(fs_watch)]
+mod watcher;
 
-use std::{fs, path::Path};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{self, File, OpenOptions},
+  io::{Read, Seek, SeekFrom},
+  path::Path,
+  time::{SystemTime, UNIX_EPOCH},
+};
 
+pub use archive::*;
 pub use extract::*;
 pub use file_move::*;
+pub use scope::*;
+#[cfg(fs_watch)]
+pub use watcher::*;
+
+/// Metadata information about a file or directory.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+  /// Whether the path points to a directory.
+  pub is_dir: bool,
+  /// Whether the path points to a regular file.
+  pub is_file: bool,
+  /// Whether the path itself is a symbolic link.
+  pub is_symlink: bool,
+  /// The size of the file, in bytes.
+  pub size: u64,
+  /// Whether the file is read only.
+  pub readonly: bool,
+  /// The Unix permission bits (`st_mode`). Always `None` on Windows.
+  pub mode: Option<u32>,
+  /// Last modification time, in milliseconds since the Unix epoch.
+  pub modified_at: Option<u64>,
+  /// Last access time, in milliseconds since the Unix epoch.
+  pub accessed_at: Option<u64>,
+  /// Creation time, in milliseconds since the Unix epoch. Not available on every platform.
+  pub created_at: Option<u64>,
+}
+
+fn to_millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+  time
+    .ok()
+    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_millis() as u64)
+}
+
+/// Reads the metadata of a file or directory, following symbolic links.
+pub fn metadata<P: AsRef<Path>>(path: P) -> crate::api::Result<Metadata> {
+  let path = path.as_ref();
+  let is_symlink = fs::symlink_metadata(path)?.file_type().is_symlink();
+  let metadata = fs::metadata(path)?;
+
+  #[cfg(unix)]
+  let mode = {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode())
+  };
+  #[cfg(not(unix))]
+  let mode = None;
+
+  Ok(Metadata {
+    is_dir: metadata.is_dir(),
+    is_file: metadata.is_file(),
+    is_symlink,
+    size: metadata.len(),
+    readonly: metadata.permissions().readonly(),
+    mode,
+    modified_at: to_millis(metadata.modified()),
+    accessed_at: to_millis(metadata.accessed()),
+    created_at: to_millis(metadata.created()),
+  })
+}
 
 /// Reads a string file.
 pub fn read_string<P: AsRef<Path>>(file: P) -> crate::api::Result<String> {
@@ -20,6 +92,85 @@ pub fn read_binary<P: AsRef<Path>>(file: P) -> crate::api::Result<Vec<u8>> {
   fs::read(file).map_err(Into::into)
 }
 
+/// Reads a section of a binary file, starting at `offset`.
+///
+/// If `length` is `None`, the file is read until its end.
+/// The returned buffer is shorter than `length` if the end of the file is reached first.
+pub fn read_binary_range<P: AsRef<Path>>(
+  file: P,
+  offset: u64,
+  length: Option<u64>,
+) -> crate::api::Result<Vec<u8>> {
+  let mut file = File::open(file)?;
+  file.seek(SeekFrom::Start(offset))?;
+  let mut buffer = Vec::new();
+  match length {
+    Some(length) => file.take(length).read_to_end(&mut buffer)?,
+    None => file.read_to_end(&mut buffer)?,
+  };
+  Ok(buffer)
+}
+
+/// The mode used to open a file, matching the `fopen` modes.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum OpenMode {
+  /// Opens the file for reading (`r`).
+  #[serde(rename = "r")]
+  Read,
+  /// Opens the file for reading and writing (`r+`).
+  #[serde(rename = "r+")]
+  ReadWrite,
+  /// Truncates or creates the file and opens it for writing (`w`).
+  #[serde(rename = "w")]
+  Write,
+  /// Truncates or creates the file and opens it for reading and writing (`w+`).
+  #[serde(rename = "w+")]
+  WriteRead,
+  /// Creates the file if needed and opens it for appending (`a`).
+  #[serde(rename = "a")]
+  Append,
+  /// Creates the file if needed and opens it for reading and appending (`a+`).
+  #[serde(rename = "a+")]
+  AppendRead,
+}
+
+impl Default for OpenMode {
+  fn default() -> Self {
+    Self::Read
+  }
+}
+
+impl OpenMode {
+  fn options(self) -> OpenOptions {
+    let mut options = OpenOptions::new();
+    match self {
+      Self::Read => options.read(true),
+      Self::ReadWrite => options.read(true).write(true),
+      Self::Write => options.write(true).create(true).truncate(true),
+      Self::WriteRead => options.read(true).write(true).create(true).truncate(true),
+      Self::Append => options.append(true).create(true),
+      Self::AppendRead => options.read(true).append(true).create(true),
+    };
+    options
+  }
+}
+
+/// Opens a file with the given mode.
+pub fn open<P: AsRef<Path>>(path: P, mode: OpenMode) -> crate::api::Result<File> {
+  mode.options().open(path).map_err(Into::into)
+}
+
+/// Reads up to `len` bytes from the reader.
+///
+/// The returned buffer is shorter than `len` only if the end of the stream is reached,
+/// so an empty buffer means there is nothing left to read.
+pub fn read_chunk<R: Read>(reader: R, len: usize) -> crate::api::Result<Vec<u8>> {
+  // `len` isn't trusted, so the buffer only grows with what is actually read
+  let mut buffer = Vec::new();
+  reader.take(len as u64).read_to_end(&mut buffer)?;
+  Ok(buffer)
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -78,6 +229,74 @@ mod test {
     }
   }
 
+  #[test]
+  fn check_read_binary_range() {
+    let file = String::from("test/api/test.txt");
+
+    let res = read_binary_range(&file, 5, Some(2));
+    assert!(res.is_ok());
+    if let Ok(vec) = res {
+      assert_eq!(vec, b"is".to_vec());
+    }
+
+    let res = read_binary_range(&file, 15, None);
+    assert!(res.is_ok());
+    if let Ok(vec) = res {
+      assert_eq!(vec, b"doc!".to_vec());
+    }
+  }
+
+  #[test]
+  fn check_read_chunk() {
+    let mut file = open("test/api/test.txt", OpenMode::Read).expect("failed to open file");
+
+    let res = read_chunk(&mut file, 4);
+    assert!(res.is_ok());
+    if let Ok(vec) = res {
+      assert_eq!(vec, b"This".to_vec());
+    }
+
+    file.seek(SeekFrom::End(-4)).expect("failed to seek");
+    let res = read_chunk(&mut file, 10);
+    assert!(res.is_ok());
+    if let Ok(vec) = res {
+      assert_eq!(vec, b"doc!".to_vec());
+    }
+
+    let res = read_chunk(&mut file, 10);
+    assert!(res.is_ok());
+    if let Ok(vec) = res {
+      assert!(vec.is_empty());
+    }
+  }
+
+  #[test]
+  fn check_open_fail() {
+    let res = open("test/api/does_not_exist.txt", OpenMode::Read);
+    assert!(res.is_err());
+  }
+
+  #[test]
+  fn check_metadata() {
+    let res = metadata("test/api/test.txt");
+
+    assert!(res.is_ok());
+
+    if let Ok(metadata) = res {
+      assert!(metadata.is_file);
+      assert!(!metadata.is_dir);
+      assert!(!metadata.is_symlink);
+      assert_eq!(metadata.size, 19);
+      assert!(metadata.modified_at.is_some());
+    }
+
+    let res = metadata("test/api/");
+    assert!(res.is_ok());
+    if let Ok(metadata) = res {
+      assert!(metadata.is_dir);
+    }
+  }
+
   #[test]
   fn check_read_binary_fail() {
     let file = String::from("test/api/");
@@ -0,0 +1,124 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::{extract::detect_archive_type, ArchiveFormat, Compression};
+use ignore::WalkBuilder;
+
+use std::{
+  fs,
+  io::{self, Write},
+  path,
+};
+
+/// The archive builder, creating an archive from the contents of a directory.
+///
+/// # Example
+/// ```no_run
+/// use tauri::api::file::{Archive, ArchiveFormat, Compression};
+/// use std::path::Path;
+///
+/// Archive::from_directory(Path::new("/path/to/dir"))
+///   .archive_format(ArchiveFormat::Tar(Some(Compression::Gz)))
+///   .create(Path::new("/path/to/archive.tar.gz"))
+///   .expect("failed to create archive");
+/// ```
+#[derive(Debug)]
+pub struct Archive<'a> {
+  source: &'a path::Path,
+  archive_format: Option<ArchiveFormat>,
+}
+
+impl<'a> Archive<'a> {
+  /// Create an `Archive` from a source directory.
+  pub fn from_directory(source: &'a path::Path) -> Archive<'a> {
+    Self {
+      source,
+      archive_format: None,
+    }
+  }
+
+  /// Specify the archive format to create. If not specified, the
+  /// archive format will determined from the destination file extension.
+  pub fn archive_format(&mut self, format: ArchiveFormat) -> &mut Self {
+    self.archive_format = Some(format);
+    self
+  }
+
+  /// Writes the archive to `destination`. The entries are relative to the source directory.
+  pub fn create(&self, destination: &path::Path) -> crate::api::Result<()> {
+    let archive = self
+      .archive_format
+      .unwrap_or_else(|| detect_archive_type(destination));
+    let output = fs::File::create(destination)?;
+
+    match archive {
+      ArchiveFormat::Zip => self.write_zip(output),
+      ArchiveFormat::Tar(None) => self.write_tar(output).map(|_| ()),
+      ArchiveFormat::Tar(Some(Compression::Gz)) => {
+        let encoder = flate2::write::GzEncoder::new(output, flate2::Compression::default());
+        self.write_tar(encoder)?.finish()?;
+        Ok(())
+      }
+      ArchiveFormat::Tar(Some(Compression::Zstd)) => {
+        let encoder = zstd::stream::write::Encoder::new(output, 0)?;
+        self.write_tar(encoder)?.finish()?;
+        Ok(())
+      }
+      ArchiveFormat::Tar(Some(Compression::Xz)) => {
+        let encoder = xz2::write::XzEncoder::new(output, 6);
+        self.write_tar(encoder)?.finish()?;
+        Ok(())
+      }
+      ArchiveFormat::Plain(_) => Err(crate::api::Error::Extract(
+        "a directory can only be archived as a zip or tar file".into(),
+      )),
+    }
+  }
+
+  fn write_tar<W: Write>(&self, writer: W) -> crate::api::Result<W> {
+    let mut builder = tar::Builder::new(writer);
+    builder.follow_symlinks(false);
+    builder.append_dir_all("", self.source)?;
+    builder.into_inner().map_err(Into::into)
+  }
+
+  fn write_zip(&self, output: fs::File) -> crate::api::Result<()> {
+    let mut zip = zip::ZipWriter::new(output);
+    let walkdir = WalkBuilder::new(self.source)
+      .standard_filters(false)
+      .build();
+    for entry in walkdir {
+      let entry = entry?;
+      let path = entry.path();
+      let relative = path.strip_prefix(self.source)?;
+      if relative.as_os_str().is_empty() {
+        continue;
+      }
+      // zip entries always use forward slashes
+      let name = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+      let metadata = fs::metadata(path)?;
+      let options =
+        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+      #[cfg(unix)]
+      let options = {
+        use std::os::unix::fs::PermissionsExt;
+        options.unix_permissions(metadata.permissions().mode())
+      };
+
+      if metadata.is_dir() {
+        zip.add_directory(name, options)?;
+      } else {
+        zip.start_file(name, options)?;
+        io::copy(&mut fs::File::open(path)?, &mut zip)?;
+      }
+    }
+    zip.finish()?;
+    Ok(())
+  }
+}
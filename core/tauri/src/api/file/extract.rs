@@ -2,9 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use either::{self, Either};
-
-use std::{fs, io, path};
+use std::{
+  fmt, fs,
+  io::{self, Read, Write},
+  path::{self, Component, PathBuf},
+};
 
 /// The supported archive formats.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -22,30 +24,132 @@ pub enum ArchiveFormat {
 pub enum Compression {
   /// Gz compression (e.g. `.tar.gz` archives)
   Gz,
+  /// Zstandard compression (e.g. `.tar.zst` archives)
+  Zstd,
+  /// Xz compression (e.g. `.tar.xz` archives)
+  Xz,
 }
 
+/// A closure called after each extracted entry with the entry path, relative to the destination
+/// directory, and the total number of bytes extracted so far.
+type ProgressHandler<'a> = dyn Fn(&path::Path, u64) + 'a;
+
 /// The extract manager.
-#[derive(Debug)]
 pub struct Extract<'a> {
   source: &'a path::Path,
   archive_format: Option<ArchiveFormat>,
+  size_limit: Option<u64>,
+  on_progress: Option<Box<ProgressHandler<'a>>>,
 }
 
-fn detect_archive_type(path: &path::Path) -> ArchiveFormat {
-  match path.extension() {
-    Some(extension) if extension == std::ffi::OsStr::new("zip") => ArchiveFormat::Zip,
-    Some(extension) if extension == std::ffi::OsStr::new("tar") => ArchiveFormat::Tar(None),
-    Some(extension) if extension == std::ffi::OsStr::new("gz") => match path
-      .file_stem()
-      .map(|e| path::Path::new(e))
-      .and_then(|f| f.extension())
-    {
-      Some(extension) if extension == std::ffi::OsStr::new("tar") => {
-        ArchiveFormat::Tar(Some(Compression::Gz))
+impl fmt::Debug for Extract<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Extract")
+      .field("source", &self.source)
+      .field("archive_format", &self.archive_format)
+      .field("size_limit", &self.size_limit)
+      .finish()
+  }
+}
+
+pub(crate) fn detect_archive_type(path: &path::Path) -> ArchiveFormat {
+  let compression = match path.extension().and_then(|e| e.to_str()) {
+    Some("zip") => return ArchiveFormat::Zip,
+    Some("tar") => return ArchiveFormat::Tar(None),
+    Some("tgz") => return ArchiveFormat::Tar(Some(Compression::Gz)),
+    Some("gz") => Compression::Gz,
+    Some("zst") => Compression::Zstd,
+    Some("xz") => Compression::Xz,
+    _ => return ArchiveFormat::Plain(None),
+  };
+  match path
+    .file_stem()
+    .map(|e| path::Path::new(e))
+    .and_then(|f| f.extension())
+  {
+    Some(extension) if extension == std::ffi::OsStr::new("tar") => {
+      ArchiveFormat::Tar(Some(compression))
+    }
+    _ => ArchiveFormat::Plain(Some(compression)),
+  }
+}
+
+/// Returns the normalized path if it is relative and doesn't escape its root directory.
+///
+/// `foo/../bar` is accepted, while `/foo`, `../foo` and `foo/../../bar` are rejected.
+fn enclosed_path(path: &path::Path) -> Option<PathBuf> {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::Prefix(_) | Component::RootDir => return None,
+      Component::ParentDir => {
+        if !normalized.pop() {
+          return None;
+        }
+      }
+      Component::Normal(part) => normalized.push(part),
+      Component::CurDir => (),
+    }
+  }
+  Some(normalized)
+}
+
+/// Ensures that the directory, after resolving symbolic links, is inside `root`.
+fn ensure_inside(root: &path::Path, dir: &path::Path) -> crate::api::Result<()> {
+  if dir.canonicalize()?.starts_with(root.canonicalize()?) {
+    Ok(())
+  } else {
+    Err(crate::api::Error::Extract(format!(
+      "{} is outside of the destination directory",
+      dir.display()
+    )))
+  }
+}
+
+/// Keeps track of the extracted bytes, enforcing the size limit and reporting progress.
+struct Extraction<'e, 'a> {
+  extract: &'e Extract<'a>,
+  extracted: u64,
+}
+
+impl Extraction<'_, '_> {
+  /// Accounts for `size` bytes that are about to be extracted.
+  fn reserve(&mut self, size: u64) -> crate::api::Result<()> {
+    let extracted = self.extracted.saturating_add(size);
+    match self.extract.size_limit {
+      Some(limit) if extracted > limit => Err(Self::limit_exceeded(limit)),
+      _ => {
+        self.extracted = extracted;
+        Ok(())
       }
-      _ => ArchiveFormat::Plain(Some(Compression::Gz)),
-    },
-    _ => ArchiveFormat::Plain(None),
+    }
+  }
+
+  /// Copies the reader into the writer, failing once the size limit is exceeded.
+  fn copy<R: Read, W: Write>(&mut self, mut reader: R, mut writer: W) -> crate::api::Result<()> {
+    let copied = match self.extract.size_limit {
+      Some(limit) => {
+        let remaining = limit.saturating_sub(self.extracted);
+        let copied = io::copy(&mut reader.take(remaining.saturating_add(1)), &mut writer)?;
+        if copied > remaining {
+          return Err(Self::limit_exceeded(limit));
+        }
+        copied
+      }
+      None => io::copy(&mut reader, &mut writer)?,
+    };
+    self.extracted += copied;
+    Ok(())
+  }
+
+  fn progress(&self, entry: &path::Path) {
+    if let Some(on_progress) = &self.extract.on_progress {
+      on_progress(entry, self.extracted);
+    }
+  }
+
+  fn limit_exceeded(limit: u64) -> crate::api::Error {
+    crate::api::Error::Extract(format!("archive exceeds the size limit of {} bytes", limit))
   }
 }
 
@@ -55,6 +159,8 @@ impl<'a> Extract<'a> {
     Self {
       source,
       archive_format: None,
+      size_limit: None,
+      on_progress: None,
     }
   }
 
@@ -65,28 +171,51 @@ impl<'a> Extract<'a> {
     self
   }
 
+  /// Limits the total number of bytes written when extracting, protecting against archive bombs.
+  /// The extraction fails as soon as the limit is exceeded, leaving the entries extracted so far.
+  pub fn size_limit(&mut self, bytes: u64) -> &mut Self {
+    self.size_limit = Some(bytes);
+    self
+  }
+
+  /// Registers a closure called after each extracted entry with the entry path,
+  /// relative to the destination directory, and the total number of bytes extracted so far.
+  pub fn on_progress<F: Fn(&path::Path, u64) + 'a>(&mut self, handler: F) -> &mut Self {
+    self.on_progress = Some(Box::new(handler));
+    self
+  }
+
   fn get_archive_reader(
     source: fs::File,
     compression: Option<Compression>,
-  ) -> Either<fs::File, flate2::read::GzDecoder<fs::File>> {
-    match compression {
-      Some(Compression::Gz) => Either::Right(flate2::read::GzDecoder::new(source)),
-      None => Either::Left(source),
-    }
+  ) -> crate::api::Result<Box<dyn Read>> {
+    Ok(match compression {
+      Some(Compression::Gz) => Box::new(flate2::read::GzDecoder::new(source)),
+      Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(source)?),
+      Some(Compression::Xz) => Box::new(xz2::read::XzDecoder::new(source)),
+      None => Box::new(source),
+    })
   }
 
   /// Extract an entire source archive into a specified path. If the source is a single compressed
   /// file and not an archive, it will be extracted into a file with the same name inside of
   /// `into_dir`.
+  ///
+  /// Entries with absolute paths, entries escaping `into_dir` (e.g. `../file`) and links
+  /// pointing outside of `into_dir` are rejected.
   pub fn extract_into(&self, into_dir: &path::Path) -> crate::api::Result<()> {
     let source = fs::File::open(self.source)?;
     let archive = self
       .archive_format
       .unwrap_or_else(|| detect_archive_type(&self.source));
+    let mut extraction = Extraction {
+      extract: self,
+      extracted: 0,
+    };
 
     match archive {
       ArchiveFormat::Plain(compression) | ArchiveFormat::Tar(compression) => {
-        let mut reader = Self::get_archive_reader(source, compression);
+        let reader = Self::get_archive_reader(source, compression)?;
 
         match archive {
           ArchiveFormat::Plain(_) => {
@@ -103,12 +232,18 @@ impl<'a> Extract<'a> {
             })?;
             let mut out_path = into_dir.join(file_name);
             out_path.set_extension("");
-            let mut out_file = fs::File::create(&out_path)?;
-            io::copy(&mut reader, &mut out_file)?;
+            let out_file = fs::File::create(&out_path)?;
+            extraction.copy(reader, out_file)?;
+            extraction.progress(out_path.strip_prefix(into_dir).unwrap_or(&out_path));
           }
           ArchiveFormat::Tar(_) => {
+            fs::create_dir_all(into_dir)?;
             let mut archive = tar::Archive::new(reader);
-            archive.unpack(into_dir)?;
+            for entry in archive.entries()? {
+              let mut entry = entry?;
+              let path = unpack_tar_entry(&mut entry, into_dir, &mut extraction)?;
+              extraction.progress(&path);
+            }
           }
           _ => unreachable!(),
         };
@@ -116,10 +251,9 @@ impl<'a> Extract<'a> {
       ArchiveFormat::Zip => {
         let mut archive = zip::ZipArchive::new(source)?;
         for i in 0..archive.len() {
-          let mut file = archive.by_index(i)?;
-          let path = into_dir.join(file.name());
-          let mut output = fs::File::create(path)?;
-          io::copy(&mut file, &mut output)?;
+          let file = archive.by_index(i)?;
+          let path = unpack_zip_entry(file, into_dir, &mut extraction)?;
+          extraction.progress(&path);
         }
       }
     };
@@ -139,10 +273,14 @@ impl<'a> Extract<'a> {
     let archive = self
       .archive_format
       .unwrap_or_else(|| detect_archive_type(&self.source));
+    let mut extraction = Extraction {
+      extract: self,
+      extracted: 0,
+    };
 
     match archive {
       ArchiveFormat::Plain(compression) | ArchiveFormat::Tar(compression) => {
-        let mut reader = Self::get_archive_reader(source, compression);
+        let reader = Self::get_archive_reader(source, compression)?;
 
         match archive {
           ArchiveFormat::Plain(_) => {
@@ -158,8 +296,9 @@ impl<'a> Extract<'a> {
               crate::api::Error::Extract("Extractor source has no file-name".into())
             })?;
             let out_path = into_dir.join(file_name);
-            let mut out_file = fs::File::create(&out_path)?;
-            io::copy(&mut reader, &mut out_file)?;
+            let out_file = fs::File::create(&out_path)?;
+            extraction.copy(reader, out_file)?;
+            extraction.progress(path::Path::new(file_name));
           }
           ArchiveFormat::Tar(_) => {
             let mut archive = tar::Archive::new(reader);
@@ -173,7 +312,8 @@ impl<'a> Extract<'a> {
                   file_to_extract
                 ))
               })?;
-            entry.unpack_in(into_dir)?;
+            let path = unpack_tar_entry(&mut entry, into_dir, &mut extraction)?;
+            extraction.progress(&path);
           }
           _ => {
             panic!("Unreasonable code");
@@ -182,15 +322,219 @@ impl<'a> Extract<'a> {
       }
       ArchiveFormat::Zip => {
         let mut archive = zip::ZipArchive::new(source)?;
-        let mut file = archive.by_name(
+        let file = archive.by_name(
           file_to_extract
             .to_str()
             .expect("Could not convert file to str"),
         )?;
-        let mut output = fs::File::create(into_dir.join(file.name()))?;
-        io::copy(&mut file, &mut output)?;
+        let path = unpack_zip_entry(file, into_dir, &mut extraction)?;
+        extraction.progress(&path);
       }
     };
     Ok(())
   }
 }
+
+/// Unpacks a tar entry inside `into_dir`, returning its relative path.
+fn unpack_tar_entry<R: Read>(
+  entry: &mut tar::Entry<'_, R>,
+  into_dir: &path::Path,
+  extraction: &mut Extraction<'_, '_>,
+) -> crate::api::Result<PathBuf> {
+  let path = entry.path()?.into_owned();
+  let relative = enclosed_path(&path).ok_or_else(|| {
+    crate::api::Error::Extract(format!(
+      "archive entry {} escapes the destination directory",
+      path.display()
+    ))
+  })?;
+
+  let kind = entry.header().entry_type();
+  if kind.is_symlink() || kind.is_hard_link() {
+    let target = entry.link_name()?.ok_or_else(|| {
+      crate::api::Error::Extract(format!("link {} has no target", path.display()))
+    })?;
+    // symbolic links are relative to their parent directory, hard links to the archive root
+    let resolved = if kind.is_symlink() {
+      relative
+        .parent()
+        .unwrap_or_else(|| path::Path::new(""))
+        .join(&target)
+    } else {
+      target.into_owned()
+    };
+    if enclosed_path(&resolved).is_none() {
+      return Err(crate::api::Error::Extract(format!(
+        "link {} points outside of the destination directory",
+        path.display()
+      )));
+    }
+  }
+
+  extraction.reserve(entry.size())?;
+  // `unpack_in` also makes sure the parent directory doesn't resolve outside of `into_dir`
+  entry.unpack_in(into_dir)?;
+  Ok(relative)
+}
+
+/// Unpacks a zip entry inside `into_dir`, returning its relative path.
+fn unpack_zip_entry(
+  mut file: zip::read::ZipFile<'_>,
+  into_dir: &path::Path,
+  extraction: &mut Extraction<'_, '_>,
+) -> crate::api::Result<PathBuf> {
+  let relative = file
+    .enclosed_name()
+    .and_then(enclosed_path)
+    .ok_or_else(|| {
+      crate::api::Error::Extract(format!(
+        "archive entry {} escapes the destination directory",
+        file.name()
+      ))
+    })?;
+  let path = into_dir.join(&relative);
+
+  if file.is_dir() {
+    fs::create_dir_all(&path)?;
+    ensure_inside(into_dir, &path)?;
+  } else {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+      ensure_inside(into_dir, parent)?;
+    }
+    let output = fs::File::create(&path)?;
+    extraction.copy(&mut file, output)?;
+    #[cfg(unix)]
+    if let Some(mode) = file.unix_mode() {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777))?;
+    }
+  }
+  Ok(relative)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::api::file::Archive;
+
+  fn write_fixture(dir: &path::Path) {
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("root.txt"), b"root file").unwrap();
+    fs::write(dir.join("nested").join("child.txt"), b"nested file").unwrap();
+  }
+
+  fn check_roundtrip(file_name: &str) {
+    let source = tempfile::tempdir().unwrap();
+    write_fixture(source.path());
+
+    let target = tempfile::tempdir().unwrap();
+    let archive_path = target.path().join(file_name);
+    Archive::from_directory(source.path())
+      .create(&archive_path)
+      .unwrap();
+
+    let out = target.path().join("out");
+    let entries = std::cell::RefCell::new(Vec::new());
+    Extract::from_source(&archive_path)
+      .on_progress(|entry, _| entries.borrow_mut().push(entry.to_path_buf()))
+      .extract_into(&out)
+      .unwrap();
+
+    assert_eq!(fs::read(out.join("root.txt")).unwrap(), b"root file");
+    assert_eq!(
+      fs::read(out.join("nested").join("child.txt")).unwrap(),
+      b"nested file"
+    );
+    assert!(entries.borrow().contains(&PathBuf::from("root.txt")));
+  }
+
+  #[test]
+  fn check_zip_roundtrip() {
+    check_roundtrip("archive.zip");
+  }
+
+  #[test]
+  fn check_tar_gz_roundtrip() {
+    check_roundtrip("archive.tar.gz");
+  }
+
+  #[test]
+  fn check_tar_zst_roundtrip() {
+    check_roundtrip("archive.tar.zst");
+  }
+
+  #[test]
+  fn check_tar_xz_roundtrip() {
+    check_roundtrip("archive.tar.xz");
+  }
+
+  #[test]
+  fn check_detect_archive_type() {
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.tar.gz")),
+      ArchiveFormat::Tar(Some(Compression::Gz))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.tar.zst")),
+      ArchiveFormat::Tar(Some(Compression::Zstd))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.xz")),
+      ArchiveFormat::Plain(Some(Compression::Xz))
+    );
+    assert_eq!(
+      detect_archive_type(path::Path::new("app.zip")),
+      ArchiveFormat::Zip
+    );
+  }
+
+  #[test]
+  fn check_enclosed_path() {
+    assert_eq!(
+      enclosed_path(path::Path::new("./foo/../bar")),
+      Some(PathBuf::from("bar"))
+    );
+    assert_eq!(enclosed_path(path::Path::new("../bar")), None);
+    assert_eq!(enclosed_path(path::Path::new("foo/../../bar")), None);
+    assert_eq!(enclosed_path(path::Path::new("/etc/passwd")), None);
+  }
+
+  #[test]
+  fn check_zip_slip() {
+    let target = tempfile::tempdir().unwrap();
+    let archive_path = target.path().join("evil.zip");
+    {
+      let mut zip = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+      zip
+        .start_file("../evil.txt", zip::write::FileOptions::default())
+        .unwrap();
+      zip.write_all(b"evil").unwrap();
+      zip.finish().unwrap();
+    }
+
+    let out = target.path().join("out");
+    let res = Extract::from_source(&archive_path).extract_into(&out);
+    assert!(res.is_err());
+    assert!(!target.path().join("evil.txt").exists());
+  }
+
+  #[test]
+  fn check_size_limit() {
+    let source = tempfile::tempdir().unwrap();
+    write_fixture(source.path());
+
+    let target = tempfile::tempdir().unwrap();
+    for file_name in &["archive.zip", "archive.tar.gz"] {
+      let archive_path = target.path().join(file_name);
+      Archive::from_directory(source.path())
+        .create(&archive_path)
+        .unwrap();
+
+      let res = Extract::from_source(&archive_path)
+        .size_limit(10)
+        .extract_into(&target.path().join("out"));
+      assert!(res.is_err());
+    }
+  }
+}
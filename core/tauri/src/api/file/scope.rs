@@ -0,0 +1,408 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::api::{
+  config::FsAllowlistConfig,
+  path::{resolve_path, BaseDirectory},
+};
+use serde::{Deserialize, Serialize};
+
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Component, Path, PathBuf},
+  sync::{Arc, Mutex},
+};
+
+/// How long a path granted with [`Scope::grant`] stays accessible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GrantLifetime {
+  /// The path is accessible to the given window, until it is closed.
+  Window,
+  /// The path is accessible to all windows, and persisted across restarts.
+  Persisted,
+}
+
+/// A path granted at runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Grant {
+  path: PathBuf,
+  /// Whether everything under the path is granted too.
+  recursive: bool,
+}
+
+impl Grant {
+  fn allows(&self, path: &Path) -> bool {
+    if self.recursive {
+      path.starts_with(&self.path)
+    } else {
+      path == self.path
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+struct Grants {
+  /// The paths granted to each window, keyed by the window label.
+  windows: HashMap<String, Vec<Grant>>,
+  persisted: Vec<Grant>,
+}
+
+/// The paths the webview can access with the file system API.
+///
+/// The scope is declared on `tauri > allowlist > fs > scope` and extended at runtime with
+/// [`Scope::grant`], e.g. with the paths the user picks on the native dialogs.
+/// The paths are compared after resolving their `.` and `..` components and the symbolic links
+/// of their existing part, so links can't lead out of the scope.
+/// The file storing the persisted grants is never accessible, and the directories containing it
+/// can't be created, renamed or removed, so the webview can't grant itself access to other paths.
+#[derive(Debug, Clone)]
+pub struct Scope {
+  /// The paths allowed by the config, `None` if all paths are allowed.
+  allowed: Option<Vec<PathBuf>>,
+  /// The file storing the persisted grants.
+  persist_path: Option<PathBuf>,
+  grants: Arc<Mutex<Grants>>,
+}
+
+impl Scope {
+  /// Creates the scope of the given allowlist, loading the grants persisted on `persist_path`.
+  pub fn new(config: &FsAllowlistConfig, persist_path: Option<PathBuf>) -> Self {
+    let allowed = config.scope.as_ref().map(|scope| {
+      scope
+        .iter()
+        .filter_map(|entry| match resolve_entry(entry) {
+          Ok(path) => Some(canonical(&path)),
+          Err(e) => {
+            log::warn!("ignoring fs scope entry `{}`: {}", entry, e);
+            None
+          }
+        })
+        .collect()
+    });
+    let persist_path = persist_path.map(|path| canonical(&path));
+    let persisted = persist_path
+      .as_ref()
+      .map(|path| load_grants(path))
+      .unwrap_or_default();
+    Self {
+      allowed,
+      persist_path,
+      grants: Arc::new(Mutex::new(Grants {
+        windows: Default::default(),
+        persisted,
+      })),
+    }
+  }
+
+  /// Whether the scope restricts the accessible paths. If not, all paths are accessible.
+  pub fn is_restricted(&self) -> bool {
+    self.allowed.is_some()
+  }
+
+  /// Whether the window with the given label can read the path.
+  pub fn is_allowed(&self, label: &str, path: impl AsRef<Path>) -> bool {
+    self.allows(label, path.as_ref(), false)
+  }
+
+  /// Whether the window with the given label can create, write, rename or remove the path.
+  pub fn is_writable(&self, label: &str, path: impl AsRef<Path>) -> bool {
+    self.allows(label, path.as_ref(), true)
+  }
+
+  /// Returns an error if the window with the given label can't read the path.
+  pub fn check(&self, label: &str, path: impl AsRef<Path>) -> crate::api::Result<()> {
+    if self.is_allowed(label, &path) {
+      Ok(())
+    } else {
+      Err(crate::api::Error::PathNotAllowed(
+        path.as_ref().to_path_buf(),
+      ))
+    }
+  }
+
+  /// Returns an error if the window with the given label can't create, write, rename or remove the path.
+  pub fn check_write(&self, label: &str, path: impl AsRef<Path>) -> crate::api::Result<()> {
+    if self.is_writable(label, &path) {
+      Ok(())
+    } else {
+      Err(crate::api::Error::PathNotAllowed(
+        path.as_ref().to_path_buf(),
+      ))
+    }
+  }
+
+  fn allows(&self, label: &str, path: &Path, write: bool) -> bool {
+    let path = match absolute(path) {
+      Ok(path) => canonical(&path),
+      Err(_) => return false,
+    };
+    if let Some(persist_path) = &self.persist_path {
+      // replacing a directory containing the file would replace the file too
+      if path == *persist_path || (write && persist_path.starts_with(&path)) {
+        return false;
+      }
+    }
+    let allowed = match &self.allowed {
+      Some(allowed) => allowed,
+      None => return true,
+    };
+    if allowed.iter().any(|allowed| path.starts_with(allowed)) {
+      return true;
+    }
+    let grants = self.grants();
+    grants.persisted.iter().any(|grant| grant.allows(&path))
+      || grants.windows.get(label).map_or(false, |granted| {
+        granted.iter().any(|grant| grant.allows(&path))
+      })
+  }
+
+  /// Grants access to the path, and everything under it if `recursive` is set.
+  ///
+  /// Window grants must be revoked with [`Scope::revoke_window`] once the window is closed.
+  /// Nothing is granted if the scope is not restricted.
+  pub fn grant(
+    &self,
+    label: &str,
+    path: impl AsRef<Path>,
+    recursive: bool,
+    lifetime: GrantLifetime,
+  ) -> crate::api::Result<()> {
+    if !self.is_restricted() {
+      return Ok(());
+    }
+    let grant = Grant {
+      path: canonical(&absolute(path.as_ref())?),
+      recursive,
+    };
+    let mut grants = self.grants();
+    match lifetime {
+      GrantLifetime::Window => {
+        grants
+          .windows
+          .entry(label.to_string())
+          .or_default()
+          .push(grant);
+      }
+      GrantLifetime::Persisted => {
+        if grants.persisted.contains(&grant) {
+          return Ok(());
+        }
+        grants.persisted.push(grant);
+        if let Some(persist_path) = &self.persist_path {
+          if let Some(parent) = persist_path.parent() {
+            fs::create_dir_all(parent)?;
+          }
+          fs::write(persist_path, serde_json::to_vec(&grants.persisted)?)?;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Revokes the paths granted to the window with the given label.
+  pub fn revoke_window(&self, label: &str) {
+    self.grants().windows.remove(label);
+  }
+
+  fn grants(&self) -> std::sync::MutexGuard<'_, Grants> {
+    self.grants.lock().expect("poisoned fs scope grants")
+  }
+}
+
+/// Resolves the base directory variable a scope entry may start with, e.g. `$APP/data`.
+fn resolve_entry(entry: &str) -> crate::api::Result<PathBuf> {
+  let variable = match entry.strip_prefix('$') {
+    Some(variable) => variable,
+    None => return Ok(PathBuf::from(entry)),
+  };
+  let (name, path) = match variable.find(|c| c == '/' || c == '\\') {
+    Some(index) => (&variable[..index], &variable[index + 1..]),
+    None => (variable, ""),
+  };
+  let dir = match name {
+    "AUDIO" => BaseDirectory::Audio,
+    "CACHE" => BaseDirectory::Cache,
+    "CONFIG" => BaseDirectory::Config,
+    "DATA" => BaseDirectory::Data,
+    "LOCALDATA" => BaseDirectory::LocalData,
+    "DESKTOP" => BaseDirectory::Desktop,
+    "DOCUMENT" => BaseDirectory::Document,
+    "DOWNLOAD" => BaseDirectory::Download,
+    "EXE" => BaseDirectory::Executable,
+    "FONT" => BaseDirectory::Font,
+    "HOME" => BaseDirectory::Home,
+    "PICTURE" => BaseDirectory::Picture,
+    "PUBLIC" => BaseDirectory::Public,
+    "RUNTIME" => BaseDirectory::Runtime,
+    "TEMPLATE" => BaseDirectory::Template,
+    "VIDEO" => BaseDirectory::Video,
+    "RESOURCE" => BaseDirectory::Resource,
+    "APP" => BaseDirectory::App,
+    "LOG" => BaseDirectory::Log,
+    "CWD" => BaseDirectory::Current,
+    _ => {
+      return Err(crate::api::Error::Path(format!(
+        "unknown base directory variable `${}`",
+        name
+      )))
+    }
+  };
+  resolve_path(path, Some(dir))
+}
+
+/// Loads the persisted grants, ignoring the file if it isn't a regular file or holds relative paths.
+fn load_grants(path: &Path) -> Vec<Grant> {
+  match fs::symlink_metadata(path) {
+    Ok(metadata) if metadata.is_file() => {}
+    Ok(_) => {
+      log::warn!(
+        "ignoring the fs scope grants on `{}`: not a regular file",
+        path.display()
+      );
+      return Vec::new();
+    }
+    Err(_) => return Vec::new(),
+  }
+  let grants: Vec<Grant> = match fs::read(path)
+    .ok()
+    .and_then(|contents| serde_json::from_slice(&contents).ok())
+  {
+    Some(grants) => grants,
+    None => {
+      log::warn!(
+        "ignoring the fs scope grants on `{}`: invalid file",
+        path.display()
+      );
+      return Vec::new();
+    }
+  };
+  if grants.iter().any(|grant| !grant.path.is_absolute()) {
+    log::warn!(
+      "ignoring the fs scope grants on `{}`: relative path",
+      path.display()
+    );
+    return Vec::new();
+  }
+  grants
+}
+
+/// Makes the path absolute, relative to the current directory.
+fn absolute(path: &Path) -> crate::api::Result<PathBuf> {
+  if path.is_absolute() {
+    Ok(path.to_path_buf())
+  } else {
+    Ok(std::env::current_dir()?.join(path))
+  }
+}
+
+/// Resolves the symbolic links and the `.` and `..` components of the existing part of the
+/// absolute path, then the `.` and `..` components of the rest, which can't be links.
+fn canonical(path: &Path) -> PathBuf {
+  for ancestor in path.ancestors() {
+    if let Ok(existing) = ancestor.canonicalize() {
+      let rest = path
+        .strip_prefix(ancestor)
+        .unwrap_or_else(|_| Path::new(""));
+      return normalize(&existing.join(rest));
+    }
+  }
+  normalize(path)
+}
+
+/// Resolves the `.` and `..` components of the path, without accessing the file system.
+fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        normalized.pop();
+      }
+      component => normalized.push(component.as_os_str()),
+    }
+  }
+  normalized
+}
+
+#[cfg(test)]
+mod test {
+  use super::{GrantLifetime, Scope};
+  use crate::api::config::FsAllowlistConfig;
+
+  fn scope(entries: &[&str], persist_path: Option<std::path::PathBuf>) -> Scope {
+    Scope::new(
+      &FsAllowlistConfig {
+        scope: Some(entries.iter().map(|e| e.to_string()).collect()),
+      },
+      persist_path,
+    )
+  }
+
+  #[test]
+  fn check_scope() {
+    let unrestricted = Scope::new(&FsAllowlistConfig { scope: None }, None);
+    assert!(unrestricted.is_allowed("main", "/etc/passwd"));
+
+    let scope = scope(&["/data"], None);
+    assert!(scope.is_allowed("main", "/data"));
+    assert!(scope.is_allowed("main", "/data/notes/todo.txt"));
+    assert!(!scope.is_allowed("main", "/database"));
+    assert!(!scope.is_allowed("main", "/data/../etc/passwd"));
+    assert!(scope.check("main", "/etc/passwd").is_err());
+  }
+
+  #[test]
+  fn check_grants() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let persist_path = dir.path().join("fs-scope.json");
+    let scope = scope(&["/data"], Some(persist_path.clone()));
+
+    scope
+      .grant("main", "/home/user/doc.txt", false, GrantLifetime::Window)
+      .unwrap();
+    assert!(scope.is_allowed("main", "/home/user/doc.txt"));
+    assert!(!scope.is_allowed("main", "/home/user/other.txt"));
+    assert!(!scope.is_allowed("other", "/home/user/doc.txt"));
+    scope.revoke_window("main");
+    assert!(!scope.is_allowed("main", "/home/user/doc.txt"));
+
+    scope
+      .grant("main", "/home/user/photos", true, GrantLifetime::Persisted)
+      .unwrap();
+    assert!(scope.is_allowed("other", "/home/user/photos/cat.png"));
+
+    // persisted grants are loaded again
+    let reloaded = self::scope(&["/data"], Some(persist_path.clone()));
+    assert!(reloaded.is_allowed("main", "/home/user/photos/cat.png"));
+
+    // the persisted grants can't be edited, even if their directory is on the scope
+    let scope = self::scope(&[dir.path().to_str().unwrap()], Some(persist_path.clone()));
+    assert!(scope.is_allowed("main", dir.path().join("notes.txt")));
+    assert!(!scope.is_allowed("main", &persist_path));
+    let unrestricted = Scope::new(&FsAllowlistConfig { scope: None }, Some(persist_path));
+    assert!(!unrestricted.is_allowed("main", dir.path().join("./fs-scope.json")));
+
+    // nor replaced by renaming or removing a directory containing them
+    assert!(scope.is_writable("main", dir.path().join("notes.txt")));
+    assert!(scope.is_allowed("main", dir.path()));
+    assert!(!scope.is_writable("main", dir.path()));
+    assert!(!scope.is_writable("main", dir.path().parent().unwrap()));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn check_symlinks() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let data = dir.path().join("data");
+    std::fs::create_dir(&data).unwrap();
+    std::os::unix::fs::symlink("/etc", data.join("etc")).unwrap();
+    let scope = scope(&[data.to_str().unwrap()], None);
+
+    assert!(scope.is_allowed("main", data.join("notes.txt")));
+    assert!(!scope.is_allowed("main", data.join("etc/passwd")));
+    assert!(!scope.is_writable("main", data.join("etc/new.txt")));
+  }
+}
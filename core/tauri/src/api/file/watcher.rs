@@ -0,0 +1,95 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher as _};
+use serde::Serialize;
+
+use std::{
+  path::{Path, PathBuf},
+  sync::mpsc::channel,
+  thread,
+  time::Duration,
+};
+
+/// A debounced file system change event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum WatchEvent {
+  /// A file or directory was created.
+  Create(PathBuf),
+  /// A file was written to.
+  Write(PathBuf),
+  /// The attributes of a file or directory changed.
+  Chmod(PathBuf),
+  /// A file or directory was removed.
+  Remove(PathBuf),
+  /// A file or directory was moved from the first path to the second path.
+  Rename(PathBuf, PathBuf),
+  /// The watched paths must be scanned again since some events may have been lost.
+  Rescan,
+  /// The watcher encountered an error.
+  Error(String),
+}
+
+/// A file system watcher. Dropping it stops watching.
+///
+/// # Example
+/// ```no_run
+/// use tauri::api::file::watch;
+/// use std::time::Duration;
+///
+/// let watcher = watch("/path/to/dir", true, Duration::from_millis(500), |event| {
+///   println!("{:?}", event);
+/// })
+/// .expect("failed to watch path");
+/// ```
+pub struct Watcher {
+  _inner: RecommendedWatcher,
+}
+
+/// Watches the given path, calling `handler` with debounced change events.
+///
+/// The `delay` is the time frame used to merge events emitted for the same path.
+/// If `recursive` is true, all subdirectories of `path` are also watched.
+pub fn watch<P, F>(
+  path: P,
+  recursive: bool,
+  delay: Duration,
+  handler: F,
+) -> crate::api::Result<Watcher>
+where
+  P: AsRef<Path>,
+  F: Fn(WatchEvent) + Send + 'static,
+{
+  let (tx, rx) = channel();
+  let mut watcher: RecommendedWatcher = notify::watcher(tx, delay)?;
+  watcher.watch(
+    path,
+    if recursive {
+      RecursiveMode::Recursive
+    } else {
+      RecursiveMode::NonRecursive
+    },
+  )?;
+
+  // the channel is closed when the watcher is dropped, ending the thread.
+  thread::spawn(move || {
+    for event in rx {
+      let event = match event {
+        DebouncedEvent::Create(path) => WatchEvent::Create(path),
+        DebouncedEvent::Write(path) => WatchEvent::Write(path),
+        DebouncedEvent::Chmod(path) => WatchEvent::Chmod(path),
+        DebouncedEvent::Remove(path) => WatchEvent::Remove(path),
+        DebouncedEvent::Rename(from, to) => WatchEvent::Rename(from, to),
+        DebouncedEvent::Rescan => WatchEvent::Rescan,
+        DebouncedEvent::Error(error, _) => WatchEvent::Error(error.to_string()),
+        // the notice events are not debounced
+        DebouncedEvent::NoticeWrite(_) | DebouncedEvent::NoticeRemove(_) => continue,
+      };
+      handler(event);
+    }
+  });
+
+  Ok(Watcher { _inner: watcher })
+}
@@ -43,6 +43,10 @@ pub use clap;
 #[cfg(global_shortcut_all)]
 pub mod shortcuts;
 
+/// The clipboard API module allows you to read and write text and images.
+#[cfg(clipboard)]
+pub mod clipboard;
+
 /// The desktop notifications API module.
 #[cfg(notification_all)]
 pub mod notification;
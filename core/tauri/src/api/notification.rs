@@ -2,8 +2,67 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use crate::api::path::{resolve_path, BaseDirectory};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 #[cfg(windows)]
 use std::path::MAIN_SEPARATOR;
+use std::{
+  fs::{create_dir_all, read_to_string, write},
+  path::{Path, PathBuf},
+  sync::{Arc, Condvar, Mutex},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The file storing the scheduled notifications, inside the app directory.
+const SCHEDULE_FILE: &str = ".tauri-notifications.json";
+
+/// The urgency level of a notification.
+///
+/// Only used on Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Urgency {
+  /// Low urgency, e.g. a background task finished.
+  Low,
+  /// Normal urgency, the default.
+  Normal,
+  /// Critical urgency, the notification doesn't expire.
+  Critical,
+}
+
+/// An action button of a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Action {
+  /// The action identifier, reported by [`NotificationEvent::Action`].
+  pub id: String,
+  /// The button label.
+  pub title: String,
+}
+
+/// A user interaction with a notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "action", rename_all = "camelCase")]
+pub enum NotificationEvent {
+  /// The notification was clicked.
+  Clicked,
+  /// An action button was clicked, with the action identifier.
+  Action(String),
+  /// The notification was dismissed or expired.
+  Closed,
+}
+
+impl NotificationEvent {
+  /// Maps an action reported by the notification server to an event.
+  #[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+  fn from_action(action: &str) -> Self {
+    match action {
+      "default" => Self::Clicked,
+      "__closed" => Self::Closed,
+      action => Self::Action(action.to_string()),
+    }
+  }
+}
 
 /// The Notification definition.
 /// Allows you to construct a Notification data and send it.
@@ -18,7 +77,7 @@ use std::path::MAIN_SEPARATOR;
 ///   .show();
 /// ```
 #[allow(dead_code)]
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Notification {
   /// The notification body.
   body: Option<String>,
@@ -28,6 +87,17 @@ pub struct Notification {
   icon: Option<String>,
   /// The notification identifier
   identifier: String,
+  /// The id of the notification, replacing the shown notification with the same id.
+  id: Option<u32>,
+  /// The action buttons.
+  #[serde(default)]
+  actions: Vec<Action>,
+  /// The urgency level.
+  urgency: Option<Urgency>,
+  /// The name of the sound played with the notification.
+  sound: Option<String>,
+  /// How long the notification is shown.
+  timeout: Option<Duration>,
 }
 
 impl Notification {
@@ -57,17 +127,75 @@ impl Notification {
     self
   }
 
-  /// Shows the notification.
-  pub fn show(self) -> crate::api::Result<()> {
+  /// Sets the notification id. Showing a notification replaces the one with the same id.
+  ///
+  /// Replacing notifications is only supported on Linux.
+  pub fn id(mut self, id: u32) -> Self {
+    self.id = Some(id);
+    self
+  }
+
+  /// Adds an action button to the notification.
+  ///
+  /// Actions are only supported on Linux.
+  pub fn action(mut self, id: impl Into<String>, title: impl Into<String>) -> Self {
+    self.actions.push(Action {
+      id: id.into(),
+      title: title.into(),
+    });
+    self
+  }
+
+  /// Sets the notification urgency.
+  pub fn urgency(mut self, urgency: Urgency) -> Self {
+    self.urgency = Some(urgency);
+    self
+  }
+
+  /// Sets the name of the sound played with the notification, e.g. `message-new-instant`.
+  pub fn sound(mut self, sound: impl Into<String>) -> Self {
+    self.sound = Some(sound.into());
+    self
+  }
+
+  /// Sets how long the notification is shown.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  fn build(&self) -> crate::api::Result<notify_rust::Notification> {
     let mut notification = notify_rust::Notification::new();
-    if let Some(body) = self.body {
-      notification.body(&body);
+    if let Some(body) = &self.body {
+      notification.body(body);
+    }
+    if let Some(title) = &self.title {
+      notification.summary(title);
+    }
+    if let Some(icon) = &self.icon {
+      notification.icon(icon);
     }
-    if let Some(title) = self.title {
-      notification.summary(&title);
+    if let Some(sound) = &self.sound {
+      notification.sound_name(sound);
     }
-    if let Some(icon) = self.icon {
-      notification.icon(&icon);
+    if let Some(timeout) = self.timeout {
+      notification.timeout(timeout.as_millis() as i32);
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+      if let Some(id) = self.id {
+        notification.id(id);
+      }
+      for action in &self.actions {
+        notification.action(&action.id, &action.title);
+      }
+      if let Some(urgency) = self.urgency {
+        notification.urgency(match urgency {
+          Urgency::Low => notify_rust::Urgency::Low,
+          Urgency::Normal => notify_rust::Urgency::Normal,
+          Urgency::Critical => notify_rust::Urgency::Critical,
+        });
+      }
     }
     #[cfg(windows)]
     {
@@ -81,7 +209,287 @@ impl Notification {
         notification.app_id(&self.identifier);
       }
     }
-    notification.show()?;
+    Ok(notification)
+  }
+
+  /// Shows the notification.
+  pub fn show(self) -> crate::api::Result<()> {
+    self.build()?.show()?;
+    Ok(())
+  }
+
+  /// Shows the notification, calling `handler` on a separate thread once the user interacts
+  /// with it.
+  ///
+  /// Interactions are only reported on Linux, the handler is never called on other platforms.
+  pub fn show_with_handler<F: FnOnce(NotificationEvent) + Send + 'static>(
+    self,
+    handler: F,
+  ) -> crate::api::Result<()> {
+    let handle = self.build()?.show()?;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    std::thread::spawn(move || {
+      handle.wait_for_action(|action| handler(NotificationEvent::from_action(action)))
+    });
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = (handle, handler);
     Ok(())
   }
+
+  /// Schedules the notification to be shown at the given time, returning its id.
+  ///
+  /// Scheduled notifications are persisted, so they are shown even if the app restarts in between,
+  /// as long as [`start_scheduler`] runs. Uses the notification id if it is set.
+  /// The user interactions are reported to the handler set with [`on_scheduled_event`].
+  pub fn schedule(self, at: SystemTime) -> crate::api::Result<u32> {
+    let id = self.id.unwrap_or_else(rand::random);
+    let scheduled = ScheduledNotification {
+      id,
+      at: to_millis(at),
+      notification: self,
+    };
+    update_schedule(|notifications| {
+      notifications.retain(|n| n.id != id);
+      notifications.push(scheduled);
+    })?;
+    start_scheduler();
+    Ok(id)
+  }
+}
+
+/// A notification scheduled with [`Notification::schedule`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ScheduledNotification {
+  /// The notification id, used to cancel it.
+  pub id: u32,
+  /// When the notification is shown, in milliseconds since the Unix epoch.
+  pub at: u64,
+  /// The notification.
+  pub notification: Notification,
+}
+
+/// Cancels the scheduled notification with the given id.
+pub fn cancel(id: u32) -> crate::api::Result<()> {
+  update_schedule(|notifications| notifications.retain(|n| n.id != id))
+}
+
+/// The notifications that are scheduled and not shown yet.
+pub fn scheduled() -> crate::api::Result<Vec<ScheduledNotification>> {
+  let _lock = SCHEDULER
+    .lock
+    .lock()
+    .expect("poisoned notification scheduler");
+  read_schedule(&schedule_path()?)
+}
+
+/// Sets the handler called with the notification id when the user interacts with a scheduled
+/// notification, replacing the previous one.
+///
+/// Tauri sets it when the app starts, emitting the interactions to every window as the
+/// `tauri://notification` event.
+pub fn on_scheduled_event<F: Fn(u32, NotificationEvent) + Send + Sync + 'static>(handler: F) {
+  *SCHEDULER
+    .handler
+    .lock()
+    .expect("poisoned notification handler") = Some(Arc::new(handler));
+}
+
+/// Starts the thread showing the scheduled notifications once they are due, if not started yet.
+///
+/// Tauri calls it when the app starts, showing the notifications that got due while it wasn't
+/// running.
+pub fn start_scheduler() {
+  let mut started = SCHEDULER
+    .lock
+    .lock()
+    .expect("poisoned notification scheduler");
+  if !*started {
+    *started = true;
+    std::thread::spawn(run_scheduler);
+  }
+}
+
+/// The state of the scheduler thread.
+struct Scheduler {
+  /// Whether the thread was started, also guarding the schedule file.
+  lock: Mutex<bool>,
+  /// Notified when the schedule changes.
+  changed: Condvar,
+  /// Called when the user interacts with a scheduled notification.
+  handler: Mutex<Option<ScheduledEventHandler>>,
+}
+
+/// A closure called with the notification id when the user interacts with a scheduled notification.
+type ScheduledEventHandler = Arc<dyn Fn(u32, NotificationEvent) + Send + Sync>;
+
+static SCHEDULER: Lazy<Scheduler> = Lazy::new(|| Scheduler {
+  lock: Mutex::new(false),
+  changed: Condvar::new(),
+  handler: Mutex::new(None),
+});
+
+fn run_scheduler() {
+  let mut lock = SCHEDULER
+    .lock
+    .lock()
+    .expect("poisoned notification scheduler");
+  loop {
+    let now = to_millis(SystemTime::now());
+    let next = schedule_path().and_then(|path| {
+      let mut notifications = read_schedule(&path)?;
+      let due = take_due(&mut notifications, now);
+      if !due.is_empty() {
+        write_schedule(&path, &notifications)?;
+      }
+      let handler = SCHEDULER
+        .handler
+        .lock()
+        .expect("poisoned notification handler")
+        .clone();
+      for scheduled in due {
+        let id = scheduled.id;
+        let _ = match handler.clone() {
+          Some(handler) => scheduled
+            .notification
+            .show_with_handler(move |event| handler(id, event)),
+          None => scheduled.notification.show(),
+        };
+      }
+      Ok(next_due(&notifications))
+    });
+    lock = match next {
+      Ok(Some(at)) => {
+        let timeout = Duration::from_millis(at.saturating_sub(now));
+        SCHEDULER
+          .changed
+          .wait_timeout(lock, timeout)
+          .expect("poisoned notification scheduler")
+          .0
+      }
+      // nothing to show until the schedule changes
+      _ => SCHEDULER
+        .changed
+        .wait(lock)
+        .expect("poisoned notification scheduler"),
+    };
+  }
+}
+
+/// Updates the schedule file and wakes the scheduler thread up.
+fn update_schedule<F: FnOnce(&mut Vec<ScheduledNotification>)>(
+  update: F,
+) -> crate::api::Result<()> {
+  let _lock = SCHEDULER
+    .lock
+    .lock()
+    .expect("poisoned notification scheduler");
+  let path = schedule_path()?;
+  let mut notifications = read_schedule(&path)?;
+  update(&mut notifications);
+  write_schedule(&path, &notifications)?;
+  SCHEDULER.changed.notify_all();
+  Ok(())
+}
+
+fn schedule_path() -> crate::api::Result<PathBuf> {
+  resolve_path(SCHEDULE_FILE, Some(BaseDirectory::App))
+}
+
+fn read_schedule(path: &Path) -> crate::api::Result<Vec<ScheduledNotification>> {
+  if path.exists() {
+    Ok(serde_json::from_str(&read_to_string(path)?)?)
+  } else {
+    Ok(Vec::new())
+  }
+}
+
+fn write_schedule(path: &Path, notifications: &[ScheduledNotification]) -> crate::api::Result<()> {
+  if let Some(parent) = path.parent() {
+    create_dir_all(parent)?;
+  }
+  write(path, serde_json::to_string(notifications)?)?;
+  Ok(())
+}
+
+/// Removes the notifications due at `now` from the schedule, returning them.
+fn take_due(
+  notifications: &mut Vec<ScheduledNotification>,
+  now: u64,
+) -> Vec<ScheduledNotification> {
+  let (due, pending): (Vec<_>, Vec<_>) = notifications.drain(..).partition(|n| n.at <= now);
+  *notifications = pending;
+  due
+}
+
+/// When the next scheduled notification is due.
+fn next_due(notifications: &[ScheduledNotification]) -> Option<u64> {
+  notifications.iter().map(|n| n.at).min()
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis() as u64)
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn scheduled(id: u32, at: u64) -> ScheduledNotification {
+    ScheduledNotification {
+      id,
+      at,
+      notification: Notification::new("studio.tauri.example").title(id.to_string()),
+    }
+  }
+
+  #[test]
+  fn check_take_due() {
+    let mut notifications = vec![scheduled(1, 30), scheduled(2, 10), scheduled(3, 20)];
+    let due = take_due(&mut notifications, 20);
+    assert_eq!(due.iter().map(|n| n.id).collect::<Vec<_>>(), vec![2, 3]);
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(next_due(&notifications), Some(30));
+    assert_eq!(next_due(&[]), None);
+  }
+
+  #[test]
+  fn check_schedule_roundtrip() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("app").join(SCHEDULE_FILE);
+    assert!(read_schedule(&path).unwrap().is_empty());
+
+    let mut notification = scheduled(7, 1000);
+    notification.notification = notification
+      .notification
+      .action("reply", "Reply")
+      .urgency(Urgency::Critical)
+      .timeout(Duration::from_secs(5));
+    write_schedule(&path, &[notification]).unwrap();
+
+    let notifications = read_schedule(&path).unwrap();
+    assert_eq!(notifications.len(), 1);
+    let restored = &notifications[0].notification;
+    assert_eq!(restored.actions[0].id, "reply");
+    assert_eq!(restored.urgency, Some(Urgency::Critical));
+    assert_eq!(restored.timeout, Some(Duration::from_secs(5)));
+  }
+
+  #[test]
+  fn check_event_from_action() {
+    assert_eq!(
+      NotificationEvent::from_action("default"),
+      NotificationEvent::Clicked
+    );
+    assert_eq!(
+      NotificationEvent::from_action("__closed"),
+      NotificationEvent::Closed
+    );
+    assert_eq!(
+      serde_json::to_value(NotificationEvent::from_action("reply")).unwrap(),
+      serde_json::json!({ "type": "action", "action": "reply" })
+    );
+  }
 }
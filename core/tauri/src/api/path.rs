@@ -56,6 +56,9 @@ pub enum BaseDirectory {
   App,
   /// The current working directory.
   Current,
+  /// The default App log directory.
+  /// Resolves to ${HOME}/Library/Logs/${APP_NAME} on macOS and ${CONFIG_DIR}/${APP_NAME}/logs elsewhere.
+  Log,
 }
 
 /// Resolves the path with the optional base directory.
@@ -92,6 +95,7 @@ pub fn resolve_path<P: AsRef<Path>>(
       BaseDirectory::Resource => resource_dir(),
       BaseDirectory::App => app_dir(),
       BaseDirectory::Current => Some(env::current_dir()?),
+      BaseDirectory::Log => log_dir(),
     };
     if let Some(mut base_dir_path_value) = base_dir_path {
       base_dir_path_value.push(path);
@@ -214,3 +218,16 @@ pub fn app_dir() -> Option<PathBuf> {
     }
   })
 }
+
+/// Returns the path to the suggested directory for your app log files.
+pub fn log_dir() -> Option<PathBuf> {
+  if cfg!(target_os = "macos") {
+    dirs_next::home_dir().and_then(|dir| {
+      app_name()
+        .ok()
+        .map(|app_name| dir.join("Library/Logs").join(app_name))
+    })
+  } else {
+    app_dir().map(|dir| dir.join("logs"))
+  }
+}
@@ -169,6 +169,80 @@ pub fn format_callback_result<T: Serialize, E: Serialize>(
   }
 }
 
+/// Formats a Javascript function that fetches raw bytes from the given URL
+/// and passes them to the success callback as an `Uint8Array`.
+/// If the request fails, the error callback is called with the error message.
+///
+/// * `url` the URL serving the bytes. Usually a `tauri://` custom protocol URL.
+/// * `success_callback` the function name of the Ok callback. Usually the `resolve` of the JS Promise.
+/// * `error_callback` the function name of the Err callback. Usually the `reject` of the JS Promise.
+///
+/// # Examples
+/// ```
+/// use tauri::api::rpc::format_binary_callback;
+/// let cb = format_binary_callback("tauri://app/data", "success_cb", "error_cb").expect("failed to format");
+/// assert!(cb.contains(r#"fetch("tauri://app/data")"#));
+/// assert!(cb.contains(r#"window["success_cb"](new Uint8Array(buffer))"#));
+/// ```
+pub fn format_binary_callback(
+  url: &str,
+  success_callback: impl AsRef<str>,
+  error_callback: impl AsRef<str>,
+) -> crate::api::Result<String> {
+  Ok(format!(
+    r#"
+      fetch({url})
+        .then(function (response) {{
+          if (!response.ok) {{
+            throw new Error('failed to fetch binary response');
+          }}
+          return response.arrayBuffer();
+        }})
+        .then(function (buffer) {{
+          window["{success}"](new Uint8Array(buffer))
+        }})
+        .catch(function (error) {{
+          window["{error}"](error.toString())
+        }})
+    "#,
+    url = serde_json::to_string(url)?,
+    success = success_callback.as_ref(),
+    error = error_callback.as_ref()
+  ))
+}
+
+/// Formats a Javascript function that passes the given bytes to the callback as an `Uint8Array`.
+///
+/// The bytes are inlined in the script as a base64 string, so this is meant for webviews
+/// that can't fetch them from the `tauri` custom protocol, e.g. pages served by a dev server.
+///
+/// * `function_name` the function name of the callback. Usually the `resolve` of the JS Promise.
+/// * `bytes` the bytes to pass to the callback.
+///
+/// # Examples
+/// ```
+/// use tauri::api::rpc::format_inline_binary_callback;
+/// let cb = format_inline_binary_callback("callback_function", &[1, 2, 3]);
+/// assert!(cb.contains(r#"atob("AQID")"#));
+/// assert!(cb.contains(r#"window["callback_function"](bytes)"#));
+/// ```
+pub fn format_inline_binary_callback(function_name: impl AsRef<str>, bytes: &[u8]) -> String {
+  format!(
+    r#"
+      (function () {{
+        var raw = atob("{bytes}")
+        var bytes = new Uint8Array(raw.length)
+        for (var i = 0; i < raw.length; i++) {{
+          bytes[i] = raw.charCodeAt(i)
+        }}
+        window["{fn}"](bytes)
+      }})()
+    "#,
+    fn = function_name.as_ref(),
+    bytes = base64::encode(bytes)
+  )
+}
+
 #[cfg(test)]
 mod test {
   use crate::api::rpc::*;
@@ -2,6 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use crate::api::{
+  command::Command,
+  config::{ShellAllowedArg, ShellAllowedCommand, ShellAllowlistConfig},
+};
+use regex::Regex;
+use std::collections::HashMap;
+
 /// Open path or URL with `with`, or system default
 pub fn open(path: String, with: Option<String>) -> crate::api::Result<()> {
   {
@@ -25,3 +32,226 @@ pub fn open(path: String, with: Option<String>) -> crate::api::Result<()> {
     }
   }
 }
+
+/// A variable argument validator, which must match the whole argument.
+fn validator_regex(validator: &str) -> Result<Regex, regex::Error> {
+  Regex::new(&format!("^(?:{})$", validator))
+}
+
+/// An argument of a scope command.
+#[derive(Debug, Clone)]
+enum AllowedArg {
+  Fixed(String),
+  Var { validator: String, regex: Regex },
+}
+
+/// A command of the scope, with its validators compiled.
+#[derive(Debug, Clone)]
+struct AllowedCommand {
+  name: String,
+  cmd: String,
+  args: Vec<AllowedArg>,
+  sidecar: bool,
+  env: Vec<String>,
+  clear_env: bool,
+  cwd: Option<String>,
+}
+
+/// The commands the webview is allowed to execute, declared on `tauri > allowlist > shell > scope`.
+#[derive(Debug, Clone, Default)]
+pub struct Scope(Vec<AllowedCommand>);
+
+impl Scope {
+  /// Creates the scope of the given allowlist, failing if a validator is not a valid regex.
+  pub fn new(config: &ShellAllowlistConfig) -> crate::api::Result<Self> {
+    config
+      .scope
+      .iter()
+      .map(|command| {
+        let args = command
+          .args
+          .iter()
+          .map(|arg| match arg {
+            ShellAllowedArg::Fixed(fixed) => Ok(AllowedArg::Fixed(fixed.clone())),
+            ShellAllowedArg::Var { validator } => validator_regex(validator)
+              .map(|regex| AllowedArg::Var {
+                validator: validator.clone(),
+                regex,
+              })
+              .map_err(|e| {
+                crate::api::Error::InvalidScope(format!(
+                  "validator `{}` of command `{}` is not a valid regex: {}",
+                  validator, command.name, e
+                ))
+              }),
+          })
+          .collect::<crate::api::Result<_>>()?;
+        Ok(AllowedCommand {
+          name: command.name.clone(),
+          cmd: command.cmd.clone(),
+          args,
+          sidecar: command.sidecar,
+          env: command.env.clone(),
+          clear_env: command.clear_env,
+          cwd: command.cwd.clone(),
+        })
+      })
+      .collect::<crate::api::Result<_>>()
+      .map(Self)
+  }
+
+  /// Prepares the command with the given name, if `args` match the arguments it is allowed with
+  /// and `env` only sets the environment variables it allows.
+  pub fn prepare(
+    &self,
+    name: &str,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    sidecar: bool,
+  ) -> crate::api::Result<Command> {
+    let allowed = self
+      .0
+      .iter()
+      .find(|command| command.name == name && command.sidecar == sidecar)
+      .ok_or_else(|| crate::api::Error::ProgramNotAllowed(name.to_string()))?;
+    let invalid = |reason: String| crate::api::Error::InvalidArgs {
+      command: name.to_string(),
+      reason,
+    };
+
+    if args.len() != allowed.args.len() {
+      return Err(invalid(format!(
+        "expected {} arguments, got {}",
+        allowed.args.len(),
+        args.len()
+      )));
+    }
+    for (index, (arg, allowed_arg)) in args.iter().zip(&allowed.args).enumerate() {
+      match allowed_arg {
+        AllowedArg::Fixed(fixed) => {
+          if arg != fixed {
+            return Err(invalid(format!(
+              "argument {} must be `{}`, got `{}`",
+              index, fixed, arg
+            )));
+          }
+        }
+        AllowedArg::Var { validator, regex } => {
+          if !regex.is_match(arg) {
+            return Err(invalid(format!(
+              "argument {} must match `{}`, got `{}`",
+              index, validator, arg
+            )));
+          }
+        }
+      }
+    }
+
+    if let Some(key) = env.keys().find(|key| !allowed.env.contains(key)) {
+      return Err(invalid(format!(
+        "environment variable `{}` not allowed",
+        key
+      )));
+    }
+
+    let mut command = if allowed.sidecar {
+      Command::new_sidecar(&allowed.cmd)
+    } else {
+      Command::new(&allowed.cmd)
+    };
+    if allowed.clear_env {
+      command = command.env_clear();
+    }
+    if let Some(cwd) = &allowed.cwd {
+      command = command.current_dir(cwd);
+    }
+    Ok(command.args(args).envs(env))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::Scope;
+  use crate::api::config::{ShellAllowedArg, ShellAllowedCommand, ShellAllowlistConfig};
+  use std::collections::HashMap;
+
+  fn scope() -> Scope {
+    Scope::new(&ShellAllowlistConfig {
+      scope: vec![ShellAllowedCommand {
+        name: "git-checkout".into(),
+        cmd: "git".into(),
+        args: vec![
+          ShellAllowedArg::Fixed("checkout".into()),
+          ShellAllowedArg::Var {
+            validator: "[a-z0-9/-]+".into(),
+          },
+        ],
+        sidecar: false,
+        env: vec!["GIT_TRACE".into()],
+        clear_env: false,
+        cwd: None,
+      }],
+    })
+    .expect("invalid shell scope")
+  }
+
+  #[test]
+  fn check_scope() {
+    let scope = scope();
+    assert!(scope
+      .prepare(
+        "git-checkout",
+        vec!["checkout".into(), "feat/scope".into()],
+        HashMap::new(),
+        false
+      )
+      .is_ok());
+
+    let mut env = HashMap::new();
+    env.insert("GIT_TRACE".to_string(), "1".to_string());
+    assert!(scope
+      .prepare(
+        "git-checkout",
+        vec!["checkout".into(), "main".into()],
+        env.clone(),
+        false
+      )
+      .is_ok());
+    env.insert("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string());
+    assert!(matches!(
+      scope.prepare(
+        "git-checkout",
+        vec!["checkout".into(), "main".into()],
+        env,
+        false
+      ),
+      Err(crate::api::Error::InvalidArgs { .. })
+    ));
+
+    assert!(matches!(
+      scope.prepare("git", vec![], HashMap::new(), false),
+      Err(crate::api::Error::ProgramNotAllowed(name)) if name == "git"
+    ));
+    assert!(matches!(
+      scope.prepare(
+        "git-checkout",
+        vec!["checkout".into(), "main".into()],
+        HashMap::new(),
+        true
+      ),
+      Err(crate::api::Error::ProgramNotAllowed(_))
+    ));
+    for args in &[
+      vec!["checkout"],
+      vec!["reset", "main"],
+      vec!["checkout", "main; rm -rf /"],
+      vec!["checkout", "main", "--force"],
+    ] {
+      let args = args.iter().map(|arg| arg.to_string()).collect();
+      assert!(matches!(
+        scope.prepare("git-checkout", args, HashMap::new(), false),
+        Err(crate::api::Error::InvalidArgs { command, .. }) if command == "git-checkout"
+      ));
+    }
+  }
+}
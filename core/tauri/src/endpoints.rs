@@ -5,12 +5,14 @@
 use crate::{
   api::{config::Config, PackageInfo},
   hooks::InvokeMessage,
+  sealed::ManagerBase,
   Params,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 mod app;
 mod cli;
+mod clipboard;
 mod dialog;
 mod event;
 #[allow(unused_imports)]
@@ -18,19 +20,52 @@ mod file_system;
 mod global_shortcut;
 mod http;
 mod internal;
+mod log;
 mod notification;
 mod shell;
 mod window;
 
+#[cfg(notification_all)]
+pub(crate) use notification::emit_scheduled_event as emit_scheduled_notification_event;
+#[cfg(shell_execute)]
+pub(crate) use shell::{kill_children, terminate_children};
+
 /// The response for a JS `invoke` call.
 pub struct InvokeResponse {
-  json: crate::Result<JsonValue>,
+  body: crate::Result<InvokeBody>,
+}
+
+impl InvokeResponse {
+  pub(crate) fn into_body(self) -> crate::Result<InvokeBody> {
+    self.body
+  }
+}
+
+/// The body of an [`InvokeResponse`].
+pub(crate) enum InvokeBody {
+  /// A value serialized to JSON.
+  Json(JsonValue),
+  /// Raw bytes, transferred to the webview as an `Uint8Array` without any JSON encoding.
+  Raw(Vec<u8>),
 }
 
 impl<T: Serialize> From<T> for InvokeResponse {
   fn from(value: T) -> Self {
     Self {
-      json: serde_json::to_value(value).map_err(Into::into),
+      body: serde_json::to_value(value)
+        .map(InvokeBody::Json)
+        .map_err(Into::into),
+    }
+  }
+}
+
+/// Raw bytes returned by a command, sent to the webview without JSON encoding.
+pub(crate) struct Binary(pub Vec<u8>);
+
+impl From<Binary> for InvokeResponse {
+  fn from(binary: Binary) -> Self {
+    Self {
+      body: Ok(InvokeBody::Raw(binary.0)),
     }
   }
 }
@@ -49,79 +84,48 @@ enum Module {
   Notification(notification::Cmd),
   Http(http::Cmd),
   GlobalShortcut(global_shortcut::Cmd),
+  Clipboard(clipboard::Cmd),
+  Log(log::Cmd),
 }
 
 impl Module {
-  fn run<M: Params>(self, message: InvokeMessage<M>, config: &Config, package_info: PackageInfo) {
+  fn run<M: Params>(
+    self,
+    mut message: InvokeMessage<M>,
+    config: &Config,
+    package_info: PackageInfo,
+  ) {
     let window = message.window();
     match self {
-      Self::App(cmd) => message.respond_async(async move {
-        cmd
-          .run(package_info)
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::Fs(cmd) => message
-        .respond_async(async move { cmd.run().and_then(|r| r.json).map_err(|e| e.to_string()) }),
-      Self::Window(cmd) => message.respond_async(async move {
-        cmd
-          .run(window)
-          .await
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::Shell(cmd) => message.respond_async(async move {
-        cmd
-          .run(window)
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::Event(cmd) => message.respond_async(async move {
-        cmd
-          .run(window)
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::Internal(cmd) => message.respond_async(async move {
-        cmd
-          .run(window)
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::Dialog(cmd) => message
-        .respond_async(async move { cmd.run().and_then(|r| r.json).map_err(|e| e.to_string()) }),
+      Self::App(cmd) => message.respond_invoke_async(async move { cmd.run(window, package_info) }),
+      Self::Fs(cmd) => message.respond_invoke_async(async move { cmd.run(window) }),
+      Self::Window(cmd) => message.respond_invoke_async(async move { cmd.run(window).await }),
+      Self::Shell(cmd) => {
+        let scope = window.manager().inner.shell_scope.clone();
+        message.respond_invoke_async(async move { cmd.run(window, scope) })
+      }
+      Self::Event(cmd) => message.respond_invoke_async(async move { cmd.run(window) }),
+      Self::Internal(cmd) => message.respond_invoke_async(async move { cmd.run(window) }),
+      Self::Dialog(cmd) => {
+        // the dialogs are sent to the main thread, which must not be blocked waiting for them
+        message.payload.main_thread = false;
+        message.respond_invoke_async(async move { cmd.run(window).await })
+      }
       Self::Cli(cmd) => {
         if let Some(cli_config) = config.tauri.cli.clone() {
-          message.respond_async(async move {
-            cmd
-              .run(&cli_config)
-              .and_then(|r| r.json)
-              .map_err(|e| e.to_string())
-          })
+          message.respond_invoke_async(async move { cmd.run(&cli_config) })
         }
       }
       Self::Notification(cmd) => {
+        // the permission dialog is sent to the main thread, which must not be blocked waiting for it
+        message.payload.main_thread = false;
         let identifier = config.tauri.bundle.identifier.clone();
-        message.respond_async(async move {
-          cmd
-            .run(identifier)
-            .and_then(|r| r.json)
-            .map_err(|e| e.to_string())
-        })
+        message.respond_invoke_async(async move { cmd.run(window, identifier).await })
       }
-      Self::Http(cmd) => message.respond_async(async move {
-        cmd
-          .run()
-          .await
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
-      Self::GlobalShortcut(cmd) => message.respond_async(async move {
-        cmd
-          .run(window)
-          .and_then(|r| r.json)
-          .map_err(|e| e.to_string())
-      }),
+      Self::Http(cmd) => message.respond_invoke_async(async move { cmd.run().await }),
+      Self::GlobalShortcut(cmd) => message.respond_invoke_async(async move { cmd.run(window) }),
+      Self::Clipboard(cmd) => message.respond_invoke_async(async move { cmd.run() }),
+      Self::Log(cmd) => message.respond_invoke_async(async move { cmd.run(window) }),
     }
   }
 }
@@ -2,10 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use std::process::exit;
-
 use super::InvokeResponse;
-use crate::api::{app::restart_application, PackageInfo};
+use crate::{api::PackageInfo, Params, Window};
 use serde::Deserialize;
 
 /// The API descriptor.
@@ -26,21 +24,17 @@ pub enum Cmd {
 }
 
 impl Cmd {
-  pub fn run(self, package_info: PackageInfo) -> crate::Result<InvokeResponse> {
+  pub fn run<M: Params>(
+    self,
+    window: Window<M>,
+    package_info: PackageInfo,
+  ) -> crate::Result<InvokeResponse> {
     match self {
       Self::GetAppVersion => Ok(package_info.version.into()),
       Self::GetAppName => Ok(package_info.name.into()),
       Self::GetTauriVersion => Ok(env!("CARGO_PKG_VERSION").into()),
-      Self::Relaunch => Ok({
-        restart_application(None);
-        ().into()
-      }),
-      Self::Exit { exit_code } => {
-        // would be great if we can have a handler inside tauri
-        // who close all window and emit an event that user can catch
-        // if they want to process something before closing the app
-        exit(exit_code);
-      }
+      Self::Relaunch => window.app_handle().restart().map(Into::into),
+      Self::Exit { exit_code } => window.app_handle().exit(exit_code).map(Into::into),
     }
   }
 }
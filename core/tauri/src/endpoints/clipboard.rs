@@ -0,0 +1,44 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::InvokeResponse;
+use serde::Deserialize;
+
+#[cfg(clipboard)]
+use crate::api::clipboard;
+
+/// The API descriptor.
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Reads the clipboard text.
+  ReadText,
+  /// Writes text to the clipboard.
+  WriteText { text: String },
+}
+
+impl Cmd {
+  pub fn run(self) -> crate::Result<InvokeResponse> {
+    match self {
+      Self::ReadText => {
+        #[cfg(clipboard_read_text)]
+        return clipboard::read_text().map(Into::into).map_err(Into::into);
+        #[cfg(not(clipboard_read_text))]
+        Err(crate::Error::ApiNotAllowlisted(
+          "clipboard > readText".to_string(),
+        ))
+      }
+      Self::WriteText { text } => {
+        #[cfg(clipboard_write_text)]
+        return clipboard::write_text(text)
+          .map(Into::into)
+          .map_err(Into::into);
+        #[cfg(not(clipboard_write_text))]
+        Err(crate::Error::ApiNotAllowlisted(
+          "clipboard > writeText".to_string(),
+        ))
+      }
+    }
+  }
+}
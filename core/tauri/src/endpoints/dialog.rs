@@ -5,7 +5,15 @@
 use super::InvokeResponse;
 #[cfg(any(dialog_open, dialog_save))]
 use crate::api::dialog::FileDialogBuilder;
-use crate::api::dialog::{ask as ask_dialog, message as message_dialog, AskResponse};
+#[cfg(any(dialog_open, dialog_save))]
+use crate::sealed::ManagerBase;
+use crate::{
+  api::{
+    dialog::{MessageDialogBuilder, MessageDialogButtons, MessageKind},
+    file::GrantLifetime,
+  },
+  Params, Window,
+};
 use serde::Deserialize;
 
 use std::path::PathBuf;
@@ -21,6 +29,8 @@ pub struct DialogFilter {
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OpenDialogOptions {
+  /// The title of the dialog.
+  pub title: Option<String>,
   /// The filters of the dialog.
   #[serde(default)]
   pub filters: Vec<DialogFilter>,
@@ -32,122 +42,255 @@ pub struct OpenDialogOptions {
   pub directory: bool,
   /// The initial path of the dialog.
   pub default_path: Option<PathBuf>,
+  /// Grants the window access to the selected paths on the fs scope,
+  /// with everything under them for directories.
+  pub fs_access: Option<GrantLifetime>,
 }
 
 /// The options for the save dialog API.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SaveDialogOptions {
+  /// The title of the dialog.
+  pub title: Option<String>,
   /// The filters of the dialog.
   #[serde(default)]
   pub filters: Vec<DialogFilter>,
-  /// The initial path of the dialog.
+  /// The initial path of the dialog: a directory, or a file path whose name is proposed.
   pub default_path: Option<PathBuf>,
+  /// The file name proposed by the dialog, overriding the one of `default_path`.
+  pub file_name: Option<String>,
+  /// Grants the window access to the selected path on the fs scope.
+  pub fs_access: Option<GrantLifetime>,
 }
 
 /// The API descriptor.
+///
+/// The dialogs are shown on the main thread with [`Window::run_on_main_thread`], and aren't modal
+/// to the invoking window: wry doesn't expose the native handle of its windows.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// The open dialog API.
-  OpenDialog {
-    options: OpenDialogOptions,
-  },
+  OpenDialog { options: OpenDialogOptions },
   /// The save dialog API.
-  SaveDialog {
-    options: SaveDialogOptions,
-  },
+  SaveDialog { options: SaveDialogOptions },
+  /// A message with an "Ok" button.
+  #[serde(rename_all = "camelCase")]
   MessageDialog {
+    title: Option<String>,
     message: String,
+    #[serde(default)]
+    kind: MessageKind,
   },
+  /// A question with "Yes" and "No" buttons.
+  #[serde(rename_all = "camelCase")]
   AskDialog {
     title: Option<String>,
     message: String,
+    #[serde(default)]
+    kind: MessageKind,
+  },
+  /// A question with "Ok" and "Cancel" buttons.
+  #[serde(rename_all = "camelCase")]
+  ConfirmDialog {
+    title: Option<String>,
+    message: String,
+    #[serde(default)]
+    kind: MessageKind,
   },
 }
 
 impl Cmd {
-  pub fn run(self) -> crate::Result<InvokeResponse> {
+  #[allow(unused_variables)]
+  pub async fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
     match self {
       Self::OpenDialog { options } => {
         #[cfg(dialog_open)]
-        return open(options);
+        return open(window, options).await;
         #[cfg(not(dialog_open))]
         return Err(crate::Error::ApiNotAllowlisted("dialog > open".to_string()));
       }
       Self::SaveDialog { options } => {
         #[cfg(dialog_save)]
-        return save(options);
+        return save(window, options).await;
         #[cfg(not(dialog_save))]
         return Err(crate::Error::ApiNotAllowlisted("dialog > save".to_string()));
       }
-      Self::MessageDialog { message } => {
-        let exe = std::env::current_exe()?;
-        let app_name = exe
-          .file_stem()
-          .expect("failed to get binary filename")
-          .to_string_lossy()
-          .to_string();
-        message_dialog(app_name, message);
+      Self::MessageDialog {
+        title,
+        message,
+        kind,
+      } => {
+        show_message(&window, title, message, kind, MessageDialogButtons::Ok).await?;
         Ok(().into())
       }
-      Self::AskDialog { title, message } => {
-        let exe = std::env::current_exe()?;
-        let answer = ask(
-          title.unwrap_or_else(|| {
-            exe
-              .file_stem()
-              .expect("failed to get binary filename")
-              .to_string_lossy()
-              .to_string()
-          }),
-          message,
-        )?;
-        Ok(answer)
-      }
+      Self::AskDialog {
+        title,
+        message,
+        kind,
+      } => show_message(&window, title, message, kind, MessageDialogButtons::YesNo)
+        .await
+        .map(Into::into),
+      Self::ConfirmDialog {
+        title,
+        message,
+        kind,
+      } => show_message(
+        &window,
+        title,
+        message,
+        kind,
+        MessageDialogButtons::OkCancel,
+      )
+      .await
+      .map(Into::into),
     }
   }
 }
 
-/// Shows an open dialog.
-#[cfg(dialog_open)]
-pub fn open(options: OpenDialogOptions) -> crate::Result<InvokeResponse> {
-  let mut dialog_builder = FileDialogBuilder::new();
-  if let Some(default_path) = options.default_path {
-    dialog_builder = dialog_builder.set_directory(default_path);
-  }
-  for filter in options.filters {
-    let extensions: Vec<&str> = filter.extensions.iter().map(|s| &**s).collect();
-    dialog_builder = dialog_builder.add_filter(filter.name, &extensions);
-  }
-  let response = if options.directory {
-    dialog_builder.pick_folder().into()
-  } else if options.multiple {
-    dialog_builder.pick_files().into()
-  } else {
-    dialog_builder.pick_file().into()
+/// The default dialog title: the binary name.
+fn default_title() -> crate::Result<String> {
+  let exe = std::env::current_exe()?;
+  Ok(
+    exe
+      .file_stem()
+      .expect("failed to get binary filename")
+      .to_string_lossy()
+      .to_string(),
+  )
+}
+
+/// Shows a message dialog on the main thread, resolving to whether the user confirmed.
+async fn show_message<M: Params>(
+  window: &Window<M>,
+  title: Option<String>,
+  message: String,
+  kind: MessageKind,
+  buttons: MessageDialogButtons,
+) -> crate::Result<bool> {
+  let title = match title {
+    Some(title) => title,
+    None => default_title()?,
   };
-  Ok(response)
+  window
+    .run_on_main_thread(move || {
+      MessageDialogBuilder::new(title, message)
+        .kind(kind)
+        .buttons(buttons)
+        .show()
+    })
+    .await
 }
 
-/// Shows a save dialog.
-#[cfg(dialog_save)]
-pub fn save(options: SaveDialogOptions) -> crate::Result<InvokeResponse> {
+#[cfg(any(dialog_open, dialog_save))]
+fn file_dialog(
+  title: Option<String>,
+  filters: Vec<DialogFilter>,
+  default_path: Option<PathBuf>,
+) -> FileDialogBuilder {
   let mut dialog_builder = FileDialogBuilder::new();
-  if let Some(default_path) = options.default_path {
-    dialog_builder = dialog_builder.set_directory(default_path);
+  if let Some(title) = title {
+    dialog_builder = dialog_builder.set_title(title);
+  }
+  if let Some(default_path) = default_path {
+    dialog_builder = dialog_builder.set_default_path(default_path);
   }
-  for filter in options.filters {
+  for filter in filters {
     let extensions: Vec<&str> = filter.extensions.iter().map(|s| &**s).collect();
     dialog_builder = dialog_builder.add_filter(filter.name, &extensions);
   }
-  Ok(dialog_builder.save_file().into())
+  dialog_builder
+}
+/// Grants the window access to the paths selected on a file dialog.
+///
+/// Window grants are revoked when the window is closed.
+#[cfg(any(dialog_open, dialog_save))]
+fn grant_access<M: Params>(
+  window: &Window<M>,
+  lifetime: Option<GrantLifetime>,
+  paths: &[PathBuf],
+  recursive: bool,
+) -> crate::Result<()> {
+  let lifetime = match lifetime {
+    Some(lifetime) if !paths.is_empty() => lifetime,
+    _ => return Ok(()),
+  };
+  let scope = window.manager().inner.fs_scope.clone();
+  let label = window.label().to_string();
+  for path in paths {
+    scope.grant(&label, path, recursive, lifetime)?;
+  }
+  if lifetime == GrantLifetime::Window {
+    window.on_destroy(move || scope.revoke_window(&label));
+  }
+  Ok(())
 }
 
-/// Shows a dialog with a yes/no question.
-pub fn ask(title: String, message: String) -> crate::Result<InvokeResponse> {
-  match ask_dialog(title, message) {
-    AskResponse::Yes => Ok(true.into()),
-    _ => Ok(false.into()),
+/// Shows an open dialog.
+#[cfg(dialog_open)]
+pub async fn open<M: Params>(
+  window: Window<M>,
+  options: OpenDialogOptions,
+) -> crate::Result<InvokeResponse> {
+  let OpenDialogOptions {
+    title,
+    filters,
+    multiple,
+    directory,
+    default_path,
+    fs_access,
+  } = options;
+  let selection = window
+    .run_on_main_thread(move || {
+      let dialog_builder = file_dialog(title, filters, default_path);
+      if directory {
+        dialog_builder.pick_folder().map(|path| vec![path])
+      } else if multiple {
+        dialog_builder.pick_files()
+      } else {
+        dialog_builder.pick_file().map(|path| vec![path])
+      }
+    })
+    .await?;
+  let response = match selection {
+    Some(paths) => {
+      grant_access(&window, fs_access, &paths, directory)?;
+      if multiple && !directory {
+        serde_json::to_value(paths)?
+      } else {
+        serde_json::to_value(&paths[0])?
+      }
+    }
+    None => serde_json::Value::Null,
+  };
+  Ok(response.into())
+}
+
+/// Shows a save dialog.
+#[cfg(dialog_save)]
+pub async fn save<M: Params>(
+  window: Window<M>,
+  options: SaveDialogOptions,
+) -> crate::Result<InvokeResponse> {
+  let SaveDialogOptions {
+    title,
+    filters,
+    default_path,
+    file_name,
+    fs_access,
+  } = options;
+  let path = window
+    .run_on_main_thread(move || {
+      let mut dialog_builder = file_dialog(title, filters, default_path);
+      if let Some(file_name) = file_name {
+        dialog_builder = dialog_builder.set_file_name(file_name);
+      }
+      dialog_builder.save_file()
+    })
+    .await?;
+  if let Some(path) = &path {
+    grant_access(&window, fs_access, std::slice::from_ref(path), false)?;
   }
+  Ok(path.into())
 }
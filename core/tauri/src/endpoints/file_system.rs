@@ -2,13 +2,85 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-use super::InvokeResponse;
+use super::{Binary, InvokeResponse};
 use crate::api::path::BaseDirectory;
+use crate::{sealed::ManagerBase, Params, Window};
 
-use crate::api::{dir, file, path::resolve_path};
+use crate::api::{dir, file, path::resolve_path, rpc::format_callback};
+#[cfg(any(fs_watch, fs_open_file))]
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
-use std::{fs, fs::File, io::Write, path::PathBuf};
+#[cfg(fs_watch)]
+use std::collections::HashSet;
+#[cfg(any(fs_write_file, fs_write_binary_file, fs_truncate_file))]
+use std::fs::OpenOptions;
+#[cfg(any(fs_watch, fs_open_file))]
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+use std::{
+  fs,
+  fs::File,
+  io::{Seek, SeekFrom, Write},
+  path::PathBuf,
+};
+
+type WatcherId = u32;
+/// A watcher, with the label of the window that started it.
+#[cfg(fs_watch)]
+struct TrackedWatcher {
+  window: String,
+  // dropping the watcher stops it
+  _watcher: file::Watcher,
+}
+#[cfg(fs_watch)]
+type WatcherStore = Arc<Mutex<HashMap<WatcherId, TrackedWatcher>>>;
+
+#[cfg(fs_watch)]
+fn watchers() -> &'static WatcherStore {
+  static STORE: Lazy<WatcherStore> = Lazy::new(Default::default);
+  &STORE
+}
+
+/// The labels of the windows with a destroy handler dropping their watchers.
+#[cfg(fs_watch)]
+fn watch_destroy_handlers() -> &'static Mutex<HashSet<String>> {
+  static HANDLERS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+  &HANDLERS
+}
+
+type FileId = u32;
+/// The open file handles of each window, keyed by the window label.
+///
+/// Each handle has its own lock, so the table is only locked to look it up.
+#[cfg(fs_open_file)]
+type FileTable = Arc<Mutex<HashMap<String, HashMap<FileId, Arc<Mutex<File>>>>>>;
+
+#[cfg(fs_open_file)]
+fn open_files() -> &'static FileTable {
+  static TABLE: Lazy<FileTable> = Lazy::new(Default::default);
+  &TABLE
+}
+
+/// Runs the closure with the file handle opened by the given window.
+#[cfg(fs_open_file)]
+fn with_file<M: Params, T, F: FnOnce(&mut File) -> crate::Result<T>>(
+  window: &Window<M>,
+  id: FileId,
+  f: F,
+) -> crate::Result<T> {
+  let file = open_files()
+    .lock()
+    .unwrap()
+    .get(&window.label().to_string())
+    .and_then(|files| files.get(&id))
+    .cloned()
+    .ok_or(crate::Error::FileHandleNotFound(id))?;
+  let mut file = file.lock().unwrap();
+  f(&mut file)
+}
 
 /// The options for the directory functions on the file system API.
 #[derive(Deserialize)]
@@ -29,6 +101,116 @@ pub struct FileOperationOptions {
   pub dir: Option<BaseDirectory>,
 }
 
+/// The options for the read file functions on the file system API.
+#[derive(Deserialize)]
+pub struct ReadFileOptions {
+  /// The base directory of the operation.
+  /// The directory path of the BaseDirectory will be the prefix of the defined file path.
+  pub dir: Option<BaseDirectory>,
+  /// The byte offset to start reading from.
+  #[serde(default)]
+  pub offset: u64,
+  /// The maximum number of bytes to read. Reads until the end of the file if not set.
+  pub length: Option<u64>,
+}
+
+/// The options for the write file functions on the file system API.
+#[derive(Deserialize)]
+pub struct WriteFileOptions {
+  /// The base directory of the operation.
+  /// The directory path of the BaseDirectory will be the prefix of the defined file path.
+  pub dir: Option<BaseDirectory>,
+  /// Whether the contents should be appended to the file instead of replacing it.
+  #[serde(default)]
+  pub append: bool,
+}
+
+/// The options for the watch API.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOptions {
+  /// Whether the subdirectories should also be watched.
+  #[serde(default)]
+  pub recursive: bool,
+  /// The debounce delay in milliseconds.
+  #[serde(default = "default_watch_delay")]
+  pub delay_ms: u64,
+  /// The base directory of the operation.
+  /// The directory path of the BaseDirectory will be the prefix of the defined path.
+  pub dir: Option<BaseDirectory>,
+}
+
+fn default_watch_delay() -> u64 {
+  500
+}
+
+/// The options for the walk API.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalkDirOptions {
+  /// The base directory of the operation.
+  /// The directory path of the BaseDirectory will be the prefix of the defined path.
+  pub dir: Option<BaseDirectory>,
+  /// The maximum number of entries sent to the webview at once.
+  #[serde(default = "default_walk_batch_size")]
+  pub batch_size: usize,
+  /// The filters of the walk.
+  #[serde(flatten)]
+  pub walk: dir::WalkOptions,
+}
+
+fn default_walk_batch_size() -> usize {
+  500
+}
+
+/// The events sent to the callback of the walk API.
+#[cfg(fs_walk)]
+#[derive(Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+enum WalkEvent {
+  /// A batch of entries.
+  Entries(Vec<dir::WalkEntry>),
+  /// An entry could not be read.
+  Error(String),
+  /// The walk is finished.
+  Done,
+}
+
+/// The reference position of a seek operation.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SeekMode {
+  /// Seek relative to the start of the file.
+  Start,
+  /// Seek relative to the current position.
+  Current,
+  /// Seek relative to the end of the file.
+  End,
+}
+
+#[cfg(fs_open_file)]
+impl SeekMode {
+  fn position(self, offset: i64) -> std::io::Result<SeekFrom> {
+    match self {
+      Self::Start if offset < 0 => Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "cannot seek before the start of the file",
+      )),
+      Self::Start => Ok(SeekFrom::Start(offset as u64)),
+      Self::Current => Ok(SeekFrom::Current(offset)),
+      Self::End => Ok(SeekFrom::End(offset)),
+    }
+  }
+}
+
+/// The payload of the `tauri://fs-watch` event.
+#[cfg(fs_watch)]
+#[derive(Clone, Serialize)]
+struct WatchEventPayload {
+  id: WatcherId,
+  event: file::WatchEvent,
+}
+
 /// The API descriptor.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
@@ -41,25 +223,76 @@ pub enum Cmd {
   /// The read binary file API.
   ReadBinaryFile {
     path: PathBuf,
-    options: Option<FileOperationOptions>,
+    options: Option<ReadFileOptions>,
   },
   /// The write file API.
   WriteFile {
     path: PathBuf,
     contents: String,
-    options: Option<FileOperationOptions>,
+    options: Option<WriteFileOptions>,
   },
   /// The write binary file API.
+  ///
+  /// The contents are base64 encoded: the webview can only send JSON messages, since the custom
+  /// protocol handler doesn't receive request bodies, and base64 is their most compact encoding.
   WriteBinaryFile {
     path: PathBuf,
     contents: String,
+    options: Option<WriteFileOptions>,
+  },
+  /// The truncate file API.
+  TruncateFile {
+    path: PathBuf,
+    len: u64,
+    options: Option<FileOperationOptions>,
+  },
+  /// The exists API.
+  Exists {
+    path: PathBuf,
     options: Option<FileOperationOptions>,
   },
+  /// The metadata API.
+  Metadata {
+    path: PathBuf,
+    options: Option<FileOperationOptions>,
+  },
+  /// The watch API.
+  Watch {
+    path: PathBuf,
+    options: Option<WatchOptions>,
+  },
+  /// The unwatch API.
+  Unwatch { id: WatcherId },
+  /// The open file API.
+  Open {
+    path: PathBuf,
+    mode: Option<file::OpenMode>,
+    options: Option<FileOperationOptions>,
+  },
+  /// The read file handle API.
+  Read { id: FileId, len: usize },
+  /// The write file handle API. The contents are base64 encoded, like on [`Cmd::WriteBinaryFile`].
+  Write { id: FileId, contents: String },
+  /// The seek file handle API.
+  Seek {
+    id: FileId,
+    offset: i64,
+    whence: SeekMode,
+  },
+  /// The close file handle API.
+  Close { id: FileId },
   /// The read dir API.
   ReadDir {
     path: PathBuf,
     options: Option<DirOperationOptions>,
   },
+  /// The walk API.
+  #[serde(rename_all = "camelCase")]
+  Walk {
+    path: PathBuf,
+    options: Option<WalkDirOptions>,
+    on_event_fn: String,
+  },
   /// The copy file API.
   CopyFile {
     source: PathBuf,
@@ -96,11 +329,11 @@ pub enum Cmd {
 }
 
 impl Cmd {
-  pub fn run(self) -> crate::Result<InvokeResponse> {
+  pub fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
     match self {
       Self::ReadTextFile { path, options } => {
         #[cfg(fs_read_text_file)]
-        return read_text_file(path, options).map(Into::into);
+        return read_text_file(&window, path, options).map(Into::into);
         #[cfg(not(fs_read_text_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > readTextFile".to_string(),
@@ -108,7 +341,7 @@ impl Cmd {
       }
       Self::ReadBinaryFile { path, options } => {
         #[cfg(fs_read_binary_file)]
-        return read_binary_file(path, options).map(Into::into);
+        return read_binary_file(&window, path, options).map(|bytes| Binary(bytes).into());
         #[cfg(not(fs_read_binary_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "readBinaryFile".to_string(),
@@ -120,7 +353,7 @@ impl Cmd {
         options,
       } => {
         #[cfg(fs_write_file)]
-        return write_file(path, contents, options).map(Into::into);
+        return write_file(&window, path, contents, options).map(Into::into);
         #[cfg(not(fs_write_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > writeFile".to_string(),
@@ -132,31 +365,128 @@ impl Cmd {
         options,
       } => {
         #[cfg(fs_write_binary_file)]
-        return write_binary_file(path, contents, options).map(Into::into);
+        return write_binary_file(&window, path, contents, options).map(Into::into);
         #[cfg(not(fs_write_binary_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "writeBinaryFile".to_string(),
         ))
       }
+      Self::TruncateFile { path, len, options } => {
+        #[cfg(fs_truncate_file)]
+        return truncate_file(&window, path, len, options).map(Into::into);
+        #[cfg(not(fs_truncate_file))]
+        Err(crate::Error::ApiNotAllowlisted(
+          "fs > truncateFile".to_string(),
+        ))
+      }
+      Self::Exists { path, options } => {
+        #[cfg(fs_exists)]
+        return exists(&window, path, options).map(Into::into);
+        #[cfg(not(fs_exists))]
+        Err(crate::Error::ApiNotAllowlisted("fs > exists".to_string()))
+      }
+      Self::Metadata { path, options } => {
+        #[cfg(fs_metadata)]
+        return metadata(&window, path, options).map(Into::into);
+        #[cfg(not(fs_metadata))]
+        Err(crate::Error::ApiNotAllowlisted("fs > metadata".to_string()))
+      }
+      Self::Watch { path, options } => {
+        #[cfg(fs_watch)]
+        return watch(window, path, options).map(Into::into);
+        #[cfg(not(fs_watch))]
+        Err(crate::Error::ApiNotAllowlisted("fs > watch".to_string()))
+      }
+      #[cfg(fs_watch)]
+      Self::Unwatch { id } => unwatch(&window, id).map(Into::into),
+      #[cfg(not(fs_watch))]
+      Self::Unwatch { .. } => Err(crate::Error::ApiNotAllowlisted("fs > watch".to_string())),
+      Self::Open {
+        path,
+        mode,
+        options,
+      } => {
+        #[cfg(fs_open_file)]
+        return open(window, path, mode.unwrap_or_default(), options).map(Into::into);
+        #[cfg(not(fs_open_file))]
+        Err(crate::Error::ApiNotAllowlisted("fs > openFile".to_string()))
+      }
+      Self::Read { id, len } => {
+        #[cfg(fs_open_file)]
+        return with_file(&window, id, |file| {
+          file::read_chunk(file, len).map_err(crate::Error::FailedToExecuteApi)
+        })
+        .map(|bytes| Binary(bytes).into());
+        #[cfg(not(fs_open_file))]
+        Err(crate::Error::ApiNotAllowlisted("fs > openFile".to_string()))
+      }
+      Self::Write { id, contents } => {
+        #[cfg(fs_open_file)]
+        return base64::decode(contents)
+          .map_err(crate::Error::Base64Decode)
+          .and_then(|bytes| {
+            with_file(&window, id, |file| {
+              file.write_all(&bytes)?;
+              Ok(bytes.len())
+            })
+          })
+          .map(Into::into);
+        #[cfg(not(fs_open_file))]
+        Err(crate::Error::ApiNotAllowlisted("fs > openFile".to_string()))
+      }
+      Self::Seek { id, offset, whence } => {
+        #[cfg(fs_open_file)]
+        return with_file(&window, id, |file| {
+          file.seek(whence.position(offset)?).map_err(Into::into)
+        })
+        .map(Into::into);
+        #[cfg(not(fs_open_file))]
+        Err(crate::Error::ApiNotAllowlisted("fs > openFile".to_string()))
+      }
+      Self::Close { id } => {
+        #[cfg(fs_open_file)]
+        {
+          // dropping the handle closes the file, once the operations in progress are done
+          open_files()
+            .lock()
+            .unwrap()
+            .get_mut(&window.label().to_string())
+            .and_then(|files| files.remove(&id))
+            .ok_or(crate::Error::FileHandleNotFound(id))?;
+          Ok(().into())
+        }
+        #[cfg(not(fs_open_file))]
+        Err(crate::Error::ApiNotAllowlisted("fs > openFile".to_string()))
+      }
       Self::ReadDir { path, options } => {
         #[cfg(fs_read_dir)]
-        return read_dir(path, options).map(Into::into);
+        return read_dir(&window, path, options).map(Into::into);
         #[cfg(not(fs_read_dir))]
         Err(crate::Error::ApiNotAllowlisted("fs > readDir".to_string()))
       }
+      Self::Walk {
+        path,
+        options,
+        on_event_fn,
+      } => {
+        #[cfg(fs_walk)]
+        return walk(window, path, options, on_event_fn).map(Into::into);
+        #[cfg(not(fs_walk))]
+        Err(crate::Error::ApiNotAllowlisted("fs > walk".to_string()))
+      }
       Self::CopyFile {
         source,
         destination,
         options,
       } => {
         #[cfg(fs_copy_file)]
-        return copy_file(source, destination, options).map(Into::into);
+        return copy_file(&window, source, destination, options).map(Into::into);
         #[cfg(not(fs_copy_file))]
         Err(crate::Error::ApiNotAllowlisted("fs > copyFile".to_string()))
       }
       Self::CreateDir { path, options } => {
         #[cfg(fs_create_dir)]
-        return create_dir(path, options).map(Into::into);
+        return create_dir(&window, path, options).map(Into::into);
         #[cfg(not(fs_create_dir))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > createDir".to_string(),
@@ -164,7 +494,7 @@ impl Cmd {
       }
       Self::RemoveDir { path, options } => {
         #[cfg(fs_remove_dir)]
-        return remove_dir(path, options).map(Into::into);
+        return remove_dir(&window, path, options).map(Into::into);
         #[cfg(not(fs_remove_dir))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > removeDir".to_string(),
@@ -172,7 +502,7 @@ impl Cmd {
       }
       Self::RemoveFile { path, options } => {
         #[cfg(fs_remove_file)]
-        return remove_file(path, options).map(Into::into);
+        return remove_file(&window, path, options).map(Into::into);
         #[cfg(not(fs_remove_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > removeFile".to_string(),
@@ -184,7 +514,7 @@ impl Cmd {
         options,
       } => {
         #[cfg(fs_rename_file)]
-        return rename_file(old_path, new_path, options).map(Into::into);
+        return rename_file(&window, old_path, new_path, options).map(Into::into);
         #[cfg(not(fs_rename_file))]
         Err(crate::Error::ApiNotAllowlisted(
           "fs > renameFile".to_string(),
@@ -200,9 +530,51 @@ impl Cmd {
   }
 }
 
+/// Resolves the path and checks that the window can read it on the fs scope.
+fn resolve<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  dir: Option<BaseDirectory>,
+) -> crate::Result<PathBuf> {
+  let path = resolve_path(path, dir)?;
+  window
+    .manager()
+    .inner
+    .fs_scope
+    .check(&window.label().to_string(), &path)?;
+  Ok(path)
+}
+
+/// Resolves the path and checks that the window can create, write, rename or remove it on the fs scope.
+#[cfg(any(
+  fs_copy_file,
+  fs_create_dir,
+  fs_remove_dir,
+  fs_remove_file,
+  fs_rename_file,
+  fs_write_file,
+  fs_write_binary_file,
+  fs_truncate_file,
+  fs_open_file
+))]
+fn resolve_write<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  dir: Option<BaseDirectory>,
+) -> crate::Result<PathBuf> {
+  let path = resolve_path(path, dir)?;
+  window
+    .manager()
+    .inner
+    .fs_scope
+    .check_write(&window.label().to_string(), &path)?;
+  Ok(path)
+}
+
 /// Reads a directory.
 #[cfg(fs_read_dir)]
-pub fn read_dir(
+pub fn read_dir<M: Params>(
+  window: &Window<M>,
   path: PathBuf,
   options: Option<DirOperationOptions>,
 ) -> crate::Result<Vec<dir::DiskEntry>> {
@@ -211,36 +583,80 @@ pub fn read_dir(
   } else {
     (false, None)
   };
-  dir::read_dir(resolve_path(path, dir)?, recursive).map_err(crate::Error::FailedToExecuteApi)
+  dir::read_dir(resolve(window, path, dir)?, recursive).map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Walks a directory tree on a separate thread,
+/// sending the entries in batches to the `on_event_fn` callback.
+#[cfg(fs_walk)]
+pub fn walk<M: Params>(
+  window: Window<M>,
+  path: PathBuf,
+  options: Option<WalkDirOptions>,
+  on_event_fn: String,
+) -> crate::Result<()> {
+  let (dir, batch_size, walk_options) = match options {
+    Some(options) => (options.dir, options.batch_size.max(1), options.walk),
+    None => (None, default_walk_batch_size(), Default::default()),
+  };
+  let walker = dir::walk(resolve(&window, path, dir)?, &walk_options)?;
+
+  let send = move |event: WalkEvent| {
+    let js = format_callback(on_event_fn.clone(), &event).expect("unable to serialize WalkEvent");
+    let _ = window.eval(js.as_str());
+  };
+  std::thread::spawn(move || {
+    let mut entries = Vec::with_capacity(batch_size);
+    for entry in walker {
+      match entry {
+        Ok(entry) => {
+          entries.push(entry);
+          if entries.len() == batch_size {
+            send(WalkEvent::Entries(std::mem::replace(
+              &mut entries,
+              Vec::with_capacity(batch_size),
+            )));
+          }
+        }
+        Err(e) => send(WalkEvent::Error(e.to_string())),
+      }
+    }
+    if !entries.is_empty() {
+      send(WalkEvent::Entries(entries));
+    }
+    send(WalkEvent::Done);
+  });
+  Ok(())
 }
 
 /// Copies a file.
 #[cfg(fs_copy_file)]
-pub fn copy_file(
+pub fn copy_file<M: Params>(
+  window: &Window<M>,
   source: PathBuf,
   destination: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<()> {
-  let (src, dest) = match options.and_then(|o| o.dir) {
-    Some(dir) => (
-      resolve_path(source, Some(dir.clone()))?,
-      resolve_path(destination, Some(dir))?,
-    ),
-    None => (source, destination),
-  };
+  let dir = options.and_then(|o| o.dir);
+  let src = resolve(window, source, dir.clone())?;
+  let dest = resolve_write(window, destination, dir)?;
   fs::copy(src, dest)?;
   Ok(())
 }
 
 /// Creates a directory.
 #[cfg(fs_create_dir)]
-pub fn create_dir(path: PathBuf, options: Option<DirOperationOptions>) -> crate::Result<()> {
+pub fn create_dir<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<DirOperationOptions>,
+) -> crate::Result<()> {
   let (recursive, dir) = if let Some(options_value) = options {
     (options_value.recursive, options_value.dir)
   } else {
     (false, None)
   };
-  let resolved_path = resolve_path(path, dir)?;
+  let resolved_path = resolve_write(window, path, dir)?;
   if recursive {
     fs::create_dir_all(resolved_path)?;
   } else {
@@ -252,13 +668,17 @@ pub fn create_dir(path: PathBuf, options: Option<DirOperationOptions>) -> crate:
 
 /// Removes a directory.
 #[cfg(fs_remove_dir)]
-pub fn remove_dir(path: PathBuf, options: Option<DirOperationOptions>) -> crate::Result<()> {
+pub fn remove_dir<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<DirOperationOptions>,
+) -> crate::Result<()> {
   let (recursive, dir) = if let Some(options_value) = options {
     (options_value.recursive, options_value.dir)
   } else {
     (false, None)
   };
-  let resolved_path = resolve_path(path, dir)?;
+  let resolved_path = resolve_write(window, path, dir)?;
   if recursive {
     fs::remove_dir_all(resolved_path)?;
   } else {
@@ -270,77 +690,257 @@ pub fn remove_dir(path: PathBuf, options: Option<DirOperationOptions>) -> crate:
 
 /// Removes a file
 #[cfg(fs_remove_file)]
-pub fn remove_file(path: PathBuf, options: Option<FileOperationOptions>) -> crate::Result<()> {
-  let resolved_path = resolve_path(path, options.and_then(|o| o.dir))?;
+pub fn remove_file<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<()> {
+  let resolved_path = resolve_write(window, path, options.and_then(|o| o.dir))?;
   fs::remove_file(resolved_path)?;
   Ok(())
 }
 
 /// Renames a file.
 #[cfg(fs_rename_file)]
-pub fn rename_file(
+pub fn rename_file<M: Params>(
+  window: &Window<M>,
   old_path: PathBuf,
   new_path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<()> {
-  let (old, new) = match options.and_then(|o| o.dir) {
-    Some(dir) => (
-      resolve_path(old_path, Some(dir.clone()))?,
-      resolve_path(new_path, Some(dir))?,
-    ),
-    None => (old_path, new_path),
-  };
+  let dir = options.and_then(|o| o.dir);
+  let old = resolve_write(window, old_path, dir.clone())?;
+  let new = resolve_write(window, new_path, dir)?;
   fs::rename(old, new).map_err(crate::Error::Io)
 }
 
+/// Opens a file for writing, either truncating it or appending to it.
+#[cfg(any(fs_write_file, fs_write_binary_file))]
+fn open_for_write<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<WriteFileOptions>,
+) -> crate::Result<File> {
+  let (append, dir) = match options {
+    Some(options) => (options.append, options.dir),
+    None => (false, None),
+  };
+  let path = resolve_write(window, path, dir)?;
+  if append {
+    OpenOptions::new()
+      .append(true)
+      .create(true)
+      .open(path)
+      .map_err(Into::into)
+  } else {
+    File::create(path).map_err(Into::into)
+  }
+}
+
 /// Writes a text file.
 #[cfg(fs_write_file)]
-pub fn write_file(
+pub fn write_file<M: Params>(
+  window: &Window<M>,
   path: PathBuf,
   contents: String,
-  options: Option<FileOperationOptions>,
+  options: Option<WriteFileOptions>,
 ) -> crate::Result<()> {
-  File::create(resolve_path(path, options.and_then(|o| o.dir))?)
-    .map_err(crate::Error::Io)
+  open_for_write(window, path, options)
     .and_then(|mut f| f.write_all(contents.as_bytes()).map_err(|err| err.into()))?;
   Ok(())
 }
 
 /// Writes a binary file.
 #[cfg(fs_write_binary_file)]
-pub fn write_binary_file(
+pub fn write_binary_file<M: Params>(
+  window: &Window<M>,
   path: PathBuf,
   contents: String,
-  options: Option<FileOperationOptions>,
+  options: Option<WriteFileOptions>,
 ) -> crate::Result<()> {
   base64::decode(contents)
     .map_err(crate::Error::Base64Decode)
     .and_then(|c| {
-      File::create(resolve_path(path, options.and_then(|o| o.dir))?)
-        .map_err(Into::into)
+      open_for_write(window, path, options)
         .and_then(|mut f| f.write_all(&c).map_err(|err| err.into()))
     })?;
   Ok(())
 }
 
+/// Truncates or extends a file to the given length.
+#[cfg(fs_truncate_file)]
+pub fn truncate_file<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  len: u64,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<()> {
+  let file = OpenOptions::new().write(true).open(resolve_write(
+    window,
+    path,
+    options.and_then(|o| o.dir),
+  )?)?;
+  file.set_len(len)?;
+  Ok(())
+}
+
+/// Checks whether a path exists.
+#[cfg(fs_exists)]
+pub fn exists<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<bool> {
+  Ok(resolve(window, path, options.and_then(|o| o.dir))?.exists())
+}
+
+/// Reads the metadata of a file or directory.
+#[cfg(fs_metadata)]
+pub fn metadata<M: Params>(
+  window: &Window<M>,
+  path: PathBuf,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<file::Metadata> {
+  file::metadata(resolve(window, path, options.and_then(|o| o.dir))?)
+    .map_err(crate::Error::FailedToExecuteApi)
+}
+
+/// Watches a path and emits `tauri://fs-watch` events to the window.
+///
+/// The watcher is dropped when the window is closed.
+#[cfg(fs_watch)]
+pub fn watch<M: Params>(
+  window: Window<M>,
+  path: PathBuf,
+  options: Option<WatchOptions>,
+) -> crate::Result<WatcherId> {
+  let (recursive, delay_ms, dir) = match options {
+    Some(options) => (options.recursive, options.delay_ms, options.dir),
+    None => (false, default_watch_delay(), None),
+  };
+  // the store stays locked until the watcher is inserted, so no other watcher takes its id
+  let mut store = watchers().lock().unwrap();
+  let id = loop {
+    let id = rand::random::<WatcherId>();
+    if !store.contains_key(&id) {
+      break id;
+    }
+  };
+  let event_window = window.clone();
+  let watcher = file::watch(
+    resolve(&window, path, dir)?,
+    recursive,
+    std::time::Duration::from_millis(delay_ms),
+    move |event| {
+      let _ = event_window.emit_internal(
+        "tauri://fs-watch".to_string(),
+        Some(WatchEventPayload { id, event }),
+      );
+    },
+  )?;
+  let label = window.label().to_string();
+  store.insert(
+    id,
+    TrackedWatcher {
+      window: label.clone(),
+      _watcher: watcher,
+    },
+  );
+  drop(store);
+  if watch_destroy_handlers()
+    .lock()
+    .unwrap()
+    .insert(label.clone())
+  {
+    window.on_destroy(move || {
+      watch_destroy_handlers().lock().unwrap().remove(&label);
+      watchers()
+        .lock()
+        .unwrap()
+        .retain(|_, tracked| tracked.window != label);
+    });
+  }
+  Ok(id)
+}
+
+/// Stops a watcher started by the window.
+#[cfg(fs_watch)]
+pub fn unwatch<M: Params>(window: &Window<M>, id: WatcherId) -> crate::Result<()> {
+  let mut watchers = watchers().lock().unwrap();
+  match watchers.get(&id) {
+    Some(tracked) if tracked.window == window.label().to_string() => {
+      watchers.remove(&id);
+      Ok(())
+    }
+    _ => Err(crate::Error::WatcherNotFound(id)),
+  }
+}
+
+/// Opens a file and stores its handle in the window resource table.
+///
+/// The handles opened by a window are closed when the window is closed.
+#[cfg(fs_open_file)]
+pub fn open<M: Params>(
+  window: Window<M>,
+  path: PathBuf,
+  mode: file::OpenMode,
+  options: Option<FileOperationOptions>,
+) -> crate::Result<FileId> {
+  let dir = options.and_then(|o| o.dir);
+  let path = match mode {
+    file::OpenMode::Read => resolve(&window, path, dir)?,
+    _ => resolve_write(&window, path, dir)?,
+  };
+  let file = file::open(path, mode)?;
+  let label = window.label().to_string();
+  let mut table = open_files().lock().unwrap();
+  if !table.contains_key(&label) {
+    let destroyed_label = label.clone();
+    window.on_destroy(move || {
+      open_files().lock().unwrap().remove(&destroyed_label);
+    });
+  }
+  let files = table.entry(label).or_default();
+  // never replace a handle the window still has open
+  let id = loop {
+    let id = rand::random::<FileId>();
+    if !files.contains_key(&id) {
+      break id;
+    }
+  };
+  files.insert(id, Arc::new(Mutex::new(file)));
+  Ok(id)
+}
+
 /// Reads a text file.
 #[cfg(fs_read_text_file)]
-pub fn read_text_file(
+pub fn read_text_file<M: Params>(
+  window: &Window<M>,
   path: PathBuf,
   options: Option<FileOperationOptions>,
 ) -> crate::Result<String> {
-  file::read_string(resolve_path(path, options.and_then(|o| o.dir))?)
+  file::read_string(resolve(window, path, options.and_then(|o| o.dir))?)
     .map_err(crate::Error::FailedToExecuteApi)
 }
 
 /// Reads a binary file.
 #[cfg(fs_read_binary_file)]
-pub fn read_binary_file(
+pub fn read_binary_file<M: Params>(
+  window: &Window<M>,
   path: PathBuf,
-  options: Option<FileOperationOptions>,
+  options: Option<ReadFileOptions>,
 ) -> crate::Result<Vec<u8>> {
-  file::read_binary(resolve_path(path, options.and_then(|o| o.dir))?)
-    .map_err(crate::Error::FailedToExecuteApi)
+  match options {
+    Some(ReadFileOptions {
+      dir,
+      offset,
+      length,
+    }) if offset > 0 || length.is_some() => {
+      file::read_binary_range(resolve(window, path, dir)?, offset, length)
+    }
+    options => file::read_binary(resolve(window, path, options.and_then(|o| o.dir))?),
+  }
+  .map_err(crate::Error::FailedToExecuteApi)
 }
 
 #[cfg(fs_path)]
@@ -3,57 +3,28 @@
 // SPDX-License-Identifier: MIT
 
 use super::InvokeResponse;
-use crate::{runtime::Dispatch, Params, Window};
-use once_cell::sync::Lazy;
+use crate::{Params, Window};
 use serde::Deserialize;
-use std::sync::{Arc, Mutex};
-
-#[cfg(global_shortcut_all)]
-use crate::api::shortcuts::ShortcutManager;
-
-#[cfg(global_shortcut_all)]
-type ShortcutManagerHandle = Arc<Mutex<ShortcutManager>>;
-
-#[cfg(global_shortcut_all)]
-pub fn manager_handle() -> &'static ShortcutManagerHandle {
-  static MANAGER: Lazy<ShortcutManagerHandle> = Lazy::new(Default::default);
-  &MANAGER
-}
 
 /// The API descriptor.
+///
+/// The shortcuts are owned by the window registering them, which receives the
+/// `tauri://global-shortcut` event when one of them is triggered.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// Register a global shortcut.
-  Register { shortcut: String, handler: String },
+  Register { shortcut: String },
   /// Register a list of global shortcuts.
-  RegisterAll {
-    shortcuts: Vec<String>,
-    handler: String,
-  },
-  /// Unregister a global shortcut.
+  RegisterAll { shortcuts: Vec<String> },
+  /// Unregister a global shortcut registered by the window.
   Unregister { shortcut: String },
-  /// Unregisters all registered shortcuts.
+  /// Unregisters all shortcuts registered by the window.
   UnregisterAll,
   /// Determines whether the given hotkey is registered or not.
   IsRegistered { shortcut: String },
 }
 
-#[cfg(global_shortcut_all)]
-fn register_shortcut<D: Dispatch>(
-  dispatcher: D,
-  manager: &mut ShortcutManager,
-  shortcut: String,
-  handler: String,
-) -> crate::Result<()> {
-  manager.register(shortcut.clone(), move || {
-    let callback_string = crate::api::rpc::format_callback(handler.to_string(), &shortcut)
-      .expect("unable to serialize shortcut string to json");
-    let _ = dispatcher.eval_script(callback_string.as_str());
-  })?;
-  Ok(())
-}
-
 #[cfg(not(global_shortcut_all))]
 impl Cmd {
   pub fn run<M: Params>(self, _window: Window<M>) -> crate::Result<InvokeResponse> {
@@ -66,36 +37,27 @@ impl Cmd {
 #[cfg(global_shortcut_all)]
 impl Cmd {
   pub fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
+    let manager = window.app_handle().global_shortcut_manager();
     match self {
-      Self::Register { shortcut, handler } => {
-        let dispatcher = window.dispatcher();
-        let mut manager = manager_handle().lock().unwrap();
-        register_shortcut(dispatcher, &mut manager, shortcut, handler)?;
+      Self::Register { shortcut } => {
+        manager.register_for_window(&window, &shortcut)?;
         Ok(().into())
       }
-      Self::RegisterAll { shortcuts, handler } => {
-        let dispatcher = window.dispatcher();
-        let mut manager = manager_handle().lock().unwrap();
+      Self::RegisterAll { shortcuts } => {
         for shortcut in shortcuts {
-          let dispatch = dispatcher.clone();
-          register_shortcut(dispatch, &mut manager, shortcut, handler.clone())?;
+          manager.register_for_window(&window, &shortcut)?;
         }
         Ok(().into())
       }
       Self::Unregister { shortcut } => {
-        let mut manager = manager_handle().lock().unwrap();
-        manager.unregister(shortcut)?;
+        manager.unregister_owned(window.label(), Some(&shortcut))?;
         Ok(().into())
       }
       Self::UnregisterAll => {
-        let mut manager = manager_handle().lock().unwrap();
-        manager.unregister_all()?;
+        manager.unregister_owned(window.label(), None)?;
         Ok(().into())
       }
-      Self::IsRegistered { shortcut } => {
-        let manager = manager_handle().lock().unwrap();
-        Ok(manager.is_registered(shortcut)?.into())
-      }
+      Self::IsRegistered { shortcut } => Ok(manager.is_registered(&shortcut)?.into()),
     }
   }
 }
@@ -0,0 +1,51 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use super::InvokeResponse;
+#[cfg(log_all)]
+use crate::logging::LevelFilter;
+use crate::{Params, Window};
+use serde::Deserialize;
+
+/// The API descriptor.
+///
+/// Levels go from 1 (`Error`) to 5 (`Trace`).
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum Cmd {
+  /// Logs a message with the `webview:<window label>` target.
+  Log { level: usize, message: String },
+  /// Emits the Rust log records up to the given level to the window, as the `tauri://log` event.
+  Subscribe { level: Option<usize> },
+  /// Stops emitting the log records to the window.
+  Unsubscribe,
+}
+
+#[cfg(not(log_all))]
+impl Cmd {
+  pub fn run<M: Params>(self, _window: Window<M>) -> crate::Result<InvokeResponse> {
+    Err(crate::Error::ApiNotAllowlisted("log > all".to_string()))
+  }
+}
+
+#[cfg(log_all)]
+impl Cmd {
+  pub fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
+    match self {
+      Self::Log { level, message } => {
+        crate::logging::log_webview(&window.label().to_string(), level, &message)?;
+      }
+      Self::Subscribe { level } => {
+        let level = match level {
+          Some(level) => crate::logging::level_filter_from_usize(level)
+            .ok_or(crate::Error::InvalidLogLevel(level))?,
+          None => LevelFilter::Trace,
+        };
+        crate::logging::subscribe(window, level)?;
+      }
+      Self::Unsubscribe => crate::logging::unsubscribe(&window.label().to_string()),
+    }
+    Ok(().into())
+  }
+}
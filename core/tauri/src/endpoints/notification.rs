@@ -3,13 +3,19 @@
 // SPDX-License-Identifier: MIT
 
 use super::InvokeResponse;
+use crate::{Params, Window};
 use serde::Deserialize;
 
 #[cfg(notification_all)]
-use crate::api::notification::Notification;
+use crate::api::notification::{Action, Notification, NotificationEvent, Urgency};
+#[cfg(notification_all)]
+use crate::runtime::manager::WindowManager;
+#[cfg(notification_all)]
+use serde::Serialize;
 
 /// The options for the notification API.
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NotificationOptions {
   /// The notification title.
   pub title: String,
@@ -17,6 +23,44 @@ pub struct NotificationOptions {
   pub body: Option<String>,
   /// The notification icon.
   pub icon: Option<String>,
+  /// The notification id, replacing the shown notification with the same id.
+  pub id: Option<u32>,
+  /// The action buttons.
+  #[cfg(notification_all)]
+  #[serde(default)]
+  pub actions: Vec<Action>,
+  /// The notification urgency.
+  #[cfg(notification_all)]
+  pub urgency: Option<Urgency>,
+  /// The name of the sound played with the notification.
+  pub sound: Option<String>,
+  /// How long the notification is shown, in milliseconds.
+  pub timeout: Option<u64>,
+  /// When to show the notification, in milliseconds since the Unix epoch.
+  pub schedule: Option<u64>,
+}
+
+/// The permission to show notifications.
+#[cfg(notification_all)]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Permission {
+  /// The user allows notifications.
+  Granted,
+  /// The user denies notifications.
+  Denied,
+  /// The user wasn't asked yet.
+  Default,
+}
+
+/// The payload of the `tauri://notification` event, emitted when the user interacts with a
+/// notification sent by the window.
+#[cfg(notification_all)]
+#[derive(Serialize)]
+struct NotificationEventPayload {
+  id: Option<u32>,
+  #[serde(flatten)]
+  event: NotificationEvent,
 }
 
 /// The API descriptor.
@@ -25,6 +69,8 @@ pub struct NotificationOptions {
 pub enum Cmd {
   /// The show notification API.
   Notification { options: NotificationOptions },
+  /// The cancel scheduled notification API.
+  CancelNotification { id: u32 },
   /// The request notification permission API.
   RequestNotificationPermission,
   /// The notification permission check API.
@@ -32,32 +78,41 @@ pub enum Cmd {
 }
 
 impl Cmd {
-  pub fn run(self, identifier: String) -> crate::Result<InvokeResponse> {
+  pub async fn run<M: Params>(
+    self,
+    #[cfg(notification_all)] window: Window<M>,
+    #[cfg(not(notification_all))] _window: Window<M>,
+    #[cfg(notification_all)] identifier: String,
+    #[cfg(not(notification_all))] _identifier: String,
+  ) -> crate::Result<InvokeResponse> {
     match self {
-      Self::Notification { options } => {
-        #[cfg(notification_all)]
-        return send(options, identifier).map(Into::into);
-        #[cfg(not(notification_all))]
-        Err(crate::Error::ApiNotAllowlisted("notification".to_string()))
-      }
-      Self::IsNotificationPermissionGranted => {
-        #[cfg(notification_all)]
-        return is_permission_granted().map(Into::into);
-        #[cfg(not(notification_all))]
-        Err(crate::Error::ApiNotAllowlisted("notification".to_string()))
-      }
-      Self::RequestNotificationPermission => {
-        #[cfg(notification_all)]
-        return request_permission().map(Into::into);
-        #[cfg(not(notification_all))]
-        Err(crate::Error::ApiNotAllowlisted("notification".to_string()))
-      }
+      #[cfg(notification_all)]
+      Self::Notification { options } => send(window, options, identifier),
+      #[cfg(notification_all)]
+      Self::CancelNotification { id } => crate::api::notification::cancel(id)
+        .map_err(Into::into)
+        .map(Into::into),
+      #[cfg(notification_all)]
+      Self::IsNotificationPermissionGranted => is_permission_granted(),
+      #[cfg(notification_all)]
+      Self::RequestNotificationPermission => request_permission(&window).await.map(Into::into),
+      #[cfg(not(notification_all))]
+      _ => Err(crate::Error::ApiNotAllowlisted("notification".to_string())),
     }
   }
 }
 
 #[cfg(notification_all)]
-pub fn send(options: NotificationOptions, identifier: String) -> crate::Result<InvokeResponse> {
+pub fn send<M: Params>(
+  window: Window<M>,
+  options: NotificationOptions,
+  identifier: String,
+) -> crate::Result<InvokeResponse> {
+  // the webview can't show notifications without the user consent
+  if !matches!(permission()?, Permission::Granted) {
+    return Err(crate::Error::NotificationPermissionDenied);
+  }
+
   let mut notification = Notification::new(identifier).title(options.title);
   if let Some(body) = options.body {
     notification = notification.body(body);
@@ -65,42 +120,96 @@ pub fn send(options: NotificationOptions, identifier: String) -> crate::Result<I
   if let Some(icon) = options.icon {
     notification = notification.icon(icon);
   }
-  notification.show()?;
+  if let Some(id) = options.id {
+    notification = notification.id(id);
+  }
+  for action in options.actions {
+    notification = notification.action(action.id, action.title);
+  }
+  if let Some(urgency) = options.urgency {
+    notification = notification.urgency(urgency);
+  }
+  if let Some(sound) = options.sound {
+    notification = notification.sound(sound);
+  }
+  if let Some(timeout) = options.timeout {
+    notification = notification.timeout(std::time::Duration::from_millis(timeout));
+  }
+
+  if let Some(at) = options.schedule {
+    let at = std::time::UNIX_EPOCH + std::time::Duration::from_millis(at);
+    return Ok(notification.schedule(at)?.into());
+  }
+
+  let id = options.id;
+  notification.show_with_handler(move |event| {
+    let _ = window.emit_internal(
+      "tauri://notification".to_string(),
+      Some(NotificationEventPayload { id, event }),
+    );
+  })?;
   Ok(().into())
 }
 
+/// Emits the `tauri://notification` event of a scheduled notification to every window.
 #[cfg(notification_all)]
-pub fn is_permission_granted() -> crate::Result<InvokeResponse> {
+pub(crate) fn emit_scheduled_event<M: Params>(
+  manager: &WindowManager<M>,
+  id: u32,
+  event: NotificationEvent,
+) {
+  let _ = manager.emit_filter_internal(
+    "tauri://notification".to_string(),
+    Some(NotificationEventPayload {
+      id: Some(id),
+      event,
+    }),
+    |_| true,
+  );
+}
+
+/// The permission to show notifications, as stored in the settings.
+#[cfg(notification_all)]
+fn permission() -> crate::Result<Permission> {
   let settings = crate::settings::read_settings()?;
-  if let Some(allow_notification) = settings.allow_notification {
-    Ok(allow_notification.into())
-  } else {
-    Ok(().into())
-  }
+  Ok(match settings.allow_notification {
+    Some(true) => Permission::Granted,
+    Some(false) => Permission::Denied,
+    None => Permission::Default,
+  })
 }
 
 #[cfg(notification_all)]
-pub fn request_permission() -> crate::Result<String> {
-  let mut settings = crate::settings::read_settings()?;
-  let granted = "granted".to_string();
-  let denied = "denied".to_string();
-  if let Some(allow_notification) = settings.allow_notification {
-    return Ok(if allow_notification { granted } else { denied });
+pub fn is_permission_granted() -> crate::Result<InvokeResponse> {
+  match permission()? {
+    Permission::Granted => Ok(true.into()),
+    Permission::Denied => Ok(false.into()),
+    Permission::Default => Ok(().into()),
   }
-  let answer = crate::api::dialog::ask(
-    "Permissions",
-    "This app wants to show notifications. Do you allow?",
-  );
-  match answer {
-    crate::api::dialog::AskResponse::Yes => {
-      settings.allow_notification = Some(true);
-      crate::settings::write_settings(settings)?;
-      Ok(granted)
-    }
-    crate::api::dialog::AskResponse::No => {
-      settings.allow_notification = Some(false);
-      crate::settings::write_settings(settings)?;
-      Ok(denied)
-    }
+}
+
+/// Asks the user for the permission if they weren't asked yet, on the main thread.
+#[cfg(notification_all)]
+pub async fn request_permission<M: Params>(window: &Window<M>) -> crate::Result<Permission> {
+  let permission = permission()?;
+  if !matches!(permission, Permission::Default) {
+    return Ok(permission);
   }
+  let answer = window
+    .run_on_main_thread(|| {
+      crate::api::dialog::ask(
+        "Permissions",
+        "This app wants to show notifications. Do you allow?",
+      )
+    })
+    .await?;
+  let granted = matches!(answer, crate::api::dialog::AskResponse::Yes);
+  let mut settings = crate::settings::read_settings()?;
+  settings.allow_notification = Some(granted);
+  crate::settings::write_settings(settings)?;
+  Ok(if granted {
+    Permission::Granted
+  } else {
+    Permission::Denied
+  })
 }
@@ -4,8 +4,9 @@
 
 use crate::{
   api::{
-    command::{Command, CommandChild, CommandEvent},
+    command::{CommandChild, CommandEvent, Encoding},
     rpc::format_callback,
+    shell::Scope,
   },
   endpoints::InvokeResponse,
   Params, Window,
@@ -16,15 +17,76 @@ use std::{
   collections::HashMap,
   sync::{Arc, Mutex},
 };
+#[cfg(shell_execute)]
+use std::{
+  collections::HashSet,
+  thread::{self, JoinHandle},
+  time::Duration,
+};
+
+/// How long a child process gets to exit once its window or the app closes, before it is killed.
+#[cfg(shell_execute)]
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(2);
 
 type ChildId = u32;
-type ChildStore = Arc<Mutex<HashMap<ChildId, CommandChild>>>;
+type ChildStore = Arc<Mutex<HashMap<ChildId, TrackedChild>>>;
+
+/// A child process, with the label of the window that spawned it.
+struct TrackedChild {
+  window: String,
+  child: CommandChild,
+}
 
 fn command_childs() -> &'static ChildStore {
   static STORE: Lazy<ChildStore> = Lazy::new(Default::default);
   &STORE
 }
 
+/// The labels of the windows with a destroy handler terminating their child processes.
+#[cfg(shell_execute)]
+fn destroy_handlers() -> &'static Mutex<HashSet<String>> {
+  static HANDLERS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+  &HANDLERS
+}
+
+/// Terminates the tracked child processes matching `filter` in the background.
+#[cfg(shell_execute)]
+fn terminate<F: Fn(&TrackedChild) -> bool>(filter: F) -> Vec<JoinHandle<()>> {
+  let mut store = command_childs().lock().unwrap();
+  let pids = store
+    .iter()
+    .filter(|(_, tracked)| filter(tracked))
+    .map(|(pid, _)| *pid)
+    .collect::<Vec<_>>();
+  pids
+    .into_iter()
+    .filter_map(|pid| store.remove(&pid))
+    .map(|tracked| {
+      thread::spawn(move || {
+        let _ = tracked.child.terminate(TERMINATE_TIMEOUT);
+      })
+    })
+    .collect()
+}
+
+/// Terminates all the child processes spawned by the webviews, waiting for them to exit.
+#[cfg(shell_execute)]
+pub(crate) fn terminate_children() {
+  for handle in terminate(|_| true) {
+    let _ = handle.join();
+  }
+}
+
+/// Kills the child processes spawned by the webviews without blocking, see [`crate::exit`].
+#[cfg(shell_execute)]
+pub(crate) fn kill_children() {
+  if let Ok(store) = command_childs().try_lock() {
+    for tracked in store.values() {
+      tracked.child.kill_now();
+    }
+  }
+}
+
 #[derive(Deserialize)]
 #[serde(untagged)]
 pub enum Buffer {
@@ -32,11 +94,27 @@ pub enum Buffer {
   Raw(Vec<u8>),
 }
 
+/// The options of the execute API.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandOptions {
+  /// The environment variables of the process, which must be allowed by the shell scope.
+  #[serde(default)]
+  pub env: HashMap<String, String>,
+  /// The encoding of the output lines, e.g. `windows-1252`.
+  pub encoding: Option<String>,
+  /// Reads the output as raw bytes instead of lines.
+  #[serde(default)]
+  pub raw: bool,
+}
+
 /// The API descriptor.
 #[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
 pub enum Cmd {
   /// The execute script API.
+  ///
+  /// `program` is the name of a command of the shell scope, and `args` must match its arguments.
   #[serde(rename_all = "camelCase")]
   Execute {
     program: String,
@@ -44,6 +122,8 @@ pub enum Cmd {
     on_event_fn: String,
     #[serde(default)]
     sidecar: bool,
+    #[serde(default)]
+    options: CommandOptions,
   },
   StdinWrite {
     pid: ChildId,
@@ -59,26 +139,51 @@ pub enum Cmd {
 }
 
 impl Cmd {
-  pub fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
+  #[allow(unused_variables)]
+  pub fn run<M: Params>(self, window: Window<M>, scope: Scope) -> crate::Result<InvokeResponse> {
     match self {
       Self::Execute {
         program,
         args,
         on_event_fn,
         sidecar,
+        options,
       } => {
         #[cfg(shell_execute)]
         {
-          let mut command = if sidecar {
-            Command::new_sidecar(program)
-          } else {
-            Command::new(program)
-          };
-          command = command.args(args);
+          let mut command = scope.prepare(&program, args, options.env, sidecar)?;
+          if let Some(label) = options.encoding {
+            let encoding = Encoding::for_label(label.as_bytes())
+              .ok_or_else(|| crate::api::Error::Shell(format!("unknown encoding `{}`", label)))?;
+            // the output is split on new lines before decoding, e.g. UTF-16 can't be read as lines
+            if !encoding.is_ascii_compatible() {
+              return Err(
+                crate::api::Error::Shell(format!("unsupported encoding `{}`", label)).into(),
+              );
+            }
+            command = command.encoding(encoding);
+          }
+          if options.raw {
+            command = command.raw_output();
+          }
           let (mut rx, child) = command.spawn()?;
 
           let pid = child.pid();
-          command_childs().lock().unwrap().insert(pid, child);
+          let label = window.label().to_string();
+          command_childs().lock().unwrap().insert(
+            pid,
+            TrackedChild {
+              window: label.clone(),
+              child,
+            },
+          );
+          // the processes spawned by a window don't outlive it
+          if destroy_handlers().lock().unwrap().insert(label.clone()) {
+            window.on_destroy(move || {
+              destroy_handlers().lock().unwrap().remove(&label);
+              terminate(|tracked| tracked.window == label);
+            });
+          }
 
           crate::async_runtime::spawn(async move {
             while let Some(event) = rx.recv().await {
@@ -102,8 +207,16 @@ impl Cmd {
       Self::KillChild { pid } => {
         #[cfg(shell_execute)]
         {
-          if let Some(child) = command_childs().lock().unwrap().remove(&pid) {
-            child.kill()?;
+          // a window can only kill the processes it spawned
+          let label = window.label().to_string();
+          let mut store = command_childs().lock().unwrap();
+          if store
+            .get(&pid)
+            .map_or(false, |tracked| tracked.window == label)
+          {
+            if let Some(tracked) = store.remove(&pid) {
+              tracked.child.kill()?;
+            }
           }
           Ok(().into())
         }
@@ -115,10 +228,16 @@ impl Cmd {
       Self::StdinWrite { pid, buffer } => {
         #[cfg(shell_execute)]
         {
-          if let Some(child) = command_childs().lock().unwrap().get_mut(&pid) {
+          // a window can only write to the processes it spawned
+          let label = window.label().to_string();
+          let mut store = command_childs().lock().unwrap();
+          if let Some(tracked) = store
+            .get_mut(&pid)
+            .filter(|tracked| tracked.window == label)
+          {
             match buffer {
-              Buffer::Text(t) => child.write(t.as_bytes())?,
-              Buffer::Raw(r) => child.write(&r)?,
+              Buffer::Text(t) => tracked.child.write(t.as_bytes())?,
+              Buffer::Raw(r) => tracked.child.write(&r)?,
             }
           }
           Ok(().into())
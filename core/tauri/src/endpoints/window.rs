@@ -6,7 +6,7 @@ use crate::{
   api::config::WindowConfig, endpoints::InvokeResponse, runtime::window::PendingWindow, Manager,
   Params, Window,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::Icon;
 use std::path::PathBuf;
@@ -29,8 +29,19 @@ impl From<IconDto> for Icon {
 
 /// The API descriptor.
 #[derive(Deserialize)]
+pub struct Cmd {
+  /// The label of the window to operate on. Defaults to the window that sent the message.
+  label: Option<String>,
+  #[serde(flatten)]
+  cmd: WindowCmd,
+}
+
+/// The window commands.
+#[derive(Deserialize)]
 #[serde(tag = "cmd", rename_all = "camelCase")]
-pub enum Cmd {
+pub enum WindowCmd {
+  ListWindows,
+  GetCurrent,
   CreateWebview {
     options: WindowConfig,
   },
@@ -90,21 +101,60 @@ pub enum Cmd {
   SetIcon {
     icon: IconDto,
   },
+  StartFileDrag {
+    paths: Vec<PathBuf>,
+    icon: Option<IconDto>,
+  },
 }
 
 #[cfg(window_create)]
-#[derive(Clone, serde::Serialize)]
+#[derive(Clone, Serialize)]
 struct WindowCreatedEvent {
   label: String,
 }
 
+/// The information about a window returned by the `listWindows` and `getCurrent` commands.
+///
+/// The runtime can't query the visibility, size, position or focus of a window, nor reports
+/// their changes made by the user, so they are left out instead of guessed from the last commands.
+#[derive(Serialize)]
+struct WindowInfo {
+  label: String,
+  /// Whether this is the window that sent the message.
+  current: bool,
+  /// The URL of the page loaded on the window, see [`Window::current_url`].
+  url: Option<String>,
+}
+
 impl Cmd {
-  pub async fn run<M: Params>(self, mut window: Window<M>) -> crate::Result<InvokeResponse> {
+  pub async fn run<M: Params>(self, window: Window<M>) -> crate::Result<InvokeResponse> {
     if cfg!(not(window_all)) {
       Err(crate::Error::ApiNotAllowlisted("window > all".to_string()))
     } else {
-      match self {
-        Self::CreateWebview { options } => {
+      let current = window.label().clone();
+      let mut window = match self.label {
+        Some(label) => {
+          let label: M::Label = label.parse().map_err(|_| crate::Error::WebviewNotFound)?;
+          window
+            .get_window(&label)
+            .ok_or(crate::Error::WebviewNotFound)?
+        }
+        None => window,
+      };
+      let info = |window: &Window<M>| WindowInfo {
+        label: window.label().to_string(),
+        current: window.label() == &current,
+        url: window.current_url(),
+      };
+
+      match self.cmd {
+        WindowCmd::ListWindows => {
+          let mut windows = window.windows().values().map(info).collect::<Vec<_>>();
+          windows.sort_by(|a, b| a.label.cmp(&b.label));
+          return Ok(windows.into());
+        }
+        WindowCmd::GetCurrent => return Ok(info(&window).into()),
+        WindowCmd::CreateWebview { options } => {
           #[cfg(not(window_create))]
           return Err(crate::Error::ApiNotAllowlisted(
             "window > create".to_string(),
@@ -129,33 +179,36 @@ impl Cmd {
             )?;
           }
         }
-        Self::SetResizable { resizable } => window.set_resizable(resizable)?,
-        Self::SetTitle { title } => window.set_title(&title)?,
-        Self::Maximize => window.maximize()?,
-        Self::Unmaximize => window.unmaximize()?,
-        Self::Minimize => window.minimize()?,
-        Self::Unminimize => window.unminimize()?,
-        Self::Show => window.show()?,
-        Self::Hide => window.hide()?,
-        Self::Close => window.close()?,
-        Self::SetDecorations { decorations } => window.set_decorations(decorations)?,
-        Self::SetAlwaysOnTop { always_on_top } => window.set_always_on_top(always_on_top)?,
-        Self::SetWidth { width } => window.set_width(width)?,
-        Self::SetHeight { height } => window.set_height(height)?,
-        Self::Resize { width, height } => window.resize(width, height)?,
-        Self::SetMinSize {
+        WindowCmd::SetResizable { resizable } => window.set_resizable(resizable)?,
+        WindowCmd::SetTitle { title } => window.set_title(&title)?,
+        WindowCmd::Maximize => window.maximize()?,
+        WindowCmd::Unmaximize => window.unmaximize()?,
+        WindowCmd::Minimize => window.minimize()?,
+        WindowCmd::Unminimize => window.unminimize()?,
+        WindowCmd::Show => window.show()?,
+        WindowCmd::Hide => window.hide()?,
+        WindowCmd::Close => window.close()?,
+        WindowCmd::SetDecorations { decorations } => window.set_decorations(decorations)?,
+        WindowCmd::SetAlwaysOnTop { always_on_top } => window.set_always_on_top(always_on_top)?,
+        WindowCmd::SetWidth { width } => window.set_width(width)?,
+        WindowCmd::SetHeight { height } => window.set_height(height)?,
+        WindowCmd::Resize { width, height } => window.resize(width, height)?,
+        WindowCmd::SetMinSize {
           min_width,
           min_height,
         } => window.set_min_size(min_width, min_height)?,
-        Self::SetMaxSize {
+        WindowCmd::SetMaxSize {
           max_width,
           max_height,
         } => window.set_max_size(max_width, max_height)?,
-        Self::SetX { x } => window.set_x(x)?,
-        Self::SetY { y } => window.set_y(y)?,
-        Self::SetPosition { x, y } => window.set_position(x, y)?,
-        Self::SetFullscreen { fullscreen } => window.set_fullscreen(fullscreen)?,
-        Self::SetIcon { icon } => window.set_icon(icon.into())?,
+        WindowCmd::SetX { x } => window.set_x(x)?,
+        WindowCmd::SetY { y } => window.set_y(y)?,
+        WindowCmd::SetPosition { x, y } => window.set_position(x, y)?,
+        WindowCmd::SetFullscreen { fullscreen } => window.set_fullscreen(fullscreen)?,
+        WindowCmd::SetIcon { icon } => window.set_icon(icon.into())?,
+        WindowCmd::StartFileDrag { paths, icon } => {
+          window.start_file_drag(paths, icon.map(Into::into))?
+        }
       }
       Ok(().into())
     }
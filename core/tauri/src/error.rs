@@ -14,6 +14,12 @@ pub enum Error {
   /// Can't access webview dispatcher because the webview was closed or not found.
   #[error("webview not found: invalid label or it was closed")]
   WebviewNotFound,
+  /// Can't access a file handle because it was closed or not found.
+  #[error("file handle {0} not found: invalid id or it was closed")]
+  FileHandleNotFound(u32),
+  /// Can't stop a file system watcher because it was stopped or not found.
+  #[error("watcher {0} not found: invalid id or it was stopped")]
+  WatcherNotFound(u32),
   /// Failed to send message to webview.
   #[error("failed to send message to the webview")]
   FailedToSendMessage,
@@ -47,9 +53,39 @@ pub enum Error {
   /// API not whitelisted on tauri.conf.json
   #[error("'{0}' not on the allowlist (https://tauri.studio/docs/api/config#tauri.allowlist)")]
   ApiNotAllowlisted(String),
+  /// The accelerator of a global shortcut couldn't be parsed.
+  #[error("invalid accelerator `{0}`")]
+  InvalidAccelerator(String),
+  /// The accelerator is already used by a local accelerator or a global shortcut.
+  #[error("accelerator `{0}` conflicts with a registered accelerator or global shortcut")]
+  AcceleratorConflict(String),
+  /// A sidecar with the same name is already running.
+  #[error("sidecar `{0}` is already running")]
+  SidecarAlreadyRunning(String),
+  /// A logger is already installed on the `log` facade.
+  #[error("a logger is already installed")]
+  LoggerAlreadyInstalled,
+  /// The built-in logger is not installed.
+  #[error("the logger is not installed, see `Builder::logger`")]
+  LoggerNotInstalled,
+  /// Invalid log level.
+  #[error("invalid log level `{0}`")]
+  InvalidLogLevel(usize),
+  /// The user didn't allow the app to show notifications.
+  #[error("the notification permission was not granted")]
+  NotificationPermissionDenied,
   /// Invalid args when running a command.
   #[error("invalid args for command `{0}`: {1}")]
   InvalidArgs(&'static str, serde_json::Error),
+  /// A script evaluated with a result threw an exception.
+  #[error("script evaluation failed: {0}")]
+  Eval(String),
+  /// A script evaluated with a result didn't return in time.
+  #[error("script evaluation timed out")]
+  EvalTimeout,
+  /// The runtime doesn't support the requested webview feature.
+  #[error("{0} is not supported by the runtime")]
+  Unsupported(&'static str),
   /// Encountered an error in the setup hook,
   #[error("error encountered during setup hood: {0}")]
   Setup(#[from] Box<dyn std::error::Error>),
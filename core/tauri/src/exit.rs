@@ -0,0 +1,72 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Cleanup that must run when the application exits.
+//!
+//! The handlers registered with [`on_exit`] run on [`run_handlers`], when the runtime emits
+//! [`crate::RunEvent::Exit`]. A runtime can exit the process without emitting it, e.g. wry exits
+//! once its last window is closed natively, so the handlers registered with [`on_process_exit`]
+//! run when the process exits if [`run_handlers`] wasn't called. Those run from `atexit`, maybe
+//! while another thread holds a lock, so they must never block: they only try to lock, don't
+//! spawn threads and only send signals.
+
+use once_cell::sync::Lazy;
+use std::sync::{
+  atomic::{AtomicBool, Ordering},
+  Mutex, MutexGuard, Once,
+};
+
+type ExitHandler = Box<dyn FnOnce() + Send>;
+type ProcessExitHandler = Box<dyn Fn() + Send>;
+
+static HANDLERS: Lazy<Mutex<Vec<ExitHandler>>> = Lazy::new(Default::default);
+static PROCESS_EXIT_HANDLERS: Lazy<Mutex<Vec<ProcessExitHandler>>> = Lazy::new(Default::default);
+/// Whether the handlers ran on [`run_handlers`].
+static CLEANED_UP: AtomicBool = AtomicBool::new(false);
+
+fn handlers() -> MutexGuard<'static, Vec<ExitHandler>> {
+  HANDLERS.lock().expect("poisoned exit handlers")
+}
+
+/// Registers a handler to run once, when the runtime emits [`crate::RunEvent::Exit`].
+pub(crate) fn on_exit<F: FnOnce() + Send + 'static>(handler: F) {
+  handlers().push(Box::new(handler));
+}
+
+/// Registers a handler to run when the process exits without [`run_handlers`] being called.
+///
+/// The handler must not block, see the module documentation.
+pub(crate) fn on_process_exit<F: Fn() + Send + 'static>(handler: F) {
+  static REGISTER: Once = Once::new();
+  REGISTER.call_once(|| unsafe {
+    libc::atexit(run_at_exit);
+  });
+  PROCESS_EXIT_HANDLERS
+    .lock()
+    .expect("poisoned process exit handlers")
+    .push(Box::new(handler));
+}
+
+/// Runs the handlers registered with [`on_exit`], in the reverse order they were registered in.
+pub(crate) fn run_handlers() {
+  let registered = std::mem::take(&mut *handlers());
+  for handler in registered.into_iter().rev() {
+    handler();
+  }
+  CLEANED_UP.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn run_at_exit() {
+  if CLEANED_UP.load(Ordering::SeqCst) {
+    return;
+  }
+  // panics can't unwind into the C runtime
+  let _ = std::panic::catch_unwind(|| {
+    if let Ok(handlers) = PROCESS_EXIT_HANDLERS.try_lock() {
+      for handler in handlers.iter().rev() {
+        handler();
+      }
+    }
+  });
+}
@@ -0,0 +1,214 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Global shortcuts, registered by the application or by its windows.
+
+use crate::{
+  accelerator::{Accelerator, AcceleratorManager},
+  api::shortcuts::ShortcutManager,
+  runtime::manager::WindowManager,
+  Params, Window,
+};
+use std::{
+  collections::{HashMap, HashSet},
+  sync::MutexGuard,
+};
+
+/// The event emitted to a window when one of its global shortcuts is triggered.
+pub(crate) const GLOBAL_SHORTCUT_EVENT: &str = "tauri://global-shortcut";
+
+/// The registered global shortcuts, with the label of the window owning each of them.
+pub(crate) struct GlobalShortcuts<L> {
+  manager: ShortcutManager,
+  /// `None` for the shortcuts registered by the application.
+  owners: HashMap<String, Option<L>>,
+  /// The windows with a destroy handler unregistering their shortcuts.
+  destroy_handlers: HashSet<L>,
+}
+
+impl<L> Default for GlobalShortcuts<L> {
+  fn default() -> Self {
+    Self {
+      manager: Default::default(),
+      owners: Default::default(),
+      destroy_handlers: Default::default(),
+    }
+  }
+}
+
+impl<L> GlobalShortcuts<L> {
+  /// Whether a registered shortcut is triggered by the same keys as `accelerator`.
+  pub(crate) fn conflicts(&self, accelerator: &Accelerator) -> bool {
+    self
+      .owners
+      .keys()
+      .any(|registered| registered.parse::<Accelerator>().ok().as_ref() == Some(accelerator))
+  }
+}
+
+/// Manages the global shortcuts of the application.
+///
+/// Shortcuts registered by a window are unregistered once the window is destroyed. Shortcuts are
+/// identified by their accelerator, e.g. `CmdOrControl+Shift+K`, so unregistering one must use
+/// the accelerator it was registered with.
+pub struct GlobalShortcutManager<P: Params> {
+  manager: WindowManager<P>,
+}
+
+impl<P: Params> Clone for GlobalShortcutManager<P> {
+  fn clone(&self) -> Self {
+    Self {
+      manager: self.manager.clone(),
+    }
+  }
+}
+
+impl<P: Params> GlobalShortcutManager<P> {
+  pub(crate) fn new(manager: WindowManager<P>) -> Self {
+    Self { manager }
+  }
+
+  fn shortcuts(&self) -> MutexGuard<'_, GlobalShortcuts<P::Label>> {
+    self
+      .manager
+      .inner
+      .global_shortcuts
+      .lock()
+      .expect("poisoned global shortcuts")
+  }
+
+  /// Determines whether the given accelerator is registered or not.
+  pub fn is_registered(&self, accelerator: &str) -> crate::Result<bool> {
+    validate(accelerator)?;
+    Ok(
+      self
+        .shortcuts()
+        .manager
+        .is_registered(accelerator.to_string())?,
+    )
+  }
+
+  /// Registers a global shortcut, calling `handler` every time it is triggered.
+  pub fn register<F: FnMut() + Send + 'static>(
+    &self,
+    accelerator: &str,
+    handler: F,
+  ) -> crate::Result<()> {
+    self.register_owned(accelerator, None, handler)
+  }
+
+  /// Registers a global shortcut owned by `window`, emitting the `tauri://global-shortcut` event
+  /// to it every time the shortcut is triggered.
+  pub(crate) fn register_for_window(
+    &self,
+    window: &Window<P>,
+    accelerator: &str,
+  ) -> crate::Result<()> {
+    let target = window.clone();
+    let payload = accelerator.to_string();
+    self.register_owned(accelerator, Some(window.label().clone()), move || {
+      let _ = target.emit_internal(GLOBAL_SHORTCUT_EVENT.to_string(), Some(payload.clone()));
+    })?;
+
+    // a single handler unregisters all the shortcuts of the window
+    if self
+      .shortcuts()
+      .destroy_handlers
+      .insert(window.label().clone())
+    {
+      let shortcuts = self.clone();
+      let label = window.label().clone();
+      window.on_destroy(move || {
+        let _ = shortcuts.unregister_owned(&label, None);
+        shortcuts.shortcuts().destroy_handlers.remove(&label);
+      });
+    }
+    Ok(())
+  }
+
+  fn register_owned<F: FnMut() + Send + 'static>(
+    &self,
+    accelerator: &str,
+    owner: Option<P::Label>,
+    handler: F,
+  ) -> crate::Result<()> {
+    validate(accelerator)?;
+    // a global shortcut would shadow the local accelerator with the same keys
+    if let Ok(parsed) = accelerator.parse() {
+      if AcceleratorManager::new(self.manager.clone()).conflicts(&parsed) {
+        return Err(crate::Error::AcceleratorConflict(accelerator.to_string()));
+      }
+    }
+    let mut shortcuts = self.shortcuts();
+    shortcuts
+      .manager
+      .register(accelerator.to_string(), handler)?;
+    shortcuts.owners.insert(accelerator.to_string(), owner);
+    Ok(())
+  }
+
+  /// Unregisters a global shortcut.
+  pub fn unregister(&self, accelerator: &str) -> crate::Result<()> {
+    validate(accelerator)?;
+    let mut shortcuts = self.shortcuts();
+    shortcuts.manager.unregister(accelerator.to_string())?;
+    shortcuts.owners.remove(accelerator);
+    Ok(())
+  }
+
+  /// Unregisters all global shortcuts, including the ones registered by windows.
+  pub fn unregister_all(&self) -> crate::Result<()> {
+    let mut shortcuts = self.shortcuts();
+    shortcuts.manager.unregister_all()?;
+    shortcuts.owners.clear();
+    Ok(())
+  }
+
+  /// Unregisters the shortcuts owned by the window with the given label, or only `accelerator`
+  /// if it is owned by it.
+  pub(crate) fn unregister_owned(
+    &self,
+    label: &P::Label,
+    accelerator: Option<&str>,
+  ) -> crate::Result<()> {
+    if let Some(accelerator) = accelerator {
+      validate(accelerator)?;
+    }
+    let mut shortcuts = self.shortcuts();
+    let owned = shortcuts
+      .owners
+      .iter()
+      .filter(|(registered, owner)| {
+        owner.as_ref() == Some(label) && accelerator.map_or(true, |a| a == registered.as_str())
+      })
+      .map(|(registered, _)| registered.clone())
+      .collect::<Vec<_>>();
+    for accelerator in owned {
+      shortcuts.owners.remove(&accelerator);
+      shortcuts.manager.unregister(accelerator)?;
+    }
+    Ok(())
+  }
+}
+
+/// Checks that the accelerator can be parsed.
+fn validate(accelerator: &str) -> crate::Result<()> {
+  tauri_hotkey::parse_hotkey(accelerator)
+    .map(|_| ())
+    .map_err(|_| crate::Error::InvalidAccelerator(accelerator.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+  use super::validate;
+
+  #[test]
+  fn check_validate() {
+    assert!(validate("CmdOrControl+Shift+K").is_ok());
+    assert!(matches!(
+      validate("Shift+NotAKey"),
+      Err(crate::Error::InvalidAccelerator(accelerator)) if accelerator == "Shift+NotAKey"
+    ));
+  }
+}
@@ -3,8 +3,12 @@
 // SPDX-License-Identifier: MIT
 
 use crate::{
-  api::rpc::{format_callback, format_callback_result},
-  runtime::app::App,
+  api::rpc::{
+    format_binary_callback, format_callback, format_callback_result, format_inline_binary_callback,
+  },
+  endpoints::{InvokeBody, InvokeResponse},
+  runtime::{app::App, webview::FileDropEvent},
+  sealed::ManagerBase,
   Params, Window,
 };
 use serde::{Deserialize, Serialize};
@@ -19,6 +23,13 @@ pub type InvokeHandler<M> = dyn Fn(InvokeMessage<M>) + Send + Sync + 'static;
 /// A closure that is run once every time a window is created and loaded.
 pub type OnPageLoad<M> = dyn Fn(Window<M>, PageLoadPayload) + Send + Sync + 'static;
 
+/// A closure that is run when a window navigates to a new URL.
+pub type OnNavigation<M> = dyn Fn(Window<M>, &str) + Send + Sync + 'static;
+
+/// A closure that is run when files are dragged onto a window, after the file drop events are
+/// emitted to it. Returns whether the webview also receives the native drop event.
+pub type OnFileDrop<M> = dyn Fn(Window<M>, &FileDropEvent) -> bool + Send + Sync + 'static;
+
 /// The payload for the [`OnPageLoad`] hook.
 #[derive(Debug, Clone, Deserialize)]
 pub struct PageLoadPayload {
@@ -32,6 +43,21 @@ impl PageLoadPayload {
   }
 }
 
+/// The result of a script evaluated with [`Window::eval_with_result`].
+#[derive(Debug, Deserialize)]
+pub(crate) struct EvalResultPayload {
+  pub(crate) id: uuid::Uuid,
+  #[serde(default)]
+  pub(crate) result: serde_json::Value,
+  pub(crate) error: Option<String>,
+}
+
+/// The accelerator triggered by the webview.
+#[derive(Debug, Deserialize)]
+pub(crate) struct AcceleratorPayload {
+  pub(crate) accelerator: String,
+}
+
 /// Payload from an invoke call.
 #[derive(Debug, Deserialize)]
 pub(crate) struct InvokePayload {
@@ -41,10 +67,25 @@ pub(crate) struct InvokePayload {
   pub(crate) error: String,
   #[serde(rename = "mainThread", default)]
   pub(crate) main_thread: bool,
+  /// The origin of the page that sent the invoke, e.g. `tauri://localhost`.
+  #[serde(rename = "__invokeOrigin", default)]
+  pub(crate) origin: Option<String>,
   #[serde(flatten)]
   pub(crate) inner: serde_json::Value,
 }
 
+impl InvokePayload {
+  /// Whether the invoke was sent by a page served through the `tauri` custom protocol.
+  ///
+  /// The window may have navigated since, so its current URL can't tell.
+  fn is_tauri_origin(&self) -> bool {
+    self
+      .origin
+      .as_deref()
+      .map_or(false, |origin| origin.starts_with("tauri://"))
+  }
+}
+
 /// An invoke message.
 pub struct InvokeMessage<M: Params> {
   window: Window<M>,
@@ -99,6 +140,48 @@ impl<M: Params> InvokeMessage<M> {
     }
   }
 
+  /// Reply to the invoke promise with an async task resolving to an [`InvokeResponse`],
+  /// which may carry raw bytes instead of a JSON value.
+  pub(crate) fn respond_invoke_async<
+    F: Future<Output = crate::Result<InvokeResponse>> + Send + 'static,
+  >(
+    self,
+    task: F,
+  ) {
+    let window = self.window;
+    let fetch_binary = self.payload.is_tauri_origin();
+    let success_callback = self.payload.callback;
+    let error_callback = self.payload.error;
+    let task = async move {
+      match task.await.and_then(InvokeResponse::into_body) {
+        Ok(InvokeBody::Json(value)) => Self::return_result(
+          window,
+          Result::<_, ()>::Ok(value),
+          success_callback,
+          error_callback,
+        ),
+        Ok(InvokeBody::Raw(bytes)) => Self::return_binary(
+          window,
+          bytes,
+          fetch_binary,
+          success_callback,
+          error_callback,
+        ),
+        Err(e) => Self::return_result(
+          window,
+          Result::<(), _>::Err(e.to_string()),
+          success_callback,
+          error_callback,
+        ),
+      }
+    };
+    if self.payload.main_thread {
+      crate::async_runtime::block_on(task);
+    } else {
+      crate::async_runtime::spawn(task);
+    }
+  }
+
   /// Reply to the invoke promise running the given closure.
   pub fn respond_closure<T: Serialize, Err: Serialize, F: FnOnce() -> Result<T, Err>>(self, f: F) {
     Self::return_closure(self.window, f, self.payload.callback, self.payload.error)
@@ -114,6 +197,20 @@ impl<M: Params> InvokeMessage<M> {
     )
   }
 
+  /// Resolve the invoke promise with raw bytes.
+  ///
+  /// The bytes are not serialized to JSON; the promise resolves to an `Uint8Array`.
+  pub fn resolve_binary(self, bytes: Vec<u8>) {
+    let fetch_binary = self.payload.is_tauri_origin();
+    Self::return_binary(
+      self.window,
+      bytes,
+      fetch_binary,
+      self.payload.callback,
+      self.payload.error,
+    )
+  }
+
   /// Reject the invoke promise with a value.
   pub fn reject<S: Serialize>(self, value: S) {
     Self::return_result(
@@ -167,4 +264,32 @@ impl<M: Params> InvokeMessage<M> {
 
     let _ = window.eval(&callback_string);
   }
+
+  /// Sends the bytes to the JS promise described by the `success_callback` and `error_callback` function names.
+  ///
+  /// The bytes are served once through the `tauri` custom protocol and fetched as an `ArrayBuffer` by the webview.
+  /// Pages loaded from another origin, like a dev server, can't fetch them, so `fetch_binary` is `false` for the invokes
+  /// they send and the bytes are inlined in the script instead.
+  pub(crate) fn return_binary(
+    window: Window<M>,
+    bytes: Vec<u8>,
+    fetch_binary: bool,
+    success_callback: String,
+    error_callback: String,
+  ) {
+    let callback_string = if fetch_binary {
+      let url = window
+        .manager()
+        .register_binary_response(window.label().clone(), bytes);
+      match format_binary_callback(&url, success_callback, error_callback.clone()) {
+        Ok(callback_string) => callback_string,
+        Err(e) => format_callback(error_callback, &e.to_string())
+          .expect("unable to serialize binary response error to json"),
+      }
+    } else {
+      format_inline_binary_callback(success_callback, &bytes)
+    };
+
+    let _ = window.eval(&callback_string);
+  }
 }
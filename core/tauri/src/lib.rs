@@ -15,16 +15,22 @@ pub(crate) use crate::api::private::async_runtime;
 pub use error::Error;
 pub use tauri_macros::{command, generate_handler};
 
+pub mod accelerator;
 pub mod api;
 /// The Tauri API endpoints.
 mod endpoints;
 mod error;
 mod event;
+mod exit;
+#[cfg(global_shortcut_all)]
+pub mod global_shortcut;
 mod hooks;
+pub mod logging;
 pub mod plugin;
 pub mod runtime;
 /// The Tauri-specific settings for your runtime e.g. notification permission status.
 pub mod settings;
+pub mod sidecar;
 #[cfg(feature = "updater")]
 pub mod updater;
 
@@ -39,7 +45,7 @@ use crate::api::config::Config;
 use crate::event::{Event, EventHandler};
 use crate::runtime::tag::Tag;
 use crate::runtime::window::PendingWindow;
-use crate::runtime::{Dispatch, Runtime};
+use crate::runtime::{Dispatch, Runtime, RuntimeHandle as _};
 use serde::Serialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -48,7 +54,7 @@ use std::path::PathBuf;
 pub use {
   api::config::WindowUrl,
   hooks::InvokeMessage,
-  runtime::app::{App, Builder},
+  runtime::app::{App, AppHandle, Builder, ExitRequestApi, RunEvent},
   runtime::webview::Attributes,
   runtime::window::export::Window,
 };
@@ -88,6 +94,7 @@ macro_rules! tauri_build_context {
 }
 
 /// A icon definition.
+#[derive(Debug, Clone)]
 pub enum Icon {
   /// Icon from file path.
   File(PathBuf),
@@ -160,6 +167,7 @@ pub trait Manager<M: Params>: sealed::ManagerBase<M> {
     match self.runtime() {
       Runtime(runtime) => runtime.create_window(pending),
       Dispatch(mut dispatcher) => dispatcher.create_window(pending),
+      RuntimeHandle(handle) => handle.create_window(pending),
     }
     .map(|window| self.manager().attach_window(window))
   }
@@ -216,6 +224,9 @@ pub(crate) mod sealed {
 
     /// A dispatcher to the running [`Runtime`].
     Dispatch(<P::Runtime as Runtime>::Dispatcher),
+
+    /// A handle to the [`Runtime`].
+    RuntimeHandle(<P::Runtime as Runtime>::Handle),
   }
 
   /// Managed handle to the application runtime.
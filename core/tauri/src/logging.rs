@@ -0,0 +1,492 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Built-in logging sink.
+//!
+//! Tauri, its plugins and the sidecar supervisor report through the [`log`] facade.
+//! Installing a [`Logger`] with [`Builder::logger`](crate::Builder::logger) makes the records
+//! available everywhere:
+//!
+//! - they are written to a rotating log file under the app log dir ([`log_dir`]);
+//! - the `console.*` calls of the webviews are forwarded to the same log,
+//!   using the `webview:<window label>` target;
+//! - the frontend can subscribe to the Rust records, emitted to the window as the `tauri://log` event.
+//!
+//! Webview records are never forwarded back to the frontend, so a subscriber logging
+//! to the console doesn't loop.
+//!
+//! Without a [`Logger`], the records go to the logger installed by the app, if any.
+//!
+//! [`log_dir`]: crate::api::path::log_dir
+
+use crate::api::PackageInfo;
+#[cfg(log_all)]
+use crate::{Params, Window};
+#[cfg(log_all)]
+use log::Level;
+use log::{Log, Metadata, Record};
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::{
+  cell::Cell,
+  collections::HashMap,
+  ffi::OsString,
+  fs::{self, File, OpenOptions},
+  io::{self, Write},
+  path::PathBuf,
+  sync::Mutex,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+pub use log::LevelFilter;
+
+/// The event emitted to the subscribed windows for each log record.
+#[cfg(log_all)]
+const LOG_EVENT: &str = "tauri://log";
+/// The target prefix of the records forwarded from the webviews.
+const WEBVIEW_TARGET_PREFIX: &str = "webview:";
+const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+const DEFAULT_ROTATIONS: usize = 5;
+
+static SINK: OnceCell<Sink> = OnceCell::new();
+
+thread_local! {
+  /// Whether a record is being forwarded on this thread, so records logged while emitting are dropped.
+  static FORWARDING: Cell<bool> = Cell::new(false);
+}
+
+/// The built-in logger configuration.
+#[derive(Debug)]
+pub struct Logger {
+  level: LevelFilter,
+  dir: Option<PathBuf>,
+  file_name: Option<String>,
+  max_file_size: u64,
+  rotations: usize,
+  stderr: bool,
+  forward_console: bool,
+}
+
+impl Default for Logger {
+  fn default() -> Self {
+    Self {
+      level: LevelFilter::Info,
+      dir: None,
+      file_name: None,
+      max_file_size: DEFAULT_MAX_FILE_SIZE,
+      rotations: DEFAULT_ROTATIONS,
+      stderr: cfg!(debug_assertions),
+      forward_console: true,
+    }
+  }
+}
+
+impl Logger {
+  /// Creates a logger writing `Info` records and above to `${APP_NAME}.log` in the app log dir.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// The maximum level of the records to log.
+  pub fn level(mut self, level: LevelFilter) -> Self {
+    self.level = level;
+    self
+  }
+
+  /// The directory of the log files. Defaults to [`log_dir`](crate::api::path::log_dir).
+  pub fn directory(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.dir.replace(dir.into());
+    self
+  }
+
+  /// The name of the log file. Defaults to `${APP_NAME}.log`.
+  pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+    self.file_name.replace(file_name.into());
+    self
+  }
+
+  /// The size in bytes after which the log file is rotated. Defaults to 2 MiB.
+  pub fn max_file_size(mut self, max_file_size: u64) -> Self {
+    self.max_file_size = max_file_size;
+    self
+  }
+
+  /// The number of rotated files to keep, as `${file_name}.1` (the most recent) to `${file_name}.${rotations}`.
+  /// Defaults to 5.
+  pub fn rotations(mut self, rotations: usize) -> Self {
+    self.rotations = rotations;
+    self
+  }
+
+  /// Whether the records are also written to stderr. Defaults to `true` on debug builds.
+  pub fn stderr(mut self, stderr: bool) -> Self {
+    self.stderr = stderr;
+    self
+  }
+
+  /// Whether the `console.*` calls of the webviews are forwarded to the log. Defaults to `true`.
+  ///
+  /// The calls are forwarded with the log API, so it must be allowlisted with `tauri > allowlist > log > all`.
+  pub fn forward_console(mut self, forward_console: bool) -> Self {
+    self.forward_console = forward_console;
+    self
+  }
+
+  /// Sets the logger as the [`log`] facade logger.
+  pub(crate) fn install(self, package_info: &PackageInfo) -> crate::Result<()> {
+    let dir = match self.dir {
+      Some(dir) => dir,
+      None => crate::api::path::log_dir().ok_or_else(|| {
+        crate::api::Error::Path("unable to determine the log dir path".to_string())
+      })?,
+    };
+    fs::create_dir_all(&dir)?;
+    let file_name = self
+      .file_name
+      .unwrap_or_else(|| format!("{}.log", package_info.name));
+
+    let sink = Sink {
+      level: self.level,
+      stderr: self.stderr,
+      forward_console: self.forward_console,
+      file: Mutex::new(RotatingFile {
+        path: dir.join(file_name),
+        max_size: self.max_file_size,
+        rotations: self.rotations,
+        file: None,
+        size: 0,
+      }),
+      subscribers: Default::default(),
+    };
+    SINK
+      .set(sink)
+      .map_err(|_| crate::Error::LoggerAlreadyInstalled)?;
+    log::set_logger(SINK.get().expect("logger not set"))
+      .map_err(|_| crate::Error::LoggerAlreadyInstalled)?;
+    log::set_max_level(self.level);
+    Ok(())
+  }
+}
+
+/// A log record, as emitted to the frontend.
+#[derive(Debug, Clone, Serialize)]
+struct RecordPayload {
+  /// The record level, from 1 (`Error`) to 5 (`Trace`).
+  level: usize,
+  target: String,
+  message: String,
+  /// Milliseconds since the UNIX epoch.
+  timestamp: u64,
+}
+
+/// A window subscribed to the log records.
+struct Subscriber {
+  level: LevelFilter,
+  forward: Box<dyn Fn(&RecordPayload) + Send>,
+}
+
+struct Sink {
+  level: LevelFilter,
+  stderr: bool,
+  forward_console: bool,
+  file: Mutex<RotatingFile>,
+  subscribers: Mutex<HashMap<String, Subscriber>>,
+}
+
+impl Sink {
+  fn forward(&self, record: &Record<'_>, message: &str, timestamp: u64) {
+    FORWARDING.with(|forwarding| {
+      if forwarding.replace(true) {
+        return;
+      }
+      let payload = RecordPayload {
+        level: record.level() as usize,
+        target: record.target().to_string(),
+        message: message.to_string(),
+        timestamp,
+      };
+      for subscriber in self
+        .subscribers
+        .lock()
+        .expect("poisoned log subscribers")
+        .values()
+        .filter(|subscriber| record.level() <= subscriber.level)
+      {
+        (subscriber.forward)(&payload);
+      }
+      forwarding.set(false);
+    })
+  }
+}
+
+impl Log for Sink {
+  fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+    metadata.level() <= self.level
+  }
+
+  fn log(&self, record: &Record<'_>) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis() as u64)
+      .unwrap_or_default();
+    let message = record.args().to_string();
+    let line = format!(
+      "{} [{}] [{}] {}\n",
+      format_timestamp(timestamp),
+      record.level(),
+      record.target(),
+      message
+    );
+
+    if self.stderr {
+      eprint!("{}", line);
+    }
+    // there's nowhere to report a failure to write the log file
+    let _ = self
+      .file
+      .lock()
+      .expect("poisoned log file")
+      .write_line(&line);
+
+    if !record.target().starts_with(WEBVIEW_TARGET_PREFIX) {
+      self.forward(record, &message, timestamp);
+    }
+  }
+
+  fn flush(&self) {
+    if let Some(file) = &mut self.file.lock().expect("poisoned log file").file {
+      let _ = file.flush();
+    }
+  }
+}
+
+/// A log file, rotated once it reaches its maximum size.
+struct RotatingFile {
+  path: PathBuf,
+  max_size: u64,
+  rotations: usize,
+  file: Option<File>,
+  size: u64,
+}
+
+impl RotatingFile {
+  fn write_line(&mut self, line: &str) -> io::Result<()> {
+    if self.file.is_none() {
+      let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&self.path)?;
+      self.size = file.metadata()?.len();
+      self.file.replace(file);
+    }
+    if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+      self.rotate()?;
+    }
+    let file = self.file.as_mut().expect("log file not open");
+    file.write_all(line.as_bytes())?;
+    self.size += line.len() as u64;
+    Ok(())
+  }
+
+  /// Shifts the rotated files by one, drops the oldest and starts a new file.
+  fn rotate(&mut self) -> io::Result<()> {
+    self.file.take();
+    if self.rotations == 0 {
+      fs::remove_file(&self.path)?;
+    } else {
+      for index in (1..self.rotations).rev() {
+        let from = self.rotated_path(index);
+        if from.exists() {
+          fs::rename(from, self.rotated_path(index + 1))?;
+        }
+      }
+      fs::rename(&self.path, self.rotated_path(1))?;
+    }
+    self.file.replace(File::create(&self.path)?);
+    self.size = 0;
+    Ok(())
+  }
+
+  fn rotated_path(&self, index: usize) -> PathBuf {
+    let mut path = OsString::from(self.path.as_os_str());
+    path.push(format!(".{}", index));
+    path.into()
+  }
+}
+
+/// Formats milliseconds since the UNIX epoch as a RFC 3339 UTC date.
+fn format_timestamp(millis: u64) -> String {
+  let secs = millis / 1000;
+  let (year, month, day) = civil_from_days(secs / 86400);
+  let time = secs % 86400;
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+    year,
+    month,
+    day,
+    time / 3600,
+    time % 3600 / 60,
+    time % 60,
+    millis % 1000
+  )
+}
+
+/// Converts days since the UNIX epoch to a (year, month, day) date of the proleptic Gregorian calendar.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+  let days = days + 719_468;
+  let era = days / 146_097;
+  let day_of_era = days - era * 146_097;
+  let year_of_era =
+    (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+  let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  // months starting from March, so the leap day is the last day of the year
+  let month = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month + 2) / 5 + 1;
+  let month = if month < 10 { month + 3 } else { month - 9 };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  (year, month, day)
+}
+
+/// Converts a level received from the frontend, from 1 (`Error`) to 5 (`Trace`).
+#[cfg(log_all)]
+fn level_from_usize(level: usize) -> Option<Level> {
+  match level {
+    1 => Some(Level::Error),
+    2 => Some(Level::Warn),
+    3 => Some(Level::Info),
+    4 => Some(Level::Debug),
+    5 => Some(Level::Trace),
+    _ => None,
+  }
+}
+
+/// Converts a level filter received from the frontend, from 0 (`Off`) to 5 (`Trace`).
+#[cfg(log_all)]
+pub(crate) fn level_filter_from_usize(level: usize) -> Option<LevelFilter> {
+  if level == 0 {
+    Some(LevelFilter::Off)
+  } else {
+    level_from_usize(level).map(|level| level.to_level_filter())
+  }
+}
+
+/// The script forwarding the `console.*` calls of the webview, if the logger is installed and
+/// forwards them, and the log API is allowlisted.
+pub(crate) fn initialization_script() -> Option<String> {
+  if !cfg!(log_all) {
+    return None;
+  }
+  SINK.get().filter(|sink| sink.forward_console).map(|_| {
+    r#"
+      var levels = { error: 1, warn: 2, info: 3, log: 3, debug: 4, trace: 5 }
+      Object.keys(levels).forEach(function (method) {
+        var original = console[method]
+        console[method] = function () {
+          original.apply(console, arguments)
+          var message = Array.prototype.map.call(arguments, function (arg) {
+            if (typeof arg === 'string') return arg
+            if (arg instanceof Error) return arg.stack || arg.message
+            try {
+              return JSON.stringify(arg)
+            } catch (_) {
+              return String(arg)
+            }
+          }).join(' ')
+          window.__TAURI__.invoke('tauri', {
+            __tauriModule: 'Log',
+            message: { cmd: 'log', level: levels[method], message: message }
+          }).catch(function () {})
+        }
+      })
+    "#
+    .to_string()
+  })
+}
+
+/// Logs a record sent by the frontend of the given window.
+#[cfg(log_all)]
+pub(crate) fn log_webview(label: &str, level: usize, message: &str) -> crate::Result<()> {
+  let level = level_from_usize(level).ok_or(crate::Error::InvalidLogLevel(level))?;
+  let target = format!("{}{}", WEBVIEW_TARGET_PREFIX, label);
+  log::log!(target: target.as_str(), level, "{}", message);
+  Ok(())
+}
+
+/// Emits the records up to the given level to the window, until it is destroyed or unsubscribes.
+#[cfg(log_all)]
+pub(crate) fn subscribe<P: Params>(window: Window<P>, level: LevelFilter) -> crate::Result<()> {
+  let sink = SINK.get().ok_or(crate::Error::LoggerNotInstalled)?;
+  let label = window.label().to_string();
+  let target = window.clone();
+  sink
+    .subscribers
+    .lock()
+    .expect("poisoned log subscribers")
+    .insert(
+      label.clone(),
+      Subscriber {
+        level,
+        forward: Box::new(move |payload| {
+          let _ = target.emit_internal(LOG_EVENT.to_string(), Some(payload.clone()));
+        }),
+      },
+    );
+  window.on_destroy(move || unsubscribe(&label));
+  Ok(())
+}
+
+/// Stops emitting the records to the window with the given label.
+#[cfg(log_all)]
+pub(crate) fn unsubscribe(label: &str) {
+  if let Some(sink) = SINK.get() {
+    sink
+      .subscribers
+      .lock()
+      .expect("poisoned log subscribers")
+      .remove(label);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{format_timestamp, RotatingFile};
+
+  #[test]
+  fn check_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+    assert_eq!(
+      format_timestamp(951_782_400_000),
+      "2000-02-29T00:00:00.000Z"
+    );
+    assert_eq!(
+      format_timestamp(1_700_000_000_123),
+      "2023-11-14T22:13:20.123Z"
+    );
+  }
+
+  #[test]
+  fn check_rotation() {
+    let dir = tempfile::tempdir().expect("failed to create temp dir");
+    let path = dir.path().join("app.log");
+    let mut file = RotatingFile {
+      path: path.clone(),
+      max_size: 10,
+      rotations: 2,
+      file: None,
+      size: 0,
+    };
+    for line in &["first\n", "second\n", "third\n", "fourth\n"] {
+      file.write_line(line).expect("failed to write log line");
+    }
+
+    let read = |index: usize| std::fs::read_to_string(file.rotated_path(index)).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "fourth\n");
+    assert_eq!(read(1), "third\n");
+    assert_eq!(read(2), "second\n");
+    assert!(!file.rotated_path(3).exists());
+  }
+}
@@ -40,6 +40,13 @@ pub trait Plugin<M: Params>: Send {
   #[allow(unused_variables)]
   fn on_page_load(&mut self, window: Window<M>, payload: PageLoadPayload) {}
 
+  /// Callback invoked when the webview navigates to `url`.
+  ///
+  /// Like [`Builder::on_navigation`](crate::Builder::on_navigation), this hook can't prevent the
+  /// navigation and isn't notified of every navigation.
+  #[allow(unused_variables)]
+  fn on_navigation(&mut self, window: Window<M>, url: &str) {}
+
   /// Add invoke_handler API extension commands.
   #[allow(unused_variables)]
   fn extend_api(&mut self, message: InvokeMessage<M>) {}
@@ -100,6 +107,14 @@ impl<M: Params> PluginStore<M> {
       .for_each(|plugin| plugin.on_page_load(window.clone(), payload.clone()))
   }
 
+  /// Runs the on_navigation hook for all plugins in the store.
+  pub(crate) fn on_navigation(&mut self, window: Window<M>, url: &str) {
+    self
+      .store
+      .values_mut()
+      .for_each(|plugin| plugin.on_navigation(window.clone(), url))
+  }
+
   pub(crate) fn extend_api(&mut self, command: String, message: InvokeMessage<M>) {
     let target = command
       .replace("plugin:", "")
@@ -3,22 +3,134 @@
 // SPDX-License-Identifier: MIT
 
 use crate::{
-  api::{assets::Assets, config::WindowUrl},
-  hooks::{InvokeHandler, InvokeMessage, OnPageLoad, PageLoadPayload, SetupHook},
+  api::{app::start_application, assets::Assets, config::WindowUrl},
+  hooks::{
+    InvokeHandler, InvokeMessage, OnFileDrop, OnNavigation, OnPageLoad, PageLoadPayload, SetupHook,
+  },
   plugin::{Plugin, PluginStore},
   runtime::{
-    flavors::wry::Wry, manager::WindowManager, tag::Tag, webview::Attributes,
-    window::PendingWindow, Dispatch, Runtime,
+    flavors::wry::Wry,
+    manager::WindowManager,
+    tag::Tag,
+    webview::{Attributes, FileDropEvent},
+    window::PendingWindow,
+    Dispatch, ExitRequestedEventAction, RunEvent as RuntimeRunEvent, Runtime, RuntimeHandle,
   },
   sealed::{ManagerBase, RuntimeOrDispatch},
   Context, Manager, Params, Window,
 };
+use std::sync::mpsc::Sender;
 
+#[cfg(global_shortcut_all)]
+use crate::global_shortcut::GlobalShortcutManager;
 use crate::runtime::manager::Args;
 #[cfg(feature = "updater")]
 use crate::updater;
+use crate::{accelerator::AcceleratorManager, logging::Logger, sidecar::SidecarSupervisor};
+
+/// An application event, emitted by the event loop.
+///
+/// The wry runtime doesn't report its event loop events, so it only emits
+/// [`RunEvent::ExitRequested`] and [`RunEvent::Exit`] when the application exits through Tauri,
+/// i.e. when the last window is closed with [`crate::Window::close`] or on [`AppHandle::exit`].
+/// Closing the last window natively isn't reported: wry exits the process without emitting them.
+#[derive(Debug)]
+pub enum RunEvent {
+  /// The application is about to exit.
+  Exit,
+  /// The last window was closed and the application is about to exit.
+  ///
+  /// Use [`ExitRequestApi::prevent_exit`] to keep it running, e.g. for system tray applications.
+  ExitRequested {
+    /// The API to prevent the exit.
+    api: ExitRequestApi,
+  },
+}
+
+/// The API exposed on [`RunEvent::ExitRequested`].
+#[derive(Debug)]
+pub struct ExitRequestApi(Sender<ExitRequestedEventAction>);
+
+impl ExitRequestApi {
+  /// Prevents the application from exiting, keeping it running without any window.
+  pub fn prevent_exit(&self) {
+    let _ = self.0.send(ExitRequestedEventAction::Prevent);
+  }
+}
+
+/// A cloneable handle to the application, usable from any thread or async task.
+///
+/// This type implements [`Manager`] which allows for manipulation of global application items.
+pub struct AppHandle<P: Params> {
+  runtime_handle: <P::Runtime as Runtime>::Handle,
+  manager: WindowManager<P>,
+}
+
+impl<P: Params> Clone for AppHandle<P> {
+  fn clone(&self) -> Self {
+    Self {
+      runtime_handle: self.runtime_handle.clone(),
+      manager: self.manager.clone(),
+    }
+  }
+}
+
+impl<P: Params> Manager<P> for AppHandle<P> {}
+impl<P: Params> ManagerBase<P> for AppHandle<P> {
+  fn manager(&self) -> &WindowManager<P> {
+    &self.manager
+  }
+
+  fn runtime(&mut self) -> RuntimeOrDispatch<'_, P> {
+    RuntimeOrDispatch::RuntimeHandle(self.runtime_handle.clone())
+  }
+}
+
+impl<P: Params> AppHandle<P> {
+  /// Creates a handle to the application of the given manager.
+  pub(crate) fn new(manager: WindowManager<P>) -> Self {
+    Self {
+      runtime_handle: manager.runtime_handle(),
+      manager,
+    }
+  }
+
+  /// Exits the application with the given exit code, emitting [`RunEvent::Exit`] first.
+  ///
+  /// Called from the [`App::run`] callback, the application exits once the callback returns.
+  pub fn exit(&self, exit_code: i32) -> crate::Result<()> {
+    self.runtime_handle.exit(exit_code)
+  }
+
+  /// Starts a new instance of the application, then exits this one with [`AppHandle::exit`].
+  pub fn restart(&self) -> crate::Result<()> {
+    start_application(None)?;
+    self.exit(0)
+  }
 
-/// A handle to the currently running application.
+  /// The manager of the global shortcuts of the application.
+  #[cfg(global_shortcut_all)]
+  pub fn global_shortcut_manager(&self) -> GlobalShortcutManager<P> {
+    GlobalShortcutManager::new(self.manager.clone())
+  }
+
+  /// The manager of the local accelerators of the application.
+  pub fn accelerator_manager(&self) -> AcceleratorManager<P> {
+    AcceleratorManager::new(self.manager.clone())
+  }
+
+  /// The supervisor of the sidecars of the application.
+  pub fn sidecar_supervisor(&self) -> SidecarSupervisor<P> {
+    SidecarSupervisor::new(self.manager.clone())
+  }
+
+  /// The paths the webview can access with the file system API.
+  pub fn fs_scope(&self) -> crate::api::file::Scope {
+    self.manager.inner.fs_scope.clone()
+  }
+}
+
+/// The application, before it runs.
 ///
 /// This type implements [`Manager`] which allows for manipulation of global application items.
 pub struct App<P: Params> {
@@ -37,6 +149,67 @@ impl<P: Params> ManagerBase<P> for App<P> {
   }
 }
 
+impl<P: Params> App<P> {
+  /// Gets a handle to the application, usable from other threads.
+  pub fn handle(&self) -> AppHandle<P> {
+    AppHandle::new(self.manager.clone())
+  }
+
+  /// The manager of the global shortcuts of the application.
+  #[cfg(global_shortcut_all)]
+  pub fn global_shortcut_manager(&self) -> GlobalShortcutManager<P> {
+    GlobalShortcutManager::new(self.manager.clone())
+  }
+
+  /// The manager of the local accelerators of the application.
+  pub fn accelerator_manager(&self) -> AcceleratorManager<P> {
+    AcceleratorManager::new(self.manager.clone())
+  }
+
+  /// The supervisor of the sidecars of the application.
+  pub fn sidecar_supervisor(&self) -> SidecarSupervisor<P> {
+    SidecarSupervisor::new(self.manager.clone())
+  }
+
+  /// The paths the webview can access with the file system API.
+  pub fn fs_scope(&self) -> crate::api::file::Scope {
+    self.manager.inner.fs_scope.clone()
+  }
+
+  /// Runs the application, calling `callback` with the [`RunEvent`]s of its event loop.
+  ///
+  /// Returns once the event loop exits, if the runtime supports it.
+  pub fn run<F>(self, mut callback: F)
+  where
+    F: FnMut(&AppHandle<P>, RunEvent) + Send + 'static,
+  {
+    // show the notifications scheduled by a previous run, reporting their interactions to every
+    // window since the one that scheduled them may be gone
+    #[cfg(notification_all)]
+    {
+      let manager = self.manager.clone();
+      crate::api::notification::on_scheduled_event(move |id, event| {
+        crate::endpoints::emit_scheduled_notification_event(&manager, id, event)
+      });
+      crate::api::notification::start_scheduler();
+    }
+
+    let handle = self.handle();
+    self.runtime.run(move |event| {
+      let event = match event {
+        RuntimeRunEvent::Exit => {
+          crate::exit::run_handlers();
+          RunEvent::Exit
+        }
+        RuntimeRunEvent::ExitRequested { tx } => RunEvent::ExitRequested {
+          api: ExitRequestApi(tx),
+        },
+      };
+      callback(&handle, event)
+    })
+  }
+}
+
 #[cfg(feature = "updater")]
 impl<M: Params> App<M> {
   /// Runs the updater hook with built-in dialog.
@@ -113,11 +286,20 @@ where
   /// Page load hook.
   on_page_load: Box<OnPageLoad<Args<E, L, A, R>>>,
 
+  /// Navigation hook.
+  on_navigation: Box<OnNavigation<Args<E, L, A, R>>>,
+
+  /// File drop hook.
+  on_file_drop: Box<OnFileDrop<Args<E, L, A, R>>>,
+
   /// windows to create when starting up.
   pending_windows: Vec<PendingWindow<Args<E, L, A, R>>>,
 
   /// All passed plugins
   plugins: PluginStore<Args<E, L, A, R>>,
+
+  /// The built-in logger to install.
+  logger: Option<Logger>,
 }
 
 impl<E, L, A, R> Builder<E, L, A, R>
@@ -133,8 +315,11 @@ where
       setup: Box::new(|_| Ok(())),
       invoke_handler: Box::new(|_| ()),
       on_page_load: Box::new(|_, _| ()),
+      on_navigation: Box::new(|_, _| ()),
+      on_file_drop: Box::new(|_, _| false),
       pending_windows: Default::default(),
       plugins: PluginStore::default(),
+      logger: None,
     }
   }
 
@@ -165,12 +350,48 @@ where
     self
   }
 
+  /// Defines the navigation hook, notified when a window navigates to a new URL.
+  ///
+  /// The hook is a notification: it can't prevent the navigation, and it isn't notified of every
+  /// navigation. It runs for [`Window::navigate`] calls and, on the wry runtime, for the link
+  /// clicks and form submissions reported by a script injected on the page. Navigations started
+  /// through `window.location`, the history API or by a page that removes the script's listeners
+  /// are not reported.
+  pub fn on_navigation<F>(mut self, on_navigation: F) -> Self
+  where
+    F: Fn(Window<Args<E, L, A, R>>, &str) + Send + Sync + 'static,
+  {
+    self.on_navigation = Box::new(on_navigation);
+    self
+  }
+
+  /// Defines the file drop hook, run when files are dragged onto a window.
+  ///
+  /// The `tauri://file-drop-hover`, `tauri://file-drop` and `tauri://file-drop-cancelled` events
+  /// are emitted to the window first. Return `true` to also let the webview receive the native
+  /// drop event, e.g. to handle it with the HTML drag and drop API.
+  pub fn on_file_drop<F>(mut self, on_file_drop: F) -> Self
+  where
+    F: Fn(Window<Args<E, L, A, R>>, &FileDropEvent) -> bool + Send + Sync + 'static,
+  {
+    self.on_file_drop = Box::new(on_file_drop);
+    self
+  }
+
   /// Adds a plugin to the runtime.
   pub fn plugin<P: Plugin<Args<E, L, A, R>> + 'static>(mut self, plugin: P) -> Self {
     self.plugins.register(plugin);
     self
   }
 
+  /// Installs the built-in [`Logger`] as the `log` facade logger, see [`crate::logging`].
+  ///
+  /// [`Builder::build`] fails if another logger is already installed.
+  pub fn logger(mut self, logger: Logger) -> Self {
+    self.logger.replace(logger);
+    self
+  }
+
   /// Creates a new webview.
   pub fn create_window<F>(mut self, label: L, url: WindowUrl, setup: F) -> Self
   where
@@ -183,14 +404,22 @@ where
     self
   }
 
-  /// Runs the configured Tauri application.
-  pub fn run(mut self, context: Context<A>) -> crate::Result<()> {
+  /// Builds the application, creating its windows and running the setup hook.
+  ///
+  /// The returned [`App`] is not running yet, see [`App::run`].
+  pub fn build(mut self, context: Context<A>) -> crate::Result<App<Args<E, L, A, R>>> {
+    if let Some(logger) = self.logger.take() {
+      logger.install(&context.package_info)?;
+    }
+
     let manager = WindowManager::with_handlers(
       context,
       self.plugins,
       self.invoke_handler,
       self.on_page_load,
-    );
+      self.on_navigation,
+      self.on_file_drop,
+    )?;
 
     // set up all the windows defined in the config
     for config in manager.config().tauri.windows.clone() {
@@ -206,11 +435,18 @@ where
     }
 
     manager.initialize_plugins()?;
+    AcceleratorManager::new(manager.clone()).register_config()?;
 
-    let mut app = App {
-      runtime: R::new()?,
-      manager,
-    };
+    // the processes spawned by the webviews don't outlive the app
+    #[cfg(shell_execute)]
+    {
+      crate::exit::on_exit(crate::endpoints::terminate_children);
+      crate::exit::on_process_exit(crate::endpoints::kill_children);
+    }
+
+    let runtime = R::new()?;
+    manager.set_runtime_handle(runtime.handle());
+    let mut app = App { runtime, manager };
 
     let pending_labels = self
       .pending_windows
@@ -234,8 +470,20 @@ where
     #[cfg(feature = "updater")]
     app.run_updater(main_window);
 
+    // registered after the child processes handler, so the sidecars are stopped first
+    let supervisor = app.sidecar_supervisor();
+    crate::exit::on_exit(move || supervisor.stop_all());
+    let supervisor = app.sidecar_supervisor();
+    crate::exit::on_process_exit(move || supervisor.kill_all());
+    app.sidecar_supervisor().start_config()?;
+
     (self.setup)(&mut app)?;
-    app.runtime.run();
+    Ok(app)
+  }
+
+  /// Runs the configured Tauri application.
+  pub fn run(self, context: Context<A>) -> crate::Result<()> {
+    self.build(context)?.run(|_, _| {});
     Ok(())
   }
 }
@@ -246,3 +494,37 @@ impl<A: Assets> Default for Builder<String, String, A, Wry> {
     Self::new()
   }
 }
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    runtime::flavors::mock::{mock_context, MockRuntime},
+    Builder, Manager, RunEvent,
+  };
+  use std::sync::{Arc, Mutex};
+
+  #[test]
+  fn check_exit_requested() {
+    let app = Builder::<String, String, _, MockRuntime>::new()
+      .build(mock_context())
+      .expect("failed to build mock app");
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    window.close().expect("failed to close window");
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    app.run(move |handle, event| {
+      let name = match &event {
+        RunEvent::Exit => "exit",
+        RunEvent::ExitRequested { .. } => "exit-requested",
+      };
+      recorded.lock().unwrap().push(name);
+      if let RunEvent::ExitRequested { api } = event {
+        api.prevent_exit();
+        handle.exit(0).expect("failed to exit");
+      }
+    });
+
+    assert_eq!(*events.lock().unwrap(), vec!["exit-requested", "exit"]);
+  }
+}
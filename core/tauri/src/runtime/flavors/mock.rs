@@ -0,0 +1,1165 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! A mock [`Runtime`] that runs a Tauri application without creating any webview.
+//!
+//! Every call made through a [`MockDispatcher`] is recorded, including the scripts sent to
+//! [`Dispatch::eval_script`], and the webview side of a window can be simulated by sending fake
+//! [`RpcRequest`]s, page loads, navigations and file drop events to it.
+//!
+//! The application doesn't need to run to be tested; running it starts an event loop on the
+//! current thread that ends once [`RuntimeHandle::exit`] is called or the last window is closed.
+//!
+//! # Example
+//! ```
+//! use tauri::{
+//!   runtime::flavors::mock::{mock_context, MockDispatcher, MockRuntime},
+//!   Builder, Manager,
+//! };
+//!
+//! let app = Builder::<String, String, _, MockRuntime>::new()
+//!   .invoke_handler(|message| {
+//!     let window = message.window();
+//!     message.respond_async(async move {
+//!       window
+//!         .emit(&"greeted".to_string(), Some("hello"))
+//!         .map_err(|e| e.to_string())?;
+//!       Ok::<_, String>("world")
+//!     })
+//!   })
+//!   .build(mock_context())
+//!   .expect("failed to build app");
+//!
+//! let window = app.get_window(&"main".to_string()).unwrap();
+//! let mock = MockDispatcher::from_window(&window);
+//!
+//! let invoke = mock.invoke("greet", serde_json::json!({}));
+//! assert_eq!(mock.assert_invoke_ok(&invoke), "world");
+//! assert_eq!(mock.assert_emitted("greeted"), "hello");
+//! ```
+
+use crate::{
+  api::{
+    assets::{AssetKey, Assets},
+    config::{Config, WindowConfig},
+    PackageInfo,
+  },
+  runtime::{
+    webview::{
+      Attributes, AttributesBase, CustomProtocol, FileDropEvent, FileDropPosition, RpcRequest,
+    },
+    window::{DetachedWindow, PendingWindow},
+    Dispatch, ExitRequestedEventAction, Params, RunEvent, Runtime, RuntimeHandle,
+  },
+  Context, Icon, Window,
+};
+use serde_json::Value as JsonValue;
+use std::{
+  borrow::Cow,
+  convert::TryFrom,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver, Sender},
+    Arc, Condvar, Mutex,
+  },
+  time::{Duration, Instant},
+};
+
+/// How long the assertion helpers wait for an asynchronous response.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A window icon that was converted for the [`MockRuntime`].
+#[derive(Debug, Clone)]
+pub struct MockIcon(pub Icon);
+
+impl TryFrom<Icon> for MockIcon {
+  type Error = crate::Error;
+  fn try_from(icon: Icon) -> Result<Self, Self::Error> {
+    Ok(Self(icon))
+  }
+}
+
+/// The [`Attributes`] of a [`MockRuntime`] window, storing every value it was built with.
+#[derive(Debug, Clone, Default)]
+pub struct MockAttributes {
+  /// The initialization scripts injected on the window.
+  pub initialization_scripts: Vec<String>,
+  /// The window x position.
+  pub x: Option<f64>,
+  /// The window y position.
+  pub y: Option<f64>,
+  /// The window width.
+  pub width: Option<f64>,
+  /// The window height.
+  pub height: Option<f64>,
+  /// The window min width.
+  pub min_width: Option<f64>,
+  /// The window min height.
+  pub min_height: Option<f64>,
+  /// The window max width.
+  pub max_width: Option<f64>,
+  /// The window max height.
+  pub max_height: Option<f64>,
+  /// Whether the window is resizable or not.
+  pub resizable: Option<bool>,
+  /// The window title.
+  pub title: Option<String>,
+  /// Whether the window starts as fullscreen or not.
+  pub fullscreen: Option<bool>,
+  /// Whether the window is maximized or not.
+  pub maximized: Option<bool>,
+  /// Whether the window is visible or not.
+  pub visible: Option<bool>,
+  /// Whether the window is transparent or not.
+  pub transparent: Option<bool>,
+  /// Whether the window has decorations or not.
+  pub decorations: Option<bool>,
+  /// Whether the window is always on top of other windows or not.
+  pub always_on_top: Option<bool>,
+  /// Whether the window is hidden from the taskbar or not.
+  pub skip_taskbar: Option<bool>,
+  /// Whether the context menu is enabled or not.
+  pub context_menu: Option<bool>,
+  /// The window icon.
+  pub icon: Option<MockIcon>,
+  /// The webview user data path.
+  pub user_data_path: Option<PathBuf>,
+  /// The webview url.
+  pub url: Option<String>,
+}
+
+impl AttributesBase for MockAttributes {}
+impl Attributes for MockAttributes {
+  type Icon = MockIcon;
+
+  fn new() -> Self {
+    Default::default()
+  }
+
+  fn with_config(config: WindowConfig) -> Self {
+    Self {
+      x: config.x,
+      y: config.y,
+      width: Some(config.width),
+      height: Some(config.height),
+      min_width: config.min_width,
+      min_height: config.min_height,
+      max_width: config.max_width,
+      max_height: config.max_height,
+      resizable: Some(config.resizable),
+      title: Some(config.title),
+      fullscreen: Some(config.fullscreen),
+      maximized: Some(config.maximized),
+      visible: Some(config.visible),
+      transparent: Some(config.transparent),
+      decorations: Some(config.decorations),
+      always_on_top: Some(config.always_on_top),
+      skip_taskbar: Some(config.skip_taskbar),
+      context_menu: Some(config.context_menu),
+      ..Default::default()
+    }
+  }
+
+  fn initialization_script(mut self, init: &str) -> Self {
+    self.initialization_scripts.push(init.to_string());
+    self
+  }
+
+  fn x(mut self, x: f64) -> Self {
+    self.x = Some(x);
+    self
+  }
+
+  fn y(mut self, y: f64) -> Self {
+    self.y = Some(y);
+    self
+  }
+
+  fn width(mut self, width: f64) -> Self {
+    self.width = Some(width);
+    self
+  }
+
+  fn height(mut self, height: f64) -> Self {
+    self.height = Some(height);
+    self
+  }
+
+  fn min_width(mut self, min_width: f64) -> Self {
+    self.min_width = Some(min_width);
+    self
+  }
+
+  fn min_height(mut self, min_height: f64) -> Self {
+    self.min_height = Some(min_height);
+    self
+  }
+
+  fn max_width(mut self, max_width: f64) -> Self {
+    self.max_width = Some(max_width);
+    self
+  }
+
+  fn max_height(mut self, max_height: f64) -> Self {
+    self.max_height = Some(max_height);
+    self
+  }
+
+  fn resizable(mut self, resizable: bool) -> Self {
+    self.resizable = Some(resizable);
+    self
+  }
+
+  fn title<S: Into<String>>(mut self, title: S) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  fn fullscreen(mut self, fullscreen: bool) -> Self {
+    self.fullscreen = Some(fullscreen);
+    self
+  }
+
+  fn maximized(mut self, maximized: bool) -> Self {
+    self.maximized = Some(maximized);
+    self
+  }
+
+  fn visible(mut self, visible: bool) -> Self {
+    self.visible = Some(visible);
+    self
+  }
+
+  fn transparent(mut self, transparent: bool) -> Self {
+    self.transparent = Some(transparent);
+    self
+  }
+
+  fn decorations(mut self, decorations: bool) -> Self {
+    self.decorations = Some(decorations);
+    self
+  }
+
+  fn always_on_top(mut self, always_on_top: bool) -> Self {
+    self.always_on_top = Some(always_on_top);
+    self
+  }
+
+  fn skip_taskbar(mut self, skip: bool) -> Self {
+    self.skip_taskbar = Some(skip);
+    self
+  }
+
+  fn context_menu(mut self, enabled: bool) -> Self {
+    self.context_menu = Some(enabled);
+    self
+  }
+
+  fn icon(mut self, icon: Self::Icon) -> Self {
+    self.icon = Some(icon);
+    self
+  }
+
+  fn has_icon(&self) -> bool {
+    self.icon.is_some()
+  }
+
+  fn user_data_path(mut self, user_data_path: Option<PathBuf>) -> Self {
+    self.user_data_path = user_data_path;
+    self
+  }
+
+  fn url(mut self, url: String) -> Self {
+    self.url = Some(url);
+    self
+  }
+
+  fn build(self) -> Self {
+    self
+  }
+}
+
+/// A call made through a [`MockDispatcher`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DispatchCall {
+  /// [`Dispatch::set_resizable`].
+  SetResizable(bool),
+  /// [`Dispatch::set_title`].
+  SetTitle(String),
+  /// [`Dispatch::maximize`].
+  Maximize,
+  /// [`Dispatch::unmaximize`].
+  Unmaximize,
+  /// [`Dispatch::minimize`].
+  Minimize,
+  /// [`Dispatch::unminimize`].
+  Unminimize,
+  /// [`Dispatch::show`].
+  Show,
+  /// [`Dispatch::hide`].
+  Hide,
+  /// [`Dispatch::close`].
+  Close,
+  /// [`Dispatch::set_decorations`].
+  SetDecorations(bool),
+  /// [`Dispatch::set_always_on_top`].
+  SetAlwaysOnTop(bool),
+  /// [`Dispatch::set_width`].
+  SetWidth(f64),
+  /// [`Dispatch::set_height`].
+  SetHeight(f64),
+  /// [`Dispatch::resize`].
+  Resize(f64, f64),
+  /// [`Dispatch::set_min_size`].
+  SetMinSize(f64, f64),
+  /// [`Dispatch::set_max_size`].
+  SetMaxSize(f64, f64),
+  /// [`Dispatch::set_x`].
+  SetX(f64),
+  /// [`Dispatch::set_y`].
+  SetY(f64),
+  /// [`Dispatch::set_position`].
+  SetPosition(f64, f64),
+  /// [`Dispatch::set_fullscreen`].
+  SetFullscreen(bool),
+  /// [`Dispatch::set_icon`].
+  SetIcon,
+  /// [`Dispatch::start_file_drag`] with the dragged paths.
+  StartFileDrag(Vec<PathBuf>),
+  /// [`Dispatch::run_on_main_thread`].
+  RunOnMainThread,
+  /// [`Dispatch::navigate`].
+  Navigate(String),
+  /// [`Dispatch::reload`].
+  Reload,
+  /// [`Dispatch::go_back`].
+  GoBack,
+  /// [`Dispatch::go_forward`].
+  GoForward,
+  /// [`Dispatch::print`].
+  Print,
+  /// [`Dispatch::set_zoom`].
+  SetZoom(f64),
+  /// [`Dispatch::open_devtools`].
+  OpenDevtools,
+  /// [`Dispatch::close_devtools`].
+  CloseDevtools,
+  /// [`Dispatch::eval_script`] with the evaluated script.
+  EvalScript(String),
+}
+
+/// An event emitted to a [`MockRuntime`] window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmittedEvent {
+  /// The event name.
+  pub event: String,
+  /// The event payload.
+  pub payload: JsonValue,
+}
+
+/// A fake invoke sent to a [`MockRuntime`] window with [`MockDispatcher::invoke`].
+#[derive(Debug, Clone)]
+pub struct MockInvoke {
+  callback: String,
+  error: String,
+}
+
+impl MockInvoke {
+  /// The name of the callback resolving the invoke promise.
+  pub fn callback(&self) -> &str {
+    &self.callback
+  }
+
+  /// The name of the callback rejecting the invoke promise.
+  pub fn error(&self) -> &str {
+    &self.error
+  }
+}
+
+type MockRpcHandler = Box<dyn Fn(MockDispatcher, RpcRequest) + Send>;
+type MockFileDropHandler = Box<dyn Fn(FileDropEvent, MockDispatcher) -> bool + Send>;
+type MockNavigationHandler = Box<dyn Fn(String, MockDispatcher) + Send>;
+
+/// The state shared between all dispatchers of a single window.
+struct MockWindow {
+  label: String,
+  attributes: MockAttributes,
+  calls: Mutex<Vec<DispatchCall>>,
+  calls_changed: Condvar,
+  rpc_handler: Mutex<Option<MockRpcHandler>>,
+  custom_protocol: Mutex<Option<CustomProtocol>>,
+  file_drop_handler: Mutex<Option<MockFileDropHandler>>,
+  navigation_handler: Mutex<Option<MockNavigationHandler>>,
+  closed: AtomicBool,
+}
+
+/// A message sent to the [`MockRuntime`] event loop.
+enum Message {
+  WindowClosed,
+  Exit,
+}
+
+/// State shared between the [`MockRuntime`], its handles and its dispatchers.
+struct MockContext {
+  windows: Mutex<Vec<Arc<MockWindow>>>,
+  sender: Mutex<Sender<Message>>,
+}
+
+impl MockContext {
+  fn send(&self, message: Message) -> crate::Result<()> {
+    self
+      .sender
+      .lock()
+      .expect("poisoned mock event loop sender")
+      .send(message)
+      .map_err(|_| crate::Error::FailedToSendMessage)
+  }
+
+  fn open_windows(&self) -> usize {
+    self
+      .windows
+      .lock()
+      .expect("poisoned mock window store")
+      .iter()
+      .filter(|window| !window.closed.load(Ordering::SeqCst))
+      .count()
+  }
+}
+
+/// Registers a new window on the runtime, keeping its handlers around for the test helpers.
+fn create_window<M: Params<Runtime = MockRuntime>>(
+  context: &Arc<MockContext>,
+  pending: PendingWindow<M>,
+) -> crate::Result<DetachedWindow<M>> {
+  let PendingWindow {
+    attributes,
+    rpc_handler,
+    custom_protocol,
+    file_drop_handler,
+    navigation_handler,
+    label,
+    ..
+  } = pending;
+
+  let rpc_handler = rpc_handler.map(|handler| {
+    let label = label.clone();
+    Box::new(move |dispatcher, request| {
+      handler(
+        DetachedWindow {
+          label: label.clone(),
+          dispatcher,
+        },
+        request,
+      )
+    }) as MockRpcHandler
+  });
+
+  let file_drop_handler = file_drop_handler.map(|handler| {
+    let label = label.clone();
+    Box::new(move |event, dispatcher| {
+      handler(
+        event,
+        DetachedWindow {
+          label: label.clone(),
+          dispatcher,
+        },
+      )
+    }) as MockFileDropHandler
+  });
+
+  let navigation_handler = navigation_handler.map(|handler| {
+    let label = label.clone();
+    Box::new(move |url, dispatcher| {
+      handler(
+        url,
+        DetachedWindow {
+          label: label.clone(),
+          dispatcher,
+        },
+      )
+    }) as MockNavigationHandler
+  });
+
+  let dispatcher = MockDispatcher {
+    window: Arc::new(MockWindow {
+      label: label.to_string(),
+      attributes: attributes.build(),
+      calls: Default::default(),
+      calls_changed: Condvar::new(),
+      rpc_handler: Mutex::new(rpc_handler),
+      custom_protocol: Mutex::new(custom_protocol),
+      file_drop_handler: Mutex::new(file_drop_handler),
+      navigation_handler: Mutex::new(navigation_handler),
+      closed: AtomicBool::new(false),
+    }),
+    context: context.clone(),
+  };
+
+  context
+    .windows
+    .lock()
+    .expect("poisoned mock window store")
+    .push(dispatcher.window.clone());
+
+  Ok(DetachedWindow { label, dispatcher })
+}
+
+/// A handle to the [`MockRuntime`].
+#[derive(Clone)]
+pub struct MockHandle {
+  context: Arc<MockContext>,
+}
+
+impl RuntimeHandle for MockHandle {
+  type Runtime = MockRuntime;
+
+  fn create_window<M: Params<Runtime = Self::Runtime>>(
+    &self,
+    pending: PendingWindow<M>,
+  ) -> crate::Result<DetachedWindow<M>> {
+    create_window(&self.context, pending)
+  }
+
+  /// Stops the event loop; the exit code is ignored since the process keeps running.
+  fn exit(&self, _code: i32) -> crate::Result<()> {
+    self.context.send(Message::Exit)
+  }
+}
+
+/// A [`Runtime`] that doesn't create any native window, useful for testing.
+///
+/// Its event loop runs on the thread calling [`Runtime::run`] until the application exits.
+pub struct MockRuntime {
+  context: Arc<MockContext>,
+  receiver: Receiver<Message>,
+}
+
+impl MockRuntime {
+  /// The dispatchers of all windows created on this runtime, in creation order.
+  pub fn windows(&self) -> Vec<MockDispatcher> {
+    self
+      .context
+      .windows
+      .lock()
+      .expect("poisoned mock window store")
+      .iter()
+      .map(|window| MockDispatcher {
+        window: window.clone(),
+        context: self.context.clone(),
+      })
+      .collect()
+  }
+}
+
+impl Runtime for MockRuntime {
+  type Dispatcher = MockDispatcher;
+  type Handle = MockHandle;
+
+  fn new() -> crate::Result<Self> {
+    let (sender, receiver) = channel();
+    Ok(Self {
+      context: Arc::new(MockContext {
+        windows: Default::default(),
+        sender: Mutex::new(sender),
+      }),
+      receiver,
+    })
+  }
+
+  fn handle(&self) -> Self::Handle {
+    MockHandle {
+      context: self.context.clone(),
+    }
+  }
+
+  fn create_window<M: Params<Runtime = Self>>(
+    &mut self,
+    pending: PendingWindow<M>,
+  ) -> crate::Result<DetachedWindow<M>> {
+    create_window(&self.context, pending)
+  }
+
+  fn run<F: FnMut(RunEvent) + Send + 'static>(self, mut callback: F) {
+    // the context keeps a sender alive, so this only ends with an exit message
+    while let Ok(message) = self.receiver.recv() {
+      for message in std::iter::once(message).chain(self.receiver.try_iter()) {
+        let exit = match message {
+          Message::Exit => true,
+          Message::WindowClosed if self.context.open_windows() == 0 => {
+            let (tx, rx) = channel();
+            callback(RunEvent::ExitRequested { tx });
+            !matches!(rx.try_recv(), Ok(ExitRequestedEventAction::Prevent))
+          }
+          Message::WindowClosed => false,
+        };
+        if exit {
+          callback(RunEvent::Exit);
+          return;
+        }
+      }
+    }
+  }
+}
+
+/// The [`Dispatch`] of a [`MockRuntime`] window.
+///
+/// Besides recording the calls made through it, it simulates the webview side of the window.
+#[derive(Clone)]
+pub struct MockDispatcher {
+  window: Arc<MockWindow>,
+  context: Arc<MockContext>,
+}
+
+impl MockDispatcher {
+  /// The dispatcher of a window managed by a [`MockRuntime`] application.
+  pub fn from_window<P: Params<Runtime = MockRuntime>>(window: &Window<P>) -> Self {
+    window.dispatcher()
+  }
+
+  /// The label of the window.
+  pub fn label(&self) -> &str {
+    &self.window.label
+  }
+
+  /// The attributes the window was created with.
+  pub fn attributes(&self) -> &MockAttributes {
+    &self.window.attributes
+  }
+
+  /// All calls made through the dispatchers of this window, in order.
+  pub fn calls(&self) -> Vec<DispatchCall> {
+    self
+      .window
+      .calls
+      .lock()
+      .expect("poisoned mock calls")
+      .clone()
+  }
+
+  /// Forgets the recorded calls.
+  pub fn clear_calls(&self) {
+    self
+      .window
+      .calls
+      .lock()
+      .expect("poisoned mock calls")
+      .clear();
+  }
+
+  /// All scripts evaluated on the window, in order.
+  pub fn evaluated_scripts(&self) -> Vec<String> {
+    self
+      .calls()
+      .into_iter()
+      .filter_map(|call| match call {
+        DispatchCall::EvalScript(script) => Some(script),
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// All events emitted to the window, in order.
+  pub fn emitted_events(&self) -> Vec<EmittedEvent> {
+    self
+      .evaluated_scripts()
+      .iter()
+      .filter_map(|script| parse_emitted_event(script))
+      .collect()
+  }
+
+  /// Sends a RPC request to the window, as the webview would.
+  ///
+  /// Requests are ignored if the window doesn't handle RPC, e.g. when it loads a remote URL.
+  pub fn send_rpc(&self, request: RpcRequest) {
+    if let Some(handler) = &*self
+      .window
+      .rpc_handler
+      .lock()
+      .expect("poisoned rpc handler")
+    {
+      handler(self.clone(), request);
+    }
+  }
+
+  /// Calls a command like `window.__TAURI__.invoke(command, args)`.
+  ///
+  /// The response can be read with [`MockDispatcher::invoke_response`].
+  pub fn invoke<S: Into<String>>(&self, command: S, args: JsonValue) -> MockInvoke {
+    let invoke = MockInvoke {
+      callback: format!("_{}", rand::random::<u32>()),
+      error: format!("_{}", rand::random::<u32>()),
+    };
+
+    let mut payload = match args {
+      JsonValue::Object(map) => map,
+      JsonValue::Null => Default::default(),
+      other => panic!("invoke arguments must be an object, got {}", other),
+    };
+    payload.insert("callback".into(), invoke.callback.clone().into());
+    payload.insert("error".into(), invoke.error.clone().into());
+
+    self.send_rpc(RpcRequest {
+      command: command.into(),
+      params: Some(JsonValue::Array(vec![JsonValue::Object(payload)])),
+    });
+    invoke
+  }
+
+  /// Notifies the application that the window finished loading `url`, triggering the page load hooks.
+  pub fn load_page<S: Into<String>>(&self, url: S) {
+    self.invoke("__initialized", serde_json::json!({ "url": url.into() }));
+  }
+
+  /// Answers the last script evaluated with [`Window::eval_with_result`], as the webview would.
+  ///
+  /// An `Err` is reported as an exception thrown by the script.
+  pub fn respond_eval(&self, result: Result<JsonValue, String>) -> MockInvoke {
+    let marker = "payload.id = '";
+    let id = self
+      .evaluated_scripts()
+      .iter()
+      .rev()
+      .find_map(|script| {
+        let rest = &script[script.find(marker)? + marker.len()..];
+        Some(rest[..rest.find('\'')?].to_string())
+      })
+      .expect("no script was evaluated with a result");
+    let payload = match result {
+      Ok(value) => serde_json::json!({ "id": id, "result": value }),
+      Err(error) => serde_json::json!({ "id": id, "error": error }),
+    };
+    self.invoke("__evalResult", payload)
+  }
+
+  /// Sends a file drop event to the window, returning whether the default behavior was blocked.
+  pub fn drop_files(&self, event: FileDropEvent) -> bool {
+    match &*self
+      .window
+      .file_drop_handler
+      .lock()
+      .expect("poisoned file drop handler")
+    {
+      Some(handler) => handler(event, self.clone()),
+      None => false,
+    }
+  }
+
+  /// Reports a navigation to `url` to the navigation hooks, as a clicked link would.
+  pub fn navigate_to<S: Into<String>>(&self, url: S) {
+    if let Some(handler) = &*self
+      .window
+      .navigation_handler
+      .lock()
+      .expect("poisoned navigation handler")
+    {
+      handler(url.into(), self.clone());
+    }
+  }
+
+  /// Requests `uri` through the custom protocol of the window.
+  pub fn fetch(&self, uri: &str) -> crate::Result<Vec<u8>> {
+    match &*self
+      .window
+      .custom_protocol
+      .lock()
+      .expect("poisoned custom protocol")
+    {
+      Some(protocol) => (protocol.handler)(uri),
+      None => Err(crate::Error::AssetNotFound(uri.to_string())),
+    }
+  }
+
+  /// The response to an invoke if it was already sent to the window.
+  ///
+  /// Resolved invokes are returned as `Ok` and rejected ones as `Err`.
+  pub fn invoke_response(&self, invoke: &MockInvoke) -> Option<Result<JsonValue, JsonValue>> {
+    self.evaluated_scripts().iter().find_map(|script| {
+      parse_callback_arg(script, &invoke.callback)
+        .map(Ok)
+        .or_else(|| parse_callback_arg(script, &invoke.error).map(Err))
+    })
+  }
+
+  /// Waits for the response to an invoke, which might be resolved asynchronously.
+  pub fn wait_invoke_response(
+    &self,
+    invoke: &MockInvoke,
+    timeout: Duration,
+  ) -> Option<Result<JsonValue, JsonValue>> {
+    self.wait_for(timeout, || self.invoke_response(invoke))
+  }
+
+  /// Asserts that the invoke was resolved, returning its value.
+  pub fn assert_invoke_ok(&self, invoke: &MockInvoke) -> JsonValue {
+    match self.wait_invoke_response(invoke, RESPONSE_TIMEOUT) {
+      Some(Ok(value)) => value,
+      Some(Err(error)) => panic!("invoke was rejected with {}", error),
+      None => panic!("invoke was not answered"),
+    }
+  }
+
+  /// Asserts that the invoke was rejected, returning its error.
+  pub fn assert_invoke_err(&self, invoke: &MockInvoke) -> JsonValue {
+    match self.wait_invoke_response(invoke, RESPONSE_TIMEOUT) {
+      Some(Err(error)) => error,
+      Some(Ok(value)) => panic!("invoke was resolved with {}", value),
+      None => panic!("invoke was not answered"),
+    }
+  }
+
+  /// Asserts that `event` was emitted to the window, returning the payload of its last emit.
+  pub fn assert_emitted(&self, event: &str) -> JsonValue {
+    let found = self.wait_for(RESPONSE_TIMEOUT, || {
+      self
+        .emitted_events()
+        .into_iter()
+        .rev()
+        .find(|emitted| emitted.event == event)
+    });
+    match found {
+      Some(emitted) => emitted.payload,
+      None => panic!(
+        "event `{}` was not emitted to window `{}`",
+        event, self.window.label
+      ),
+    }
+  }
+
+  /// Asserts that `event` was never emitted to the window.
+  pub fn assert_not_emitted(&self, event: &str) {
+    if self.emitted_events().iter().any(|e| e.event == event) {
+      panic!(
+        "event `{}` was emitted to window `{}`",
+        event, self.window.label
+      );
+    }
+  }
+
+  /// Polls `f` every time a call is recorded until it returns a value or `timeout` elapses.
+  fn wait_for<T, F: Fn() -> Option<T>>(&self, timeout: Duration, f: F) -> Option<T> {
+    let deadline = Instant::now() + timeout;
+    loop {
+      let seen = self.window.calls.lock().expect("poisoned mock calls").len();
+      if let Some(value) = f() {
+        return Some(value);
+      }
+      let now = Instant::now();
+      if now >= deadline {
+        return None;
+      }
+      let calls = self.window.calls.lock().expect("poisoned mock calls");
+      // only sleep if nothing was recorded while `f` was running
+      if calls.len() == seen {
+        let _ = self
+          .window
+          .calls_changed
+          .wait_timeout(calls, deadline - now)
+          .expect("poisoned mock calls");
+      }
+    }
+  }
+
+  fn record(&self, call: DispatchCall) -> crate::Result<()> {
+    self
+      .window
+      .calls
+      .lock()
+      .expect("poisoned mock calls")
+      .push(call);
+    self.window.calls_changed.notify_all();
+    Ok(())
+  }
+}
+
+impl Dispatch for MockDispatcher {
+  type Runtime = MockRuntime;
+  type Icon = MockIcon;
+  type Attributes = MockAttributes;
+
+  fn create_window<M: Params<Runtime = Self::Runtime>>(
+    &mut self,
+    pending: PendingWindow<M>,
+  ) -> crate::Result<DetachedWindow<M>> {
+    create_window(&self.context, pending)
+  }
+
+  fn set_resizable(&self, resizable: bool) -> crate::Result<()> {
+    self.record(DispatchCall::SetResizable(resizable))
+  }
+
+  fn set_title<S: Into<String>>(&self, title: S) -> crate::Result<()> {
+    self.record(DispatchCall::SetTitle(title.into()))
+  }
+
+  fn maximize(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Maximize)
+  }
+
+  fn unmaximize(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Unmaximize)
+  }
+
+  fn minimize(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Minimize)
+  }
+
+  fn unminimize(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Unminimize)
+  }
+
+  fn show(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Show)
+  }
+
+  fn hide(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Hide)
+  }
+
+  fn close(&self) -> crate::Result<bool> {
+    self.record(DispatchCall::Close)?;
+    if !self.window.closed.swap(true, Ordering::SeqCst) {
+      self.context.send(Message::WindowClosed)?;
+    }
+    Ok(true)
+  }
+
+  fn set_decorations(&self, decorations: bool) -> crate::Result<()> {
+    self.record(DispatchCall::SetDecorations(decorations))
+  }
+
+  fn set_always_on_top(&self, always_on_top: bool) -> crate::Result<()> {
+    self.record(DispatchCall::SetAlwaysOnTop(always_on_top))
+  }
+
+  fn set_width(&self, width: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetWidth(width))
+  }
+
+  fn set_height(&self, height: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetHeight(height))
+  }
+
+  fn resize(&self, width: f64, height: f64) -> crate::Result<()> {
+    self.record(DispatchCall::Resize(width, height))
+  }
+
+  fn set_min_size(&self, min_width: f64, min_height: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetMinSize(min_width, min_height))
+  }
+
+  fn set_max_size(&self, max_width: f64, max_height: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetMaxSize(max_width, max_height))
+  }
+
+  fn set_x(&self, x: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetX(x))
+  }
+
+  fn set_y(&self, y: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetY(y))
+  }
+
+  fn set_position(&self, x: f64, y: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetPosition(x, y))
+  }
+
+  fn set_fullscreen(&self, fullscreen: bool) -> crate::Result<()> {
+    self.record(DispatchCall::SetFullscreen(fullscreen))
+  }
+
+  fn set_icon(&self, _icon: Self::Icon) -> crate::Result<()> {
+    self.record(DispatchCall::SetIcon)
+  }
+
+  fn start_file_drag(&self, paths: Vec<PathBuf>, _icon: Option<Self::Icon>) -> crate::Result<()> {
+    self.record(DispatchCall::StartFileDrag(paths))
+  }
+
+  fn run_on_main_thread<F: FnOnce() + Send + 'static>(&self, f: F) -> crate::Result<()> {
+    // the mock runtime has no main thread to dispatch to
+    self.record(DispatchCall::RunOnMainThread)?;
+    f();
+    Ok(())
+  }
+
+  fn navigate<S: Into<String>>(&self, url: S) -> crate::Result<()> {
+    self.record(DispatchCall::Navigate(url.into()))
+  }
+
+  fn reload(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Reload)
+  }
+
+  fn go_back(&self) -> crate::Result<()> {
+    self.record(DispatchCall::GoBack)
+  }
+
+  fn go_forward(&self) -> crate::Result<()> {
+    self.record(DispatchCall::GoForward)
+  }
+
+  fn print(&self) -> crate::Result<()> {
+    self.record(DispatchCall::Print)
+  }
+
+  fn set_zoom(&self, zoom: f64) -> crate::Result<()> {
+    self.record(DispatchCall::SetZoom(zoom))
+  }
+
+  fn open_devtools(&self) -> crate::Result<()> {
+    self.record(DispatchCall::OpenDevtools)
+  }
+
+  fn close_devtools(&self) -> crate::Result<()> {
+    self.record(DispatchCall::CloseDevtools)
+  }
+
+  fn eval_script<S: Into<String>>(&self, script: S) -> crate::Result<()> {
+    self.record(DispatchCall::EvalScript(script.into()))
+  }
+}
+
+/// [`Assets`] implementation without any asset.
+#[derive(Debug, Default)]
+pub struct NoopAsset;
+
+impl Assets for NoopAsset {
+  fn get<Key: Into<AssetKey>>(&self, _key: Key) -> Option<Cow<'_, [u8]>> {
+    None
+  }
+}
+
+/// A [`Context`] with the default configuration, creating a single `main` window.
+pub fn mock_context() -> Context<NoopAsset> {
+  Context {
+    config: Config {
+      tauri: Default::default(),
+      build: Default::default(),
+      plugins: Default::default(),
+    },
+    assets: NoopAsset,
+    default_window_icon: None,
+    package_info: PackageInfo {
+      name: "test",
+      version: "0.1.0",
+    },
+  }
+}
+
+/// Reads the first JSON value of `input`, returning it with the rest of the input.
+fn parse_json_prefix(input: &str) -> Option<(JsonValue, &str)> {
+  let mut stream = serde_json::Deserializer::from_str(input).into_iter::<JsonValue>();
+  let value = stream.next()?.ok()?;
+  Some((value, &input[stream.byte_offset()..]))
+}
+
+/// Parses a script generated by the event emitter.
+fn parse_emitted_event(script: &str) -> Option<EmittedEvent> {
+  let marker = "({event: ";
+  let rest = &script[script.find(marker)? + marker.len()..];
+  let (event, rest) = parse_json_prefix(rest)?;
+  let rest = rest.strip_prefix(", payload: ")?;
+  let (payload, _) = parse_json_prefix(rest)?;
+  Some(EmittedEvent {
+    event: event.as_str()?.to_string(),
+    payload,
+  })
+}
+
+/// Parses the argument of a script generated by [`crate::api::rpc::format_callback`].
+fn parse_callback_arg(script: &str, function_name: &str) -> Option<JsonValue> {
+  let call = format!(r#"window["{}"]("#, function_name);
+  let arg = &script[script.find(&call)? + call.len()..];
+
+  match arg.strip_prefix("JSON.parse('") {
+    // large arguments are escaped inside a single quoted string
+    Some(escaped) => {
+      let mut json = String::with_capacity(escaped.len());
+      let mut chars = escaped.chars();
+      while let Some(c) = chars.next() {
+        match c {
+          '\\' => json.push(chars.next()?),
+          '\'' => break,
+          c => json.push(c),
+        }
+      }
+      serde_json::from_str(&json).ok()
+    }
+    None => parse_json_prefix(arg).map(|(value, _)| value),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::{api::rpc::format_callback, Builder, Manager};
+
+  fn mock_app() -> crate::App<crate::runtime::manager::Args<String, String, NoopAsset, MockRuntime>>
+  {
+    Builder::<String, String, _, MockRuntime>::new()
+      .invoke_handler(|message| match message.command() {
+        "echo" => {
+          let window = message.window();
+          let payload = message.payload();
+          message.respond_async(async move {
+            window
+              .emit(&"echoed".to_string(), Some(payload.clone()))
+              .map_err(|e| e.to_string())?;
+            Ok::<_, String>(payload)
+          })
+        }
+        _ => message.reject("unknown command"),
+      })
+      .build(mock_context())
+      .expect("failed to build mock app")
+  }
+
+  #[test]
+  fn check_parse_callback_arg() {
+    let script = format_callback("cb", &vec!["it's"; 4096]).expect("failed to format callback");
+    assert!(script.contains("JSON.parse('"));
+    assert_eq!(
+      parse_callback_arg(&script, "cb"),
+      Some(serde_json::json!(vec!["it's"; 4096]))
+    );
+
+    let script = format_callback("cb", &5).expect("failed to format callback");
+    assert_eq!(parse_callback_arg(&script, "cb"), Some(5.into()));
+    assert_eq!(parse_callback_arg(&script, "other"), None);
+  }
+
+  #[test]
+  fn check_invoke() {
+    let app = mock_app();
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+
+    let invoke = mock.invoke("echo", serde_json::json!({ "value": 1 }));
+    let response = mock.assert_invoke_ok(&invoke);
+    assert_eq!(response["value"], 1);
+    assert_eq!(mock.assert_emitted("echoed")["value"], 1);
+
+    let invoke = mock.invoke("missing", JsonValue::Null);
+    assert_eq!(mock.assert_invoke_err(&invoke), "unknown command");
+  }
+
+  #[test]
+  fn check_dispatch_calls() {
+    let app = mock_app();
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+    mock.clear_calls();
+
+    window.set_title("mock").expect("failed to set title");
+    window.maximize().expect("failed to maximize");
+    assert_eq!(
+      mock.calls(),
+      vec![
+        DispatchCall::SetTitle("mock".into()),
+        DispatchCall::Maximize
+      ]
+    );
+  }
+}
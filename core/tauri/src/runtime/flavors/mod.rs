@@ -5,3 +5,6 @@
 //! Officially supported webview runtimes.
 
 pub mod wry;
+
+#[cfg(feature = "test")]
+pub mod mock;
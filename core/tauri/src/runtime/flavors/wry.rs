@@ -8,15 +8,26 @@ use crate::{
   api::config::WindowConfig,
   runtime::{
     webview::{
-      Attributes, AttributesBase, CustomProtocol, FileDropEvent, FileDropHandler, RpcRequest,
-      WebviewRpcHandler,
+      Attributes, AttributesBase, CustomProtocol, FileDropEvent, FileDropHandler,
+      NavigationHandler, RpcRequest, WebviewRpcHandler,
     },
     window::{DetachedWindow, PendingWindow},
-    Dispatch, Params, Runtime,
+    Dispatch, ExitRequestedEventAction, Params, RunEvent, Runtime, RuntimeHandle,
   },
   Icon,
 };
-use std::{convert::TryFrom, path::PathBuf};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::{
+  collections::{HashSet, VecDeque},
+  convert::TryFrom,
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::channel,
+    Arc, Mutex, MutexGuard,
+  },
+};
 
 #[cfg(target_os = "windows")]
 use crate::api::path::{resolve_path, BaseDirectory};
@@ -41,6 +52,63 @@ impl TryFrom<Icon> for WryIcon {
   }
 }
 
+/// Prevents the webview context menu from opening.
+const DISABLE_CONTEXT_MENU_SCRIPT: &str = r#"
+  window.addEventListener('contextmenu', function (e) { e.preventDefault() }, true)
+"#;
+
+/// The RPC method the webviews call to run the tasks queued with [`Dispatch::run_on_main_thread`].
+const MAIN_THREAD_RPC_METHOD: &str = "__tauriMainThread";
+
+/// The RPC method used by [`NAVIGATION_SCRIPT`] to notify the navigation hooks.
+const NAVIGATION_RPC_METHOD: &str = "__tauriNavigation";
+
+/// Reports link clicks and form submissions to the navigation hooks, without delaying them.
+///
+/// wry doesn't expose the webview navigation events, so navigations started through
+/// `window.location` aren't reported.
+const NAVIGATION_SCRIPT: &str = r#"
+  (function () {
+    function notifyNavigation(url) {
+      window.rpc.notify('__tauriNavigation', { url: url })
+    }
+    document.addEventListener('click', function (e) {
+      if (e.defaultPrevented || e.button !== 0) {
+        return
+      }
+      var link = e.target
+      while (link && link.tagName !== 'A') {
+        link = link.parentElement
+      }
+      if (!link || !link.href || (link.target && link.target !== '_self')) {
+        return
+      }
+      var url = new URL(link.href, window.location.href)
+      if (url.hash && url.href.split('#')[0] === window.location.href.split('#')[0]) {
+        return
+      }
+      notifyNavigation(url.href)
+    })
+    document.addEventListener('submit', function (e) {
+      if (e.defaultPrevented) {
+        return
+      }
+      notifyNavigation(new URL(e.target.action || window.location.href, window.location.href).href)
+    })
+  })()
+"#;
+
+/// A navigation reported by [`NAVIGATION_SCRIPT`].
+#[derive(Deserialize)]
+struct NavigationRequest {
+  url: String,
+}
+
+/// Sets the zoom level of the document.
+fn zoom_script(zoom: f64) -> String {
+  format!("document.documentElement.style.zoom = '{}'", zoom)
+}
+
 impl AttributesBase for wry::Attributes {}
 impl Attributes for wry::Attributes {
   type Icon = WryIcon;
@@ -81,6 +149,10 @@ impl Attributes for wry::Attributes {
       webview = webview.y(y);
     }
 
+    webview = webview
+      .skip_taskbar(config.skip_taskbar)
+      .context_menu(config.context_menu);
+
     // If we are on windows use App Data Local as user_data
     // to prevent any bundled application to failed.
 
@@ -192,6 +264,19 @@ impl Attributes for wry::Attributes {
     self
   }
 
+  fn skip_taskbar(mut self, skip: bool) -> Self {
+    self.skip_taskbar = skip;
+    self
+  }
+
+  fn context_menu(self, enabled: bool) -> Self {
+    if enabled {
+      self
+    } else {
+      self.initialization_script(DISABLE_CONTEXT_MENU_SCRIPT)
+    }
+  }
+
   fn icon(mut self, icon: Self::Icon) -> Self {
     self.icon = Some(icon.0);
     self
@@ -228,18 +313,213 @@ impl From<wry::RpcRequest> for RpcRequest {
 impl From<wry::FileDropEvent> for FileDropEvent {
   fn from(event: wry::FileDropEvent) -> Self {
     match event {
-      wry::FileDropEvent::Hovered(paths) => FileDropEvent::Hovered(paths),
-      wry::FileDropEvent::Dropped(paths) => FileDropEvent::Dropped(paths),
+      // wry doesn't report the cursor position
+      wry::FileDropEvent::Hovered(paths) => FileDropEvent::Hovered {
+        paths,
+        position: None,
+      },
+      wry::FileDropEvent::Dropped(paths) => FileDropEvent::Dropped {
+        paths,
+        position: None,
+      },
       wry::FileDropEvent::Cancelled => FileDropEvent::Cancelled,
     }
   }
 }
 
+type RunCallback = Box<dyn FnMut(RunEvent) + Send>;
+
+type MainThreadTask = Box<dyn FnOnce() + Send>;
+
+/// The state of the run callback.
+#[derive(Default)]
+struct RunState {
+  /// The run callback, `None` while it runs or before the runtime runs.
+  callback: Option<RunCallback>,
+  /// Whether the runtime is running.
+  running: bool,
+  /// The events emitted while the callback runs, delivered once it returns.
+  queued: VecDeque<RunEvent>,
+  /// The code to exit the process with once the callback returns.
+  exit_code: Option<i32>,
+}
+
+/// State shared between the [`Wry`] runtime, its handles and its dispatchers.
+///
+/// [`wry::Application`] doesn't report its event loop events nor the windows closed by the user,
+/// so the run callback is only called for the exits requested through Tauri: closing the last
+/// open window with [`Dispatch::close`] and [`RuntimeHandle::exit`]. A window closed natively is
+/// still counted as open.
+#[derive(Default)]
+struct WryContext {
+  run: Mutex<RunState>,
+  /// The ids of the open windows.
+  windows: Mutex<HashSet<u64>>,
+  next_window_id: AtomicU64,
+  /// The tasks queued with [`Dispatch::run_on_main_thread`].
+  main_thread_tasks: Mutex<VecDeque<MainThreadTask>>,
+}
+
+impl WryContext {
+  fn run_state(&self) -> MutexGuard<'_, RunState> {
+    self.run.lock().expect("poisoned run callback")
+  }
+
+  fn windows(&self) -> MutexGuard<'_, HashSet<u64>> {
+    self.windows.lock().expect("poisoned wry windows")
+  }
+
+  /// Takes the next task queued with [`Dispatch::run_on_main_thread`].
+  fn next_main_thread_task(&self) -> Option<MainThreadTask> {
+    self
+      .main_thread_tasks
+      .lock()
+      .expect("poisoned main thread tasks")
+      .pop_front()
+  }
+
+  /// Registers a new open window, returning its id.
+  fn add_window(&self) -> u64 {
+    let id = self.next_window_id.fetch_add(1, Ordering::Relaxed);
+    self.windows().insert(id);
+    id
+  }
+
+  /// Calls the run callback with the event, if the runtime is running.
+  fn run_event(&self, event: RunEvent) {
+    self.emit(event, None)
+  }
+
+  /// Calls the run callback with the event, then exits the process if `exit_code` is set.
+  ///
+  /// The events emitted while the callback runs, e.g. by the callback itself, are queued and
+  /// delivered once it returns, and the process only exits after that.
+  fn emit(&self, event: RunEvent, exit_code: Option<i32>) {
+    let mut state = self.run_state();
+    if let Some(code) = exit_code {
+      state.exit_code.get_or_insert(code);
+    }
+    let mut callback = match state.callback.take() {
+      Some(callback) => callback,
+      None if state.running => {
+        state.queued.push_back(event);
+        return;
+      }
+      None => {
+        let exit_code = state.exit_code;
+        drop(state);
+        if let Some(code) = exit_code {
+          std::process::exit(code);
+        }
+        return;
+      }
+    };
+    drop(state);
+
+    let mut event = event;
+    loop {
+      callback(event);
+      let mut state = self.run_state();
+      match state.queued.pop_front() {
+        Some(next) => event = next,
+        None => {
+          let exit_code = state.exit_code;
+          state.callback.replace(callback);
+          drop(state);
+          if let Some(code) = exit_code {
+            // the wry event loop can't be stopped, so the process exits here
+            std::process::exit(code);
+          }
+          return;
+        }
+      }
+    }
+  }
+
+  /// Asks the run callback if the application can exit.
+  ///
+  /// The exit can't be prevented if it is requested while the callback runs.
+  fn exit_requested(&self) -> bool {
+    let (tx, rx) = channel();
+    self.run_event(RunEvent::ExitRequested { tx });
+    !matches!(rx.try_recv(), Ok(ExitRequestedEventAction::Prevent))
+  }
+}
+
+/// Adds a window to a running [`wry::Application`].
+fn create_window<M: Params<Runtime = Wry>>(
+  application: &wry::ApplicationProxy,
+  context: &Arc<WryContext>,
+  pending: PendingWindow<M>,
+) -> crate::Result<DetachedWindow<M>> {
+  let PendingWindow {
+    attributes,
+    rpc_handler,
+    custom_protocol,
+    file_drop_handler,
+    navigation_handler,
+    label,
+    ..
+  } = pending;
+
+  let attributes = if navigation_handler.is_some() {
+    attributes.initialization_script(NAVIGATION_SCRIPT)
+  } else {
+    attributes
+  };
+
+  let id = context.add_window();
+  let rpc_handler = create_rpc_handler(
+    application.clone(),
+    context.clone(),
+    id,
+    label.clone(),
+    rpc_handler,
+    navigation_handler,
+  );
+
+  let file_drop_handler = file_drop_handler.map(|handler| {
+    create_file_drop_handler(
+      application.clone(),
+      context.clone(),
+      id,
+      label.clone(),
+      handler,
+    )
+  });
+
+  let window = application
+    .add_window_with_configs(
+      attributes,
+      rpc_handler,
+      custom_protocol
+        .map(create_custom_protocol)
+        .unwrap_or_default(),
+      file_drop_handler,
+    )
+    .map_err(|_| {
+      context.windows().remove(&id);
+      crate::Error::CreateWebview
+    })?;
+
+  let dispatcher = WryDispatcher {
+    window,
+    id,
+    application: application.clone(),
+    context: context.clone(),
+  };
+
+  Ok(DetachedWindow { label, dispatcher })
+}
+
 /// The Tauri [`Dispatch`] for [`Wry`].
 #[derive(Clone)]
 pub struct WryDispatcher {
   window: wry::WindowProxy,
+  /// The id of the window on the [`WryContext`].
+  id: u64,
   application: wry::ApplicationProxy,
+  context: Arc<WryContext>,
 }
 
 impl Dispatch for WryDispatcher {
@@ -251,41 +531,7 @@ impl Dispatch for WryDispatcher {
     &mut self,
     pending: PendingWindow<M>,
   ) -> crate::Result<DetachedWindow<M>> {
-    let PendingWindow {
-      attributes,
-      rpc_handler,
-      custom_protocol,
-      file_drop_handler,
-      label,
-      ..
-    } = pending;
-
-    let proxy = self.application.clone();
-
-    let rpc_handler =
-      rpc_handler.map(|handler| create_rpc_handler(proxy.clone(), label.clone(), handler));
-
-    let file_drop_handler = file_drop_handler
-      .map(|handler| create_file_drop_handler(proxy.clone(), label.clone(), handler));
-
-    let window = self
-      .application
-      .add_window_with_configs(
-        attributes,
-        rpc_handler,
-        custom_protocol
-          .map(create_custom_protocol)
-          .unwrap_or_default(),
-        file_drop_handler,
-      )
-      .map_err(|_| crate::Error::CreateWebview)?;
-
-    let dispatcher = WryDispatcher {
-      window,
-      application: proxy,
-    };
-
-    Ok(DetachedWindow { label, dispatcher })
+    create_window(&self.application, &self.context, pending)
   }
 
   fn set_resizable(&self, resizable: bool) -> crate::Result<()> {
@@ -344,11 +590,33 @@ impl Dispatch for WryDispatcher {
       .map_err(|_| crate::Error::FailedToSendMessage)
   }
 
-  fn close(&self) -> crate::Result<()> {
+  fn close(&self) -> crate::Result<bool> {
+    let last = {
+      let mut windows = self.context.windows();
+      if !windows.remove(&self.id) {
+        // already closed
+        return Ok(true);
+      }
+      windows.is_empty()
+    };
+    if last && !self.context.exit_requested() {
+      // wry exits its event loop once the last window is closed,
+      // so the window is only hidden to keep the application running
+      self.context.windows().insert(self.id);
+      self
+        .window
+        .hide()
+        .map_err(|_| crate::Error::FailedToSendMessage)?;
+      return Ok(false);
+    }
+    if last {
+      self.context.run_event(RunEvent::Exit);
+    }
     self
       .window
       .close()
-      .map_err(|_| crate::Error::FailedToSendMessage)
+      .map_err(|_| crate::Error::FailedToSendMessage)?;
+    Ok(true)
   }
 
   fn set_decorations(&self, decorations: bool) -> crate::Result<()> {
@@ -435,6 +703,62 @@ impl Dispatch for WryDispatcher {
       .map_err(|_| crate::Error::FailedToSendMessage)
   }
 
+  fn start_file_drag(&self, _paths: Vec<PathBuf>, _icon: Option<Self::Icon>) -> crate::Result<()> {
+    // wry can't start native drag operations yet
+    Err(crate::Error::Unsupported(
+      "dragging files out of the window",
+    ))
+  }
+
+  fn run_on_main_thread<F: FnOnce() + Send + 'static>(&self, f: F) -> crate::Result<()> {
+    // wry can't send custom events to its event loop, so the webview is asked to request the
+    // queued tasks with an RPC call, which wry handles on the main thread
+    self
+      .context
+      .main_thread_tasks
+      .lock()
+      .expect("poisoned main thread tasks")
+      .push_back(Box::new(f));
+    self.eval_script(format!("window.rpc.notify('{}')", MAIN_THREAD_RPC_METHOD))
+  }
+
+  fn navigate<S: Into<String>>(&self, url: S) -> crate::Result<()> {
+    self.eval_script(format!(
+      "window.location.href = {}",
+      JsonValue::String(url.into())
+    ))
+  }
+
+  fn reload(&self) -> crate::Result<()> {
+    self.eval_script("window.location.reload()")
+  }
+
+  fn go_back(&self) -> crate::Result<()> {
+    self.eval_script("window.history.back()")
+  }
+
+  fn go_forward(&self) -> crate::Result<()> {
+    self.eval_script("window.history.forward()")
+  }
+
+  fn print(&self) -> crate::Result<()> {
+    self.eval_script("window.print()")
+  }
+
+  fn set_zoom(&self, zoom: f64) -> crate::Result<()> {
+    self.eval_script(zoom_script(zoom))
+  }
+
+  // wry can't toggle the devtools programmatically yet
+
+  fn open_devtools(&self) -> crate::Result<()> {
+    Err(crate::Error::Unsupported("opening the devtools"))
+  }
+
+  fn close_devtools(&self) -> crate::Result<()> {
+    Err(crate::Error::Unsupported("closing the devtools"))
+  }
+
   fn eval_script<S: Into<String>>(&self, script: S) -> crate::Result<()> {
     self
       .window
@@ -443,89 +767,123 @@ impl Dispatch for WryDispatcher {
   }
 }
 
+/// A handle to the [`Wry`] runtime.
+#[derive(Clone)]
+pub struct WryHandle {
+  application: wry::ApplicationProxy,
+  context: Arc<WryContext>,
+}
+
+impl RuntimeHandle for WryHandle {
+  type Runtime = Wry;
+
+  fn create_window<M: Params<Runtime = Self::Runtime>>(
+    &self,
+    pending: PendingWindow<M>,
+  ) -> crate::Result<DetachedWindow<M>> {
+    create_window(&self.application, &self.context, pending)
+  }
+
+  fn exit(&self, code: i32) -> crate::Result<()> {
+    // returns if the run callback is running, which exits the process once it returns
+    self.context.emit(RunEvent::Exit, Some(code));
+    Ok(())
+  }
+}
+
 /// A Tauri [`Runtime`] wrapper around [`wry::Application`].
 pub struct Wry {
   inner: wry::Application,
+  context: Arc<WryContext>,
 }
 
 impl Runtime for Wry {
   type Dispatcher = WryDispatcher;
+  type Handle = WryHandle;
 
   fn new() -> crate::Result<Self> {
     let app = wry::Application::new().map_err(|_| crate::Error::CreateWebview)?;
-    Ok(Self { inner: app })
+    Ok(Self {
+      inner: app,
+      context: Default::default(),
+    })
+  }
+
+  fn handle(&self) -> Self::Handle {
+    WryHandle {
+      application: self.inner.application_proxy(),
+      context: self.context.clone(),
+    }
   }
 
   fn create_window<M: Params<Runtime = Self>>(
     &mut self,
     pending: PendingWindow<M>,
   ) -> crate::Result<DetachedWindow<M>> {
-    let PendingWindow {
-      attributes,
-      rpc_handler,
-      custom_protocol,
-      file_drop_handler,
-      label,
-      ..
-    } = pending;
-
-    let proxy = self.inner.application_proxy();
-
-    let rpc_handler =
-      rpc_handler.map(|handler| create_rpc_handler(proxy.clone(), label.clone(), handler));
-
-    let file_drop_handler = file_drop_handler
-      .map(|handler| create_file_drop_handler(proxy.clone(), label.clone(), handler));
-
-    let window = self
-      .inner
-      .add_window_with_configs(
-        attributes,
-        rpc_handler,
-        custom_protocol
-          .map(create_custom_protocol)
-          .unwrap_or_default(),
-        file_drop_handler,
-      )
-      .map_err(|_| crate::Error::CreateWebview)?;
-
-    let dispatcher = WryDispatcher {
-      window,
-      application: proxy,
-    };
-
-    Ok(DetachedWindow { label, dispatcher })
+    create_window(&self.inner.application_proxy(), &self.context, pending)
   }
 
-  fn run(self) {
+  fn run<F: FnMut(RunEvent) + Send + 'static>(self, callback: F) {
+    {
+      let mut state = self.context.run_state();
+      state.callback.replace(Box::new(callback));
+      state.running = true;
+    }
     wry::Application::run(self.inner)
   }
 }
 
-/// Create a wry rpc handler from a tauri rpc handler.
+/// Create a wry rpc handler from a tauri rpc handler and navigation handler.
+///
+/// The navigations reported by [`NAVIGATION_SCRIPT`] are passed to the navigation handler,
+/// and the tasks queued with [`Dispatch::run_on_main_thread`] run when the webview requests them.
 fn create_rpc_handler<M: Params<Runtime = Wry>>(
   app_proxy: wry::ApplicationProxy,
+  context: Arc<WryContext>,
+  id: u64,
   label: M::Label,
-  handler: WebviewRpcHandler<M>,
-) -> wry::WindowRpcHandler {
-  Box::new(move |window, request| {
-    handler(
-      DetachedWindow {
-        dispatcher: WryDispatcher {
-          window,
-          application: app_proxy.clone(),
-        },
-        label: label.clone(),
+  handler: Option<WebviewRpcHandler<M>>,
+  navigation_handler: Option<NavigationHandler<M>>,
+) -> Option<wry::WindowRpcHandler> {
+  Some(Box::new(move |window, request| {
+    if request.method == MAIN_THREAD_RPC_METHOD {
+      while let Some(task) = context.next_main_thread_task() {
+        task();
+      }
+      return None;
+    }
+
+    let window = DetachedWindow {
+      dispatcher: WryDispatcher {
+        window,
+        id,
+        application: app_proxy.clone(),
+        context: context.clone(),
       },
-      request.into(),
-    );
+      label: label.clone(),
+    };
+    if request.method == NAVIGATION_RPC_METHOD {
+      if let Some(navigation_handler) = &navigation_handler {
+        let navigation = request
+          .params
+          .and_then(|mut params| params.get_mut(0).map(JsonValue::take))
+          .and_then(|navigation| serde_json::from_value::<NavigationRequest>(navigation).ok());
+        if let Some(navigation) = navigation {
+          navigation_handler(navigation.url, window);
+        }
+      }
+    } else if let Some(handler) = &handler {
+      handler(window, request.into());
+    }
     None
-  })
+  }))
 }
 
 /// Create a wry file drop handler from a tauri file drop handler.
 fn create_file_drop_handler<M: Params<Runtime = Wry>>(
   app_proxy: wry::ApplicationProxy,
+  context: Arc<WryContext>,
+  id: u64,
   label: M::Label,
   handler: FileDropHandler<M>,
 ) -> wry::WindowFileDropHandler {
@@ -535,7 +893,9 @@ fn create_file_drop_handler<M: Params<Runtime = Wry>>(
       DetachedWindow {
         dispatcher: WryDispatcher {
           window,
+          id,
           application: app_proxy.clone(),
+          context: context.clone(),
         },
         label: label.clone(),
       },
@@ -3,23 +3,31 @@
 // SPDX-License-Identifier: MIT
 
 use crate::{
+  accelerator::AcceleratorManager,
   api::{
     assets::Assets,
     config::{Config, WindowUrl},
     PackageInfo,
   },
   event::{Event, EventHandler, Listeners},
-  hooks::{InvokeHandler, InvokeMessage, InvokePayload, OnPageLoad, PageLoadPayload},
+  hooks::{
+    EvalResultPayload, InvokeHandler, InvokeMessage, InvokePayload, OnFileDrop, OnNavigation,
+    OnPageLoad, PageLoadPayload,
+  },
   plugin::PluginStore,
   runtime::{
     tag::{tags_to_javascript_array, Tag, ToJavascript},
-    webview::{Attributes, CustomProtocol, FileDropEvent, FileDropHandler, WebviewRpcHandler},
+    webview::{
+      Attributes, CustomProtocol, FileDropEvent, FileDropHandler, FileDropPosition,
+      NavigationHandler, WebviewRpcHandler,
+    },
     window::{DetachedWindow, PendingWindow},
     Dispatch, Icon, Runtime,
   },
   sealed::ParamsBase,
   Context, Params, Window,
 };
+use once_cell::sync::OnceCell;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::marker::PhantomData;
@@ -27,12 +35,62 @@ use std::{
   borrow::Cow,
   collections::{HashMap, HashSet},
   convert::TryInto,
+  path::PathBuf,
   sync::{Arc, Mutex, MutexGuard},
+  time::{Duration, Instant},
 };
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
+/// The custom protocol path prefix used to serve raw invoke responses.
+const BINARY_RESPONSE_PATH: &str = "__tauri_binary__/";
+
+/// How long a raw invoke response waits to be fetched before it is dropped.
+const BINARY_RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A raw invoke response waiting to be fetched by the webview.
+struct BinaryResponse<L> {
+  /// The label of the window the response was sent to.
+  window: L,
+  bytes: Vec<u8>,
+  registered_at: Instant,
+}
+
+impl<L> BinaryResponse<L> {
+  fn is_expired(&self) -> bool {
+    self.registered_at.elapsed() >= BINARY_RESPONSE_TIMEOUT
+  }
+}
+
+/// Raw invoke responses waiting to be fetched, keyed by their id.
+type BinaryResponses<L> = HashMap<Uuid, BinaryResponse<L>>;
+
+/// Closures to run once a window is destroyed, keyed by the window label.
+type DestroyHandlers<L> = HashMap<L, Vec<Box<dyn FnOnce() + Send>>>;
+
+/// The payload of the `tauri://file-drop` and `tauri://file-drop-hover` events.
+#[derive(Serialize)]
+struct FileDropPayload<'a> {
+  paths: &'a [PathBuf],
+  position: Option<FileDropPosition>,
+}
+
+/// The outcome of a script evaluated with [`Window::eval_with_result`].
+pub(crate) enum EvalOutcome {
+  /// The value returned by the script.
+  Value(JsonValue),
+  /// The exception thrown by the script.
+  Exception(String),
+  /// The script didn't return in time.
+  Timeout,
+}
+
+/// Scripts waiting for their result, with the label of the window evaluating them.
+type EvalResults<L> = HashMap<Uuid, (L, oneshot::Sender<EvalOutcome>)>;
+
 pub struct InnerWindowManager<M: Params> {
   windows: Mutex<HashMap<M::Label, Window<M>>>,
+  destroy_handlers: Mutex<DestroyHandlers<M::Label>>,
   plugins: Mutex<PluginStore<M>>,
   listeners: Listeners<M::Event, M::Label>,
 
@@ -42,13 +100,41 @@ pub struct InnerWindowManager<M: Params> {
   /// The page load hook, invoked when the webview performs a navigation.
   on_page_load: Box<OnPageLoad<M>>,
 
+  /// The navigation hook, invoked when the webview navigates to a new URL.
+  on_navigation: Box<OnNavigation<M>>,
+
+  /// The file drop hook, invoked when files are dragged onto a window.
+  on_file_drop: Box<OnFileDrop<M>>,
+
+  /// The URL of the page loaded on each window.
+  current_urls: Mutex<HashMap<M::Label, String>>,
+
   config: Config,
   assets: Arc<M::Assets>,
   default_window_icon: Option<Vec<u8>>,
 
   /// A list of salts that are valid for the current application.
   salts: Mutex<HashSet<Uuid>>,
+  /// Scripts evaluated with [`Window::eval_with_result`] waiting for their result.
+  eval_results: Mutex<EvalResults<M::Label>>,
+  /// Raw invoke responses waiting to be fetched by the webview through the custom protocol.
+  binary_responses: Arc<Mutex<BinaryResponses<M::Label>>>,
   package_info: PackageInfo,
+
+  /// The handle to the runtime, set once the runtime is created.
+  runtime_handle: OnceCell<<M::Runtime as Runtime>::Handle>,
+
+  /// The registered global shortcuts.
+  #[cfg(global_shortcut_all)]
+  pub(crate) global_shortcuts: Mutex<crate::global_shortcut::GlobalShortcuts<M::Label>>,
+  /// The registered local accelerators.
+  pub(crate) accelerators: Mutex<crate::accelerator::Accelerators<M>>,
+  /// The supervised sidecars.
+  pub(crate) sidecars: Mutex<crate::sidecar::Sidecars>,
+  /// The paths accessible with the file system API.
+  pub(crate) fs_scope: crate::api::file::Scope,
+  /// The commands the webview can execute with the shell API.
+  pub(crate) shell_scope: crate::api::shell::Scope,
 }
 
 /// A [Zero Sized Type] marker representing a full [`Params`].
@@ -100,24 +186,61 @@ impl<P: Params> WindowManager<P> {
     plugins: PluginStore<P>,
     invoke_handler: Box<InvokeHandler<P>>,
     on_page_load: Box<OnPageLoad<P>>,
-  ) -> Self {
-    Self {
+    on_navigation: Box<OnNavigation<P>>,
+    on_file_drop: Box<OnFileDrop<P>>,
+  ) -> crate::Result<Self> {
+    let shell_scope = crate::api::shell::Scope::new(&context.config.tauri.allowlist.shell)?;
+    let fs_scope = crate::api::file::Scope::new(
+      &context.config.tauri.allowlist.fs,
+      crate::api::path::app_dir().map(|dir| dir.join(".fs-scope.json")),
+    );
+    Ok(Self {
       inner: Arc::new(InnerWindowManager {
         windows: Mutex::default(),
+        destroy_handlers: Mutex::default(),
         plugins: Mutex::new(plugins),
         listeners: Listeners::default(),
         invoke_handler,
         on_page_load,
+        on_navigation,
+        on_file_drop,
+        current_urls: Mutex::default(),
         config: context.config,
         assets: Arc::new(context.assets),
         default_window_icon: context.default_window_icon,
         salts: Mutex::default(),
+        eval_results: Mutex::default(),
+        binary_responses: Default::default(),
         package_info: context.package_info,
+        runtime_handle: OnceCell::new(),
+        #[cfg(global_shortcut_all)]
+        global_shortcuts: Mutex::default(),
+        accelerators: Mutex::default(),
+        sidecars: Mutex::default(),
+        fs_scope,
+        shell_scope,
       }),
       _marker: Args::default(),
+    })
+  }
+
+  /// Sets the handle to the runtime, once it is created.
+  pub(crate) fn set_runtime_handle(&self, handle: <P::Runtime as Runtime>::Handle) {
+    if self.inner.runtime_handle.set(handle).is_err() {
+      panic!("runtime handle already set");
     }
   }
 
+  /// The handle to the runtime.
+  pub(crate) fn runtime_handle(&self) -> <P::Runtime as Runtime>::Handle {
+    self
+      .inner
+      .runtime_handle
+      .get()
+      .expect("runtime handle not set")
+      .clone()
+  }
+
   /// Get a locked handle to the windows.
   pub(crate) fn windows_lock(&self) -> MutexGuard<'_, HashMap<P::Label, Window<P>>> {
     self.inner.windows.lock().expect("poisoned window manager")
@@ -163,7 +286,12 @@ impl<P: Params> WindowManager<P> {
             "#,
         window_labels_array = tags_to_javascript_array(pending_labels)?,
         current_window_label = label.to_javascript()?,
-      ));
+      ))
+      .initialization_script(&AcceleratorManager::<P>::initialization_script());
+
+    if let Some(script) = crate::logging::initialization_script() {
+      attributes = attributes.initialization_script(&script);
+    }
 
     if !attributes.has_icon() {
       if let Some(default_window_icon) = &self.inner.default_window_icon {
@@ -214,17 +342,23 @@ impl<P: Params> WindowManager<P> {
         }
         Err(e) => {
           let error: crate::Error = e.into();
-          let _ = window.eval(&format!(
-            r#"console.error({})"#,
-            JsonValue::String(error.to_string())
-          ));
+          log::error!("invalid IPC message from `{}`: {}", window.label(), error);
         }
       }
     })
   }
 
+  fn prepare_navigation_handler(&self) -> NavigationHandler<P> {
+    let manager = self.clone();
+    Box::new(move |url, window| {
+      let window = manager.attach_window(window);
+      manager.run_on_navigation(window, &url)
+    })
+  }
+
   fn prepare_custom_protocol(&self) -> CustomProtocol {
     let assets = self.inner.assets.clone();
+    let binary_responses = self.inner.binary_responses.clone();
     let bundle_identifier = self.inner.config.tauri.bundle.identifier.clone();
     CustomProtocol {
       name: "tauri".into(),
@@ -247,6 +381,22 @@ impl<P: Params> WindowManager<P> {
           path.chars().skip(1).collect::<String>()
         };
 
+        // raw invoke responses are served once and then dropped
+        if let Some(id) = path.strip_prefix(BINARY_RESPONSE_PATH) {
+          return id
+            .parse::<Uuid>()
+            .ok()
+            .and_then(|id| {
+              binary_responses
+                .lock()
+                .expect("poisoned binary responses")
+                .remove(&id)
+            })
+            .filter(|response| !response.is_expired())
+            .map(|response| response.bytes)
+            .ok_or_else(|| crate::Error::AssetNotFound(path.clone()));
+        }
+
         let asset_response = assets
           .get(&path)
           .ok_or(crate::Error::AssetNotFound(path))
@@ -254,8 +404,7 @@ impl<P: Params> WindowManager<P> {
         match asset_response {
           Ok(asset) => Ok(asset),
           Err(e) => {
-            #[cfg(debug_assertions)]
-            eprintln!("{:?}", e); // TODO log::error!
+            log::error!("{}", e);
             Err(e)
           }
         }
@@ -266,22 +415,24 @@ impl<P: Params> WindowManager<P> {
   fn prepare_file_drop(&self) -> FileDropHandler<P> {
     let manager = self.clone();
     Box::new(move |event, window| {
-      let manager = manager.clone();
-      crate::async_runtime::block_on(async move {
-        let window = manager.attach_window(window);
-        let _ = match event {
-          FileDropEvent::Hovered(paths) => {
-            window.emit_internal("tauri://file-drop".to_string(), Some(paths))
-          }
-          FileDropEvent::Dropped(paths) => {
-            window.emit_internal("tauri://file-drop-hover".to_string(), Some(paths))
-          }
-          FileDropEvent::Cancelled => {
-            window.emit_internal("tauri://file-drop-cancelled".to_string(), Some(()))
-          }
-        };
-      });
-      true
+      let window = manager.attach_window(window);
+      let payload = FileDropPayload {
+        paths: event.paths(),
+        position: event.position(),
+      };
+      let _ = match event {
+        FileDropEvent::Hovered { .. } => {
+          window.emit_internal("tauri://file-drop-hover".to_string(), Some(payload))
+        }
+        FileDropEvent::Dropped { .. } => {
+          window.emit_internal("tauri://file-drop".to_string(), Some(payload))
+        }
+        FileDropEvent::Cancelled => {
+          window.emit_internal("tauri://file-drop-cancelled".to_string(), Some(()))
+        }
+      };
+      // block the native drop unless the hook lets the webview receive it
+      !(manager.inner.on_file_drop)(window, &event)
     })
   }
 
@@ -357,7 +508,18 @@ impl<P: Params> WindowManager<P> {
 #[cfg(test)]
 mod test {
   use super::{Args, WindowManager};
-  use crate::{generate_context, plugin::PluginStore, runtime::flavors::wry::Wry};
+  use crate::{
+    generate_context,
+    plugin::PluginStore,
+    runtime::{
+      flavors::{
+        mock::{mock_context, DispatchCall, MockDispatcher, MockRuntime},
+        wry::Wry,
+      },
+      webview::{FileDropEvent, FileDropPosition},
+    },
+    Builder, Manager,
+  };
 
   #[test]
   fn check_get_url() {
@@ -367,7 +529,10 @@ mod test {
       PluginStore::default(),
       Box::new(|_| ()),
       Box::new(|_, _| ()),
-    );
+      Box::new(|_, _| true),
+      Box::new(|_, _| false),
+    )
+    .expect("failed to create the window manager");
 
     #[cfg(custom_protocol)]
     assert_eq!(manager.get_url(), "tauri://studio.tauri.example");
@@ -375,6 +540,47 @@ mod test {
     #[cfg(dev)]
     assert_eq!(manager.get_url(), manager.config().build.dev_path);
   }
+
+  #[test]
+  fn check_file_drop() {
+    let app = Builder::<String, String, _, MockRuntime>::new()
+      .on_file_drop(|_, event| matches!(event, FileDropEvent::Cancelled))
+      .build(mock_context())
+      .expect("failed to build mock app");
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+    let position = Some(FileDropPosition { x: 4.0, y: 2.0 });
+
+    assert!(mock.drop_files(FileDropEvent::Hovered {
+      paths: vec!["hovered.txt".into()],
+      position,
+    }));
+    let payload = mock.assert_emitted("tauri://file-drop-hover");
+    assert_eq!(payload["paths"], serde_json::json!(["hovered.txt"]));
+    assert_eq!(
+      payload["position"],
+      serde_json::json!({ "x": 4.0, "y": 2.0 })
+    );
+
+    assert!(mock.drop_files(FileDropEvent::Dropped {
+      paths: vec!["dropped.txt".into()],
+      position,
+    }));
+    assert_eq!(
+      mock.assert_emitted("tauri://file-drop")["paths"],
+      serde_json::json!(["dropped.txt"])
+    );
+
+    assert!(!mock.drop_files(FileDropEvent::Cancelled));
+    mock.assert_emitted("tauri://file-drop-cancelled");
+
+    window
+      .start_file_drag(vec!["dragged.txt".into()], None)
+      .expect("failed to start drag");
+    assert!(mock
+      .calls()
+      .contains(&DispatchCall::StartFileDrag(vec!["dragged.txt".into()])));
+  }
 }
 
 impl<P: Params> WindowManager<P> {
@@ -382,6 +588,7 @@ impl<P: Params> WindowManager<P> {
     (self.inner.invoke_handler)(message);
   }
   pub fn run_on_page_load(&self, window: Window<P>, payload: PageLoadPayload) {
+    self.set_current_url(window.label().clone(), payload.url().to_string());
     (self.inner.on_page_load)(window.clone(), payload.clone());
     self
       .inner
@@ -390,6 +597,35 @@ impl<P: Params> WindowManager<P> {
       .expect("poisoned plugin store")
       .on_page_load(window, payload);
   }
+  /// Runs the navigation hooks, notifying them that the window navigates to `url`.
+  pub fn run_on_navigation(&self, window: Window<P>, url: &str) {
+    self.set_current_url(window.label().clone(), url.to_string());
+    (self.inner.on_navigation)(window.clone(), url);
+    self
+      .inner
+      .plugins
+      .lock()
+      .expect("poisoned plugin store")
+      .on_navigation(window, url);
+  }
+  fn set_current_url(&self, label: P::Label, url: String) {
+    self
+      .inner
+      .current_urls
+      .lock()
+      .expect("poisoned current urls")
+      .insert(label, url);
+  }
+  /// The URL of the page loaded on the window with the given label.
+  pub fn current_url(&self, label: &P::Label) -> Option<String> {
+    self
+      .inner
+      .current_urls
+      .lock()
+      .expect("poisoned current urls")
+      .get(label)
+      .cloned()
+  }
   pub fn extend_api(&self, command: String, message: InvokeMessage<P>) {
     self
       .inner
@@ -428,6 +664,8 @@ impl<P: Params> WindowManager<P> {
       WindowUrl::External(url) => (url.as_str().starts_with("tauri://"), url.to_string()),
     };
 
+    self.set_current_url(pending.label.clone(), url.clone());
+
     let attributes = pending.attributes.clone();
     if is_local {
       let label = pending.label.clone();
@@ -439,6 +677,7 @@ impl<P: Params> WindowManager<P> {
     }
 
     pending.file_drop_handler = Some(self.prepare_file_drop());
+    pending.navigation_handler = Some(self.prepare_navigation_handler());
 
     Ok(pending)
   }
@@ -446,10 +685,13 @@ impl<P: Params> WindowManager<P> {
     let window = Window::new(self.clone(), window);
 
     // insert the window into our manager
-    {
-      self
-        .windows_lock()
-        .insert(window.label().clone(), window.clone());
+    let replaced = self
+      .windows_lock()
+      .insert(window.label().clone(), window.clone());
+    // the runtime may not report the windows closed natively, so the resources of a window
+    // are released once its label is reused at the latest
+    if replaced.is_some() {
+      self.run_destroy_handlers(window.label());
     }
 
     // let plugins know that a new window has been added to the manager
@@ -464,6 +706,56 @@ impl<P: Params> WindowManager<P> {
 
     window
   }
+  /// Registers a closure to run when the window with the given label is destroyed.
+  ///
+  /// The wry runtime doesn't report the windows closed natively, so the closure runs when the
+  /// window is closed with [`Window::close`], or when a new window reuses its label.
+  pub fn on_window_destroy<F: FnOnce() + Send + 'static>(&self, label: P::Label, handler: F) {
+    self
+      .inner
+      .destroy_handlers
+      .lock()
+      .expect("poisoned destroy handlers")
+      .entry(label)
+      .or_default()
+      .push(Box::new(handler));
+  }
+  /// Detaches the window from the manager and releases everything tied to its lifetime.
+  pub fn on_window_close(&self, label: &P::Label) {
+    self.windows_lock().remove(label);
+    // dropping the senders fails the pending evaluations of the window
+    self
+      .inner
+      .eval_results
+      .lock()
+      .expect("poisoned eval results")
+      .retain(|_, (window, _)| window != label);
+    self
+      .inner
+      .current_urls
+      .lock()
+      .expect("poisoned current urls")
+      .remove(label);
+    self
+      .inner
+      .binary_responses
+      .lock()
+      .expect("poisoned binary responses")
+      .retain(|_, response| &response.window != label);
+    self.run_destroy_handlers(label);
+  }
+  /// Runs and removes the destroy handlers of the window with the given label.
+  fn run_destroy_handlers(&self, label: &P::Label) {
+    let handlers = self
+      .inner
+      .destroy_handlers
+      .lock()
+      .expect("poisoned destroy handlers")
+      .remove(label);
+    for handler in handlers.into_iter().flatten() {
+      handler();
+    }
+  }
   pub fn emit_filter_internal<S: Serialize + Clone, F: Fn(&Window<P>) -> bool>(
     &self,
     event: String,
@@ -553,6 +845,69 @@ impl<P: Params> WindowManager<P> {
       .expect("poisoned salt mutex")
       .remove(&uuid)
   }
+  /// Stores raw bytes to be fetched once by the window with the given label,
+  /// returning the URL serving them.
+  ///
+  /// The bytes are dropped if they aren't fetched within a minute
+  /// or once the window is closed.
+  pub fn register_binary_response(&self, label: P::Label, bytes: Vec<u8>) -> String {
+    let id = Uuid::new_v4();
+    let mut responses = self
+      .inner
+      .binary_responses
+      .lock()
+      .expect("poisoned binary responses");
+    responses.retain(|_, response| !response.is_expired());
+    responses.insert(
+      id,
+      BinaryResponse {
+        window: label,
+        bytes,
+        registered_at: Instant::now(),
+      },
+    );
+    format!(
+      "tauri://{}/{}{}",
+      self.inner.config.tauri.bundle.identifier, BINARY_RESPONSE_PATH, id
+    )
+  }
+  /// Registers a script evaluation on the window with the given label, returning its id and outcome.
+  pub(crate) fn register_eval_result(
+    &self,
+    label: P::Label,
+  ) -> (Uuid, oneshot::Receiver<EvalOutcome>) {
+    let id = Uuid::new_v4();
+    let (tx, rx) = oneshot::channel();
+    self
+      .inner
+      .eval_results
+      .lock()
+      .expect("poisoned eval results")
+      .insert(id, (label, tx));
+    (id, rx)
+  }
+  /// Settles a pending script evaluation, or drops it if `outcome` is `None`.
+  ///
+  /// Evaluations that already settled are ignored.
+  pub(crate) fn settle_eval_result(&self, id: Uuid, outcome: Option<EvalOutcome>) {
+    let pending = self
+      .inner
+      .eval_results
+      .lock()
+      .expect("poisoned eval results")
+      .remove(&id);
+    if let (Some((_, tx)), Some(outcome)) = (pending, outcome) {
+      let _ = tx.send(outcome);
+    }
+  }
+  /// Settles a pending script evaluation with the result posted by the webview.
+  pub(crate) fn resolve_eval_result(&self, payload: EvalResultPayload) {
+    let outcome = match payload.error {
+      Some(error) => EvalOutcome::Exception(error),
+      None => EvalOutcome::Value(payload.result),
+    };
+    self.settle_eval_result(payload.id, Some(outcome))
+  }
   pub fn get_window(&self, label: &P::Label) -> Option<Window<P>> {
     self.windows_lock().get(label).cloned()
   }
@@ -11,7 +11,7 @@ use crate::{
   },
   Attributes, Icon, Params,
 };
-use std::convert::TryFrom;
+use std::{convert::TryFrom, path::PathBuf, sync::mpsc::Sender};
 
 pub(crate) mod app;
 pub mod flavors;
@@ -20,22 +20,64 @@ pub mod tag;
 pub mod webview;
 pub mod window;
 
+/// An event emitted by the [`Runtime`] event loop.
+#[derive(Debug)]
+pub enum RunEvent {
+  /// The event loop is exiting.
+  Exit,
+  /// The last window was closed and the event loop is about to exit.
+  ///
+  /// Sending [`ExitRequestedEventAction::Prevent`] keeps the event loop running.
+  ExitRequested {
+    /// Channel used to prevent the exit.
+    tx: Sender<ExitRequestedEventAction>,
+  },
+}
+
+/// Action to take when the event loop is about to exit.
+#[derive(Debug)]
+pub enum ExitRequestedEventAction {
+  /// Prevent the event loop from exiting.
+  Prevent,
+}
+
 /// The webview runtime interface.
 pub trait Runtime: Sized + 'static {
   /// The message dispatcher.
   type Dispatcher: Dispatch<Runtime = Self>;
 
+  /// The runtime handle type.
+  type Handle: RuntimeHandle<Runtime = Self>;
+
   /// Creates a new webview runtime.
   fn new() -> crate::Result<Self>;
 
+  /// Gets a thread-safe handle to the runtime.
+  fn handle(&self) -> Self::Handle;
+
   /// Create a new webview window.
   fn create_window<P: Params<Runtime = Self>>(
     &mut self,
     pending: PendingWindow<P>,
   ) -> crate::Result<DetachedWindow<P>>;
 
-  /// Run the webview runtime.
-  fn run(self);
+  /// Run the webview runtime, calling `callback` with the event loop events.
+  fn run<F: FnMut(RunEvent) + Send + 'static>(self, callback: F);
+}
+
+/// A thread-safe handle to the [`Runtime`], usable before and while it runs.
+pub trait RuntimeHandle: Clone + Send + Sync + Sized + 'static {
+  /// The runtime this [`RuntimeHandle`] belongs to.
+  type Runtime: Runtime<Handle = Self>;
+
+  /// Create a new webview window.
+  fn create_window<P: Params<Runtime = Self::Runtime>>(
+    &self,
+    pending: PendingWindow<P>,
+  ) -> crate::Result<DetachedWindow<P>>;
+
+  /// Exits the event loop with the given exit code, emitting [`RunEvent::Exit`] first.
+  fn exit(&self, code: i32) -> crate::Result<()>;
 }
 
 /// Webview dispatcher. A thread-safe handle to the webview API.
@@ -79,8 +121,11 @@ pub trait Dispatch: Clone + Send + Sized + 'static {
   /// Hides the window.
   fn hide(&self) -> crate::Result<()>;
 
-  /// Closes the window.
-  fn close(&self) -> crate::Result<()>;
+  /// Closes the window, returning whether it was closed.
+  ///
+  /// Closing the last window emits [`RunEvent::ExitRequested`]. If the exit is prevented,
+  /// the runtime may keep the window open, hiding it instead.
+  fn close(&self) -> crate::Result<bool>;
 
   /// Updates the hasDecorations flag.
   fn set_decorations(&self, decorations: bool) -> crate::Result<()>;
@@ -118,6 +163,40 @@ pub trait Dispatch: Clone + Send + Sized + 'static {
   /// Updates the window icon.
   fn set_icon(&self, icon: Self::Icon) -> crate::Result<()>;
 
+  /// Starts a native drag of the given files from the window to the OS.
+  ///
+  /// The icon represents the dragged files, defaulting to the platform one.
+  fn start_file_drag(&self, paths: Vec<PathBuf>, icon: Option<Self::Icon>) -> crate::Result<()>;
+
+  /// Runs the closure on the main thread without waiting for it, e.g. to show native dialogs.
+  fn run_on_main_thread<F: FnOnce() + Send + 'static>(&self, f: F) -> crate::Result<()>;
+
+  /// Navigates the webview to the given URL.
+  ///
+  /// The navigation hooks are run by Tauri before calling this.
+  fn navigate<S: Into<String>>(&self, url: S) -> crate::Result<()>;
+
+  /// Reloads the current page.
+  fn reload(&self) -> crate::Result<()>;
+
+  /// Navigates back in the webview history.
+  fn go_back(&self) -> crate::Result<()>;
+
+  /// Navigates forward in the webview history.
+  fn go_forward(&self) -> crate::Result<()>;
+
+  /// Opens the print dialog for the current page.
+  fn print(&self) -> crate::Result<()>;
+
+  /// Sets the zoom level of the webview.
+  fn set_zoom(&self, zoom: f64) -> crate::Result<()>;
+
+  /// Opens the webview devtools.
+  fn open_devtools(&self) -> crate::Result<()>;
+
+  /// Closes the webview devtools.
+  fn close_devtools(&self) -> crate::Result<()>;
+
   /// Executes javascript on the window this [`Dispatch`] represents.
   fn eval_script<S: Into<String>>(&self, script: S) -> crate::Result<()>;
 }
@@ -6,6 +6,7 @@
 
 use crate::runtime::Icon;
 use crate::{api::config::WindowConfig, runtime::window::DetachedWindow};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use std::{convert::TryFrom, path::PathBuf};
 
@@ -80,6 +81,12 @@ pub trait Attributes: AttributesBase {
   /// Whether the window should always be on top of other windows.
   fn always_on_top(self, always_on_top: bool) -> Self;
 
+  /// Whether the window should be hidden from the taskbar.
+  fn skip_taskbar(self, skip: bool) -> Self;
+
+  /// Whether the webview context menu is enabled or not.
+  fn context_menu(self, enabled: bool) -> Self;
+
   /// Sets the window icon.
   fn icon(self, icon: Self::Icon) -> Self;
 
@@ -112,20 +119,63 @@ pub struct CustomProtocol {
   pub handler: Box<dyn Fn(&str) -> crate::Result<Vec<u8>> + Send>,
 }
 
+/// The cursor position of a file drop event, in logical pixels relative to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FileDropPosition {
+  /// The horizontal position.
+  pub x: f64,
+  /// The vertical position.
+  pub y: f64,
+}
+
 /// The file drop event payload.
+///
+/// The cursor position is `None` if the runtime doesn't report it, which is always the case
+/// with the wry runtime.
 #[derive(Debug, Clone)]
 pub enum FileDropEvent {
   /// The file(s) have been dragged onto the window, but have not been dropped yet.
-  Hovered(Vec<PathBuf>),
+  Hovered {
+    /// The dragged paths.
+    paths: Vec<PathBuf>,
+    /// The cursor position.
+    position: Option<FileDropPosition>,
+  },
   /// The file(s) have been dropped onto the window.
-  Dropped(Vec<PathBuf>),
+  Dropped {
+    /// The dropped paths.
+    paths: Vec<PathBuf>,
+    /// The cursor position.
+    position: Option<FileDropPosition>,
+  },
   /// The file drop was aborted.
   Cancelled,
 }
 
+impl FileDropEvent {
+  /// The dragged or dropped paths, empty if the drop was cancelled.
+  pub fn paths(&self) -> &[PathBuf] {
+    match self {
+      Self::Hovered { paths, .. } | Self::Dropped { paths, .. } => paths,
+      Self::Cancelled => &[],
+    }
+  }
+
+  /// The cursor position, if known.
+  pub fn position(&self) -> Option<FileDropPosition> {
+    match self {
+      Self::Hovered { position, .. } | Self::Dropped { position, .. } => *position,
+      Self::Cancelled => None,
+    }
+  }
+}
+
 /// Rpc handler.
 pub(crate) type WebviewRpcHandler<M> = Box<dyn Fn(DetachedWindow<M>, RpcRequest) + Send>;
 
 /// File drop handler callback
 /// Return `true` in the callback to block the OS' default behavior of handling a file drop.
 pub(crate) type FileDropHandler<M> = Box<dyn Fn(FileDropEvent, DetachedWindow<M>) -> bool + Send>;
+
+/// Navigation handler callback, run when the webview navigates to a new URL.
+pub(crate) type NavigationHandler<M> = Box<dyn Fn(String, DetachedWindow<M>) + Send>;
@@ -6,24 +6,31 @@
 
 use crate::api::config::WindowConfig;
 use crate::{
+  accelerator::AcceleratorManager,
   api::config::WindowUrl,
   event::{Event, EventHandler},
-  hooks::{InvokeMessage, InvokePayload, PageLoadPayload},
+  hooks::{AcceleratorPayload, EvalResultPayload, InvokeMessage, InvokePayload, PageLoadPayload},
   runtime::{
     tag::ToJavascript,
-    webview::{CustomProtocol, FileDropHandler, WebviewRpcHandler},
+    webview::{CustomProtocol, FileDropHandler, NavigationHandler, WebviewRpcHandler},
     Dispatch, Runtime,
   },
   sealed::{ManagerBase, RuntimeOrDispatch},
-  Attributes, Icon, Manager, Params,
+  AppHandle, Attributes, Icon, Manager, Params,
 };
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value as JsonValue;
 use std::{
   convert::TryInto,
+  future::Future,
   hash::{Hash, Hasher},
+  path::PathBuf,
+  time::Duration,
 };
 
+/// How long [`Window::eval_with_result`] waits for the script result.
+const EVAL_RESULT_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// A webview window that has yet to be built.
 pub struct PendingWindow<M: Params> {
   /// The label that the window will be named.
@@ -43,6 +50,9 @@ pub struct PendingWindow<M: Params> {
 
   /// How to handle a file dropping onto the webview window.
   pub file_drop_handler: Option<FileDropHandler<M>>,
+
+  /// How to handle the webview window navigating to a new URL.
+  pub navigation_handler: Option<NavigationHandler<M>>,
 }
 
 impl<M: Params> PendingWindow<M> {
@@ -59,6 +69,7 @@ impl<M: Params> PendingWindow<M> {
       rpc_handler: None,
       custom_protocol: None,
       file_drop_handler: None,
+      navigation_handler: None,
     }
   }
 
@@ -73,6 +84,7 @@ impl<M: Params> PendingWindow<M> {
       rpc_handler: None,
       custom_protocol: None,
       file_drop_handler: None,
+      navigation_handler: None,
     }
   }
 }
@@ -113,7 +125,7 @@ impl<M: Params> PartialEq for DetachedWindow<M> {
 /// We want to export the runtime related window at the crate root, but not look like a re-export.
 pub(crate) mod export {
   use super::*;
-  use crate::runtime::manager::WindowManager;
+  use crate::runtime::manager::{EvalOutcome, WindowManager};
 
   /// A webview window managed by Tauri.
   ///
@@ -170,6 +182,11 @@ pub(crate) mod export {
       Self { manager, window }
     }
 
+    /// A handle to the application this window belongs to.
+    pub fn app_handle(&self) -> AppHandle<P> {
+      AppHandle::new(self.manager.clone())
+    }
+
     /// The current window's dispatcher.
     pub(crate) fn dispatcher(&self) -> <P::Runtime as Runtime>::Dispatcher {
       self.window.dispatcher.clone()
@@ -181,6 +198,18 @@ pub(crate) mod export {
       if &command == "__initialized" {
         let payload: PageLoadPayload = serde_json::from_value(payload.inner)?;
         manager.run_on_page_load(self, payload);
+      } else if &command == "__evalResult" {
+        let result: EvalResultPayload = serde_json::from_value(payload.inner.clone())?;
+        manager.resolve_eval_result(result);
+        InvokeMessage::new(self, command, payload).resolve(());
+      } else if command == crate::accelerator::ACCELERATOR_COMMAND {
+        // only triggers the accelerators scoped to this window, whatever page sent the command
+        let triggered: AcceleratorPayload = serde_json::from_value(payload.inner.clone())?;
+        AcceleratorManager::new(manager).trigger(self.clone(), &triggered.accelerator)?;
+        InvokeMessage::new(self, command, payload).resolve(());
+      } else if command == crate::accelerator::ACCELERATORS_COMMAND {
+        let accelerators = AcceleratorManager::new(manager).scoped_to(self.label());
+        InvokeMessage::new(self, command, payload).resolve(accelerators);
       } else {
         let message = InvokeMessage::new(self, command.to_string(), payload);
         if let Some(module) = &message.payload.tauri_module {
@@ -275,6 +304,147 @@ pub(crate) mod export {
       self.window.dispatcher.eval_script(js)
     }
 
+    /// Runs the closure on the main thread, resolving to its result, e.g. to show native dialogs.
+    ///
+    /// Never resolves if awaited on the main thread, as the closure can't run before it is free.
+    pub async fn run_on_main_thread<T, F>(&self, f: F) -> crate::Result<T>
+    where
+      T: Send + 'static,
+      F: FnOnce() -> T + Send + 'static,
+    {
+      let (tx, rx) = tokio::sync::oneshot::channel();
+      self.window.dispatcher.run_on_main_thread(move || {
+        let _ = tx.send(f());
+      })?;
+      // the window was closed before running the closure
+      rx.await.map_err(|_| crate::Error::WebviewNotFound)
+    }
+
+    /// Starts a native drag of the given files from this window to the OS, e.g. to let users
+    /// drop them on their file manager.
+    ///
+    /// The wry runtime doesn't support dragging files out yet, so this always fails with
+    /// [`crate::Error::Unsupported`] there.
+    pub fn start_file_drag(&self, paths: Vec<PathBuf>, icon: Option<Icon>) -> crate::Result<()> {
+      let icon = icon.map(TryInto::try_into).transpose()?;
+      self.window.dispatcher.start_file_drag(paths, icon)
+    }
+
+    /// Navigates this window to the given URL, notifying the navigation hooks.
+    pub fn navigate(&self, url: &str) -> crate::Result<()> {
+      self.manager.run_on_navigation(self.clone(), url);
+      self.window.dispatcher.navigate(url)
+    }
+
+    /// Reloads the current page of this window.
+    pub fn reload(&self) -> crate::Result<()> {
+      self.window.dispatcher.reload()
+    }
+
+    /// Navigates back in this window's history.
+    pub fn go_back(&self) -> crate::Result<()> {
+      self.window.dispatcher.go_back()
+    }
+
+    /// Navigates forward in this window's history.
+    pub fn go_forward(&self) -> crate::Result<()> {
+      self.window.dispatcher.go_forward()
+    }
+
+    /// The URL of the page loaded on this window, or of its last allowed navigation.
+    ///
+    /// This is tracked from the page load and navigation hooks rather than read from the webview,
+    /// so it goes stale on navigations the runtime doesn't report, such as history navigations,
+    /// `window.location` changes and in-page URL changes through `history.pushState` or the hash.
+    pub fn current_url(&self) -> Option<String> {
+      self.manager.current_url(self.label())
+    }
+
+    /// Opens the print dialog for the current page of this window.
+    pub fn print(&self) -> crate::Result<()> {
+      self.window.dispatcher.print()
+    }
+
+    /// Sets the zoom level of this window's webview.
+    pub fn set_zoom(&self, zoom: impl Into<f64>) -> crate::Result<()> {
+      self.window.dispatcher.set_zoom(zoom.into())
+    }
+
+    /// Opens the devtools of this window's webview.
+    pub fn open_devtools(&self) -> crate::Result<()> {
+      self.window.dispatcher.open_devtools()
+    }
+
+    /// Closes the devtools of this window's webview.
+    pub fn close_devtools(&self) -> crate::Result<()> {
+      self.window.dispatcher.close_devtools()
+    }
+
+    /// Evaluates a JavaScript expression on this window, resolving to its value.
+    ///
+    /// The expression can evaluate to a `Promise`, which is awaited. Exceptions thrown by the
+    /// expression are returned as [`crate::Error::Eval`]. The result is posted back through the
+    /// Tauri API, so the window must load a local URL.
+    ///
+    /// Fails with [`crate::Error::EvalTimeout`] if the result doesn't arrive within ten seconds,
+    /// see [`Window::eval_with_result_timeout`].
+    pub fn eval_with_result<T: DeserializeOwned>(
+      &self,
+      js: &str,
+    ) -> impl Future<Output = crate::Result<T>> + Send {
+      self.eval_with_result_timeout(js, EVAL_RESULT_TIMEOUT)
+    }
+
+    /// Like [`Window::eval_with_result`], waiting up to `timeout` for the result.
+    pub fn eval_with_result_timeout<T: DeserializeOwned>(
+      &self,
+      js: &str,
+      timeout: Duration,
+    ) -> impl Future<Output = crate::Result<T>> + Send {
+      let (id, outcome) = self.manager.register_eval_result(self.label().clone());
+      let script = format!(
+        r#"
+          (function () {{
+            function post(payload) {{
+              payload.id = '{id}'
+              window.__TAURI__.invoke('__evalResult', payload)
+            }}
+            function fail(e) {{
+              post({{ error: String(e) }})
+            }}
+            try {{
+              Promise.resolve((function () {{ return ({js}
+              ) }})()).then(function (result) {{ post({{ result: result }}) }}, fail)
+            }} catch (e) {{
+              fail(e)
+            }}
+          }})()
+        "#,
+        id = id,
+        js = js
+      );
+
+      let manager = self.manager.clone();
+      if self.eval(&script).is_ok() {
+        crate::async_runtime::spawn(async move {
+          tokio::time::sleep(timeout).await;
+          manager.settle_eval_result(id, Some(EvalOutcome::Timeout));
+        });
+      } else {
+        manager.settle_eval_result(id, None);
+      }
+
+      async move {
+        match outcome.await {
+          Ok(EvalOutcome::Value(value)) => Ok(serde_json::from_value(value)?),
+          Ok(EvalOutcome::Exception(error)) => Err(crate::Error::Eval(error)),
+          Ok(EvalOutcome::Timeout) => Err(crate::Error::EvalTimeout),
+          // the window was closed or the script couldn't be sent
+          Err(_) => Err(crate::Error::WebviewNotFound),
+        }
+      }
+    }
+
     /// Determines if this window should be resizable.
     pub fn set_resizable(&self, resizable: bool) -> crate::Result<()> {
       self.window.dispatcher.set_resizable(resizable)
@@ -316,8 +486,22 @@ pub(crate) mod export {
     }
 
     /// Closes this window.
+    ///
+    /// Resources tied to the window lifetime (e.g. file watchers) are released.
+    /// With the wry runtime, if this is the last window and the exit is prevented on
+    /// [`crate::RunEvent::ExitRequested`], the window is hidden instead and keeps its resources.
     pub fn close(&self) -> crate::Result<()> {
-      self.window.dispatcher.close()
+      if self.window.dispatcher.close()? {
+        self.manager.on_window_close(self.label());
+      }
+      Ok(())
+    }
+
+    /// Runs the given closure once this window is closed.
+    pub(crate) fn on_destroy<F: FnOnce() + Send + 'static>(&self, handler: F) {
+      self
+        .manager
+        .on_window_destroy(self.window.label.clone(), handler)
     }
 
     /// Determines if this window should be [decorated].
@@ -401,3 +585,76 @@ pub(crate) mod export {
     }
   }
 }
+
+#[cfg(test)]
+mod test {
+  use crate::{
+    runtime::flavors::mock::{mock_context, DispatchCall, MockDispatcher, MockRuntime},
+    Builder, Manager,
+  };
+  use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+  };
+
+  #[test]
+  fn check_navigation_hook() {
+    let navigations = Arc::new(Mutex::new(Vec::new()));
+    let recorded = navigations.clone();
+    let app = Builder::<String, String, _, MockRuntime>::new()
+      .on_navigation(move |_, url| recorded.lock().unwrap().push(url.to_string()))
+      .build(mock_context())
+      .expect("failed to build mock app");
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+    mock.clear_calls();
+
+    mock.navigate_to("https://tauri.studio");
+    assert_eq!(
+      window.current_url().as_deref(),
+      Some("https://tauri.studio")
+    );
+
+    window
+      .navigate("https://tauri.studio/docs")
+      .expect("failed to navigate");
+    assert_eq!(
+      window.current_url().as_deref(),
+      Some("https://tauri.studio/docs")
+    );
+    assert_eq!(
+      *navigations.lock().unwrap(),
+      vec!["https://tauri.studio", "https://tauri.studio/docs"]
+    );
+    assert_eq!(
+      mock.calls(),
+      vec![DispatchCall::Navigate("https://tauri.studio/docs".into())]
+    );
+  }
+
+  #[test]
+  fn check_eval_with_result() {
+    let app = Builder::<String, String, _, MockRuntime>::new()
+      .build(mock_context())
+      .expect("failed to build mock app");
+    let window = app.get_window(&"main".into()).expect("missing main window");
+    let mock = MockDispatcher::from_window(&window);
+
+    let result = window.eval_with_result::<u32>("1 + 1");
+    mock.respond_eval(Ok(2.into()));
+    assert_eq!(crate::async_runtime::block_on(result).unwrap(), 2);
+
+    let result = window.eval_with_result::<u32>("missing()");
+    mock.respond_eval(Err("ReferenceError: missing is not defined".into()));
+    assert!(matches!(
+      crate::async_runtime::block_on(result),
+      Err(crate::Error::Eval(_))
+    ));
+
+    let result = window.eval_with_result_timeout::<u32>("1", Duration::from_millis(10));
+    assert!(matches!(
+      crate::async_runtime::block_on(result),
+      Err(crate::Error::EvalTimeout)
+    ));
+  }
+}
@@ -0,0 +1,502 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Sidecars supervised by the application.
+//!
+//! Supervised sidecars are restarted with an exponential backoff when they exit, and stopped in
+//! the reverse order they were started in when the application exits. Their state changes are
+//! emitted to the windows as the `tauri://sidecar` event, and each line they write to stdout or
+//! stderr as the `tauri://sidecar-log` event. The lines are also logged with the `sidecar:<name>`
+//! target, see [`crate::logging`].
+
+use crate::{
+  api::{
+    command::{Command, CommandChild, CommandEvent, TerminatedPayload},
+    config::SidecarConfig,
+    private::async_runtime::Receiver,
+  },
+  runtime::manager::WindowManager,
+  Params,
+};
+use serde::Serialize;
+use std::{
+  sync::MutexGuard,
+  time::{Duration, Instant},
+};
+
+/// The event emitted to the windows when the status of a sidecar changes.
+const SIDECAR_EVENT: &str = "tauri://sidecar";
+/// The event emitted to the windows when a sidecar writes a line to stdout or stderr.
+const SIDECAR_LOG_EVENT: &str = "tauri://sidecar-log";
+
+/// The delay before the first restart of a sidecar.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// The maximum delay between two restarts of a sidecar.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long a sidecar must run for its backoff to be reset.
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// How long a sidecar gets to exit once it is stopped, before it is killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A sidecar to supervise.
+#[derive(Debug, Clone)]
+pub struct Sidecar {
+  name: String,
+  args: Vec<String>,
+  restart: bool,
+  max_restarts: Option<u32>,
+}
+
+impl Sidecar {
+  /// Creates a sidecar with the given name, as declared on `tauri > bundle > externalBin`.
+  ///
+  /// The sidecar is restarted every time it exits, until it is stopped.
+  pub fn new<S: Into<String>>(name: S) -> Self {
+    Self {
+      name: name.into(),
+      args: Vec::new(),
+      restart: true,
+      max_restarts: None,
+    }
+  }
+
+  /// Appends arguments to the sidecar.
+  pub fn args<I, S>(mut self, args: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    self
+      .args
+      .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+    self
+  }
+
+  /// Whether the sidecar is restarted when it exits or not.
+  pub fn restart(mut self, restart: bool) -> Self {
+    self.restart = restart;
+    self
+  }
+
+  /// How many times the sidecar is restarted before giving up.
+  pub fn max_restarts(mut self, max_restarts: u32) -> Self {
+    self.max_restarts.replace(max_restarts);
+    self
+  }
+}
+
+impl From<SidecarConfig> for Sidecar {
+  fn from(config: SidecarConfig) -> Self {
+    Self {
+      name: config.name,
+      args: config.args,
+      restart: config.restart,
+      max_restarts: config.max_restarts,
+    }
+  }
+}
+
+/// The status of a supervised sidecar.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum SidecarStatus {
+  /// The sidecar is running.
+  Running {
+    /// The process id.
+    pid: u32,
+  },
+  /// The sidecar exited, and will be restarted after the delay.
+  #[serde(rename_all = "camelCase")]
+  Restarting {
+    /// The exit code of the sidecar, if it didn't fail to spawn.
+    code: Option<i32>,
+    /// The signal that terminated the sidecar.
+    signal: Option<i32>,
+    /// How many times the sidecar was restarted.
+    attempt: u32,
+    /// The delay before the restart, in milliseconds.
+    delay_ms: u64,
+  },
+  /// The sidecar exited and won't be restarted.
+  Exited {
+    /// The exit code of the sidecar.
+    code: Option<i32>,
+    /// The signal that terminated the sidecar.
+    signal: Option<i32>,
+  },
+  /// The sidecar couldn't be started, or restarted within its maximum number of restarts.
+  Failed {
+    /// Why the sidecar couldn't be started.
+    error: String,
+  },
+  /// The sidecar was stopped.
+  Stopped,
+}
+
+impl SidecarStatus {
+  fn is_active(&self) -> bool {
+    matches!(self, Self::Running { .. } | Self::Restarting { .. })
+  }
+}
+
+#[derive(Serialize)]
+struct StatusPayload<'a> {
+  name: &'a str,
+  #[serde(flatten)]
+  status: &'a SidecarStatus,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Stream {
+  Stdout,
+  Stderr,
+}
+
+#[derive(Serialize)]
+struct LogPayload<'a> {
+  name: &'a str,
+  stream: Stream,
+  line: &'a str,
+}
+
+struct Supervised {
+  name: String,
+  status: SidecarStatus,
+  child: Option<CommandChild>,
+  /// Incremented every time the sidecar is started, so the tasks supervising a previous run stop.
+  generation: u32,
+  stopping: bool,
+}
+
+/// The supervised sidecars, in the order they were started in.
+#[derive(Default)]
+pub(crate) struct Sidecars(Vec<Supervised>);
+
+impl Sidecars {
+  fn get_mut(&mut self, name: &str) -> Option<&mut Supervised> {
+    self.0.iter_mut().find(|sidecar| sidecar.name == name)
+  }
+
+  /// The sidecar with the given name, if its current run is the given one and it wasn't stopped.
+  fn current(&mut self, name: &str, generation: u32) -> Option<&mut Supervised> {
+    self
+      .get_mut(name)
+      .filter(|sidecar| sidecar.generation == generation && !sidecar.stopping)
+  }
+}
+
+/// The delay before the next restart of a sidecar.
+fn next_backoff(backoff: Duration) -> Duration {
+  std::cmp::min(backoff * 2, MAX_BACKOFF)
+}
+
+/// Supervises the sidecars of the application.
+pub struct SidecarSupervisor<P: Params> {
+  manager: WindowManager<P>,
+}
+
+impl<P: Params> Clone for SidecarSupervisor<P> {
+  fn clone(&self) -> Self {
+    Self {
+      manager: self.manager.clone(),
+    }
+  }
+}
+
+impl<P: Params> SidecarSupervisor<P> {
+  pub(crate) fn new(manager: WindowManager<P>) -> Self {
+    Self { manager }
+  }
+
+  fn sidecars(&self) -> MutexGuard<'_, Sidecars> {
+    self
+      .manager
+      .inner
+      .sidecars
+      .lock()
+      .expect("poisoned sidecars")
+  }
+
+  /// The status of the sidecar with the given name, if it was started.
+  pub fn status(&self, name: &str) -> Option<SidecarStatus> {
+    self
+      .sidecars()
+      .0
+      .iter()
+      .find(|sidecar| sidecar.name == name)
+      .map(|sidecar| sidecar.status.clone())
+  }
+
+  /// Starts supervising a sidecar.
+  ///
+  /// Fails if a sidecar with the same name is already running, or if it can't be spawned.
+  pub fn start(&self, sidecar: Sidecar) -> crate::Result<()> {
+    let generation = {
+      let mut sidecars = self.sidecars();
+      match sidecars.get_mut(&sidecar.name) {
+        Some(supervised) if supervised.status.is_active() => {
+          return Err(crate::Error::SidecarAlreadyRunning(sidecar.name));
+        }
+        Some(supervised) => {
+          supervised.generation += 1;
+          supervised.stopping = false;
+          supervised.generation
+        }
+        None => {
+          sidecars.0.push(Supervised {
+            name: sidecar.name.clone(),
+            status: SidecarStatus::Stopped,
+            child: None,
+            generation: 0,
+            stopping: false,
+          });
+          0
+        }
+      }
+    };
+
+    let rx = match self.spawn(&sidecar, generation) {
+      Some(Ok(rx)) => rx,
+      Some(Err(e)) => {
+        self.set_status(
+          &sidecar.name,
+          generation,
+          SidecarStatus::Failed {
+            error: e.to_string(),
+          },
+        );
+        return Err(e.into());
+      }
+      // stopped while spawning
+      None => return Ok(()),
+    };
+    let supervisor = self.clone();
+    crate::async_runtime::spawn(async move {
+      supervisor.supervise(sidecar, generation, rx).await;
+    });
+    Ok(())
+  }
+
+  /// Starts supervising the sidecars declared in the config.
+  pub(crate) fn start_config(&self) -> crate::Result<()> {
+    for config in self.manager.config().tauri.sidecars.clone() {
+      self.start(config.into())?;
+    }
+    Ok(())
+  }
+
+  /// Spawns the sidecar process, returning its events unless the sidecar was stopped meanwhile.
+  fn spawn(
+    &self,
+    sidecar: &Sidecar,
+    generation: u32,
+  ) -> Option<crate::api::Result<Receiver<CommandEvent>>> {
+    let (rx, child) = match Command::new_sidecar(&sidecar.name)
+      .args(&sidecar.args)
+      .spawn()
+    {
+      Ok(spawned) => spawned,
+      Err(e) => {
+        log::error!("failed to start sidecar `{}`: {}", sidecar.name, e);
+        return Some(Err(e));
+      }
+    };
+
+    let pid = child.pid();
+    match self.sidecars().current(&sidecar.name, generation) {
+      Some(supervised) => {
+        supervised.child.replace(child);
+      }
+      None => {
+        // stopped while spawning
+        let _ = child.kill();
+        return None;
+      }
+    }
+    self.set_status(&sidecar.name, generation, SidecarStatus::Running { pid });
+    Some(Ok(rx))
+  }
+
+  /// Restarts the sidecar every time it exits, until it is stopped or runs out of restarts.
+  ///
+  /// A restart that fails to spawn the sidecar counts as a crash, and is retried the same way.
+  async fn supervise(&self, sidecar: Sidecar, generation: u32, mut rx: Receiver<CommandEvent>) {
+    let mut attempt = 0;
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+      let started = Instant::now();
+      let TerminatedPayload {
+        mut code,
+        mut signal,
+      } = self.forward_events(&sidecar.name, &mut rx).await;
+
+      // a sidecar that ran long enough starts over
+      if started.elapsed() >= STABLE_AFTER {
+        attempt = 0;
+        backoff = INITIAL_BACKOFF;
+      }
+      let mut spawn_error = None;
+      rx = loop {
+        if !sidecar.restart || sidecar.max_restarts.map_or(false, |max| attempt >= max) {
+          let status = match spawn_error {
+            Some(error) => SidecarStatus::Failed { error },
+            None => SidecarStatus::Exited { code, signal },
+          };
+          self.set_status(&sidecar.name, generation, status);
+          return;
+        }
+
+        attempt += 1;
+        log::warn!(
+          "sidecar `{}` exited with code {:?}, restarting in {}ms",
+          sidecar.name,
+          code,
+          backoff.as_millis()
+        );
+        let restarting = SidecarStatus::Restarting {
+          code,
+          signal,
+          attempt,
+          delay_ms: backoff.as_millis() as u64,
+        };
+        if !self.set_status(&sidecar.name, generation, restarting) {
+          return;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = next_backoff(backoff);
+
+        match self.spawn(&sidecar, generation) {
+          Some(Ok(rx)) => break rx,
+          // the sidecar never ran, so it has no exit status
+          Some(Err(e)) => {
+            spawn_error.replace(e.to_string());
+            code = None;
+            signal = None;
+          }
+          // stopped while spawning
+          None => return,
+        }
+      };
+    }
+  }
+
+  /// Forwards the output of the sidecar until it terminates.
+  async fn forward_events(&self, name: &str, rx: &mut Receiver<CommandEvent>) -> TerminatedPayload {
+    let target = format!("sidecar:{}", name);
+    while let Some(event) = rx.recv().await {
+      let (stream, line) = match event {
+        CommandEvent::Stdout(line) => (Stream::Stdout, line),
+        CommandEvent::Stderr(line) => (Stream::Stderr, line),
+        CommandEvent::Terminated(payload) => return payload,
+        _ => continue,
+      };
+      let level = match stream {
+        Stream::Stdout => log::Level::Info,
+        Stream::Stderr => log::Level::Warn,
+      };
+      log::log!(target: target.as_str(), level, "{}", line);
+      let _ = self.manager.emit_filter_internal(
+        SIDECAR_LOG_EVENT.to_string(),
+        Some(LogPayload {
+          name,
+          stream,
+          line: &line,
+        }),
+        |_| true,
+      );
+    }
+    // the channel closes without a status if the process couldn't be waited on
+    TerminatedPayload {
+      code: None,
+      signal: None,
+    }
+  }
+
+  /// Sets the status of the given run of the sidecar, returning `false` if it was stopped.
+  fn set_status(&self, name: &str, generation: u32, status: SidecarStatus) -> bool {
+    match self.sidecars().current(name, generation) {
+      Some(supervised) => {
+        supervised.status = status.clone();
+        if !status.is_active() {
+          supervised.child.take();
+        }
+      }
+      None => return false,
+    }
+    self.emit_status(name, &status);
+    true
+  }
+
+  fn emit_status(&self, name: &str, status: &SidecarStatus) {
+    let _ = self.manager.emit_filter_internal(
+      SIDECAR_EVENT.to_string(),
+      Some(StatusPayload { name, status }),
+      |_| true,
+    );
+  }
+
+  /// Stops the sidecar with the given name, waiting for it to exit.
+  pub fn stop(&self, name: &str) -> crate::Result<()> {
+    let child = {
+      let mut sidecars = self.sidecars();
+      match sidecars.get_mut(name) {
+        Some(supervised) if supervised.status.is_active() => {
+          supervised.stopping = true;
+          supervised.status = SidecarStatus::Stopped;
+          supervised.child.take()
+        }
+        _ => return Ok(()),
+      }
+    };
+    if let Some(child) = child {
+      child.terminate(STOP_TIMEOUT)?;
+    }
+    self.emit_status(name, &SidecarStatus::Stopped);
+    Ok(())
+  }
+
+  /// Stops all the sidecars, in the reverse order they were started in.
+  pub fn stop_all(&self) {
+    let names = self
+      .sidecars()
+      .0
+      .iter()
+      .rev()
+      .map(|sidecar| sidecar.name.clone())
+      .collect::<Vec<_>>();
+    for name in names {
+      let _ = self.stop(&name);
+    }
+  }
+
+  /// Kills the running sidecars without blocking, see [`crate::exit`].
+  pub(crate) fn kill_all(&self) {
+    if let Ok(sidecars) = self.manager.inner.sidecars.try_lock() {
+      for sidecar in sidecars.0.iter().rev() {
+        if let Some(child) = &sidecar.child {
+          child.kill_now();
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::{next_backoff, INITIAL_BACKOFF, MAX_BACKOFF};
+
+  #[test]
+  fn check_backoff() {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut delays = Vec::new();
+    for _ in 0..10 {
+      delays.push(backoff.as_millis());
+      backoff = next_backoff(backoff);
+    }
+    assert_eq!(&delays[..4], &[500, 1000, 2000, 4000]);
+    assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(backoff, MAX_BACKOFF);
+  }
+}
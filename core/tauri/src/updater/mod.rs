@@ -341,7 +341,6 @@ pub use self::error::Error;
 
 use crate::{
   api::{
-    app::restart_application,
     config::UpdaterConfig,
     dialog::{ask, AskResponse},
   },
@@ -413,8 +412,14 @@ pub(crate) async fn check_update_with_dialog<M: Params>(
       // if dialog enabled only
       if updater.should_update && updater_config.dialog {
         let body = updater.body.clone().unwrap_or_else(|| String::from(""));
-        let dialog =
-          prompt_for_install(&updater.clone(), package_info.name, &body.clone(), pubkey).await;
+        let dialog = prompt_for_install(
+          &window,
+          &updater.clone(),
+          package_info.name,
+          &body.clone(),
+          pubkey,
+        )
+        .await;
 
         if dialog.is_err() {
           send_status_update(
@@ -550,7 +555,9 @@ fn send_status_update<M: Params>(window: Window<M>, status: &str, error: Option<
 
 // Prompt a dialog asking if the user want to install the new version
 // Maybe we should add an option to customize it in future versions.
-async fn prompt_for_install(
+// The dialogs are shown on the main thread, as GTK and AppKit require.
+async fn prompt_for_install<M: Params>(
+  window: &Window<M>,
   updater: &self::core::Update,
   app_name: &str,
   body: &str,
@@ -561,18 +568,19 @@ async fn prompt_for_install(
 
   // todo(lemarier): We should review this and make sure we have
   // something more conventional.
-  let should_install = ask(
-    format!(r#"A new version of {} is available! "#, app_name),
-    format!(
-      r#"{} {} is now available -- you have {}.
+  let title = format!(r#"A new version of {} is available! "#, app_name);
+  let message = format!(
+    r#"{} {} is now available -- you have {}.
 
 Would you like to install it now?
 
 Release Notes:
 {}"#,
-      app_name, updater.version, updater.current_version, escaped_body,
-    ),
+    app_name, updater.version, updater.current_version, escaped_body,
   );
+  let should_install = window
+    .run_on_main_thread(move || ask(title, message))
+    .await?;
 
   match should_install {
     AskResponse::Yes => {
@@ -583,13 +591,17 @@ Release Notes:
       updater.download_and_install(pubkey.clone()).await?;
 
       // Ask user if we need to restart the application
-      let should_exit = ask(
-        "Ready to Restart",
-        "The installation was successful, do you want to restart the application now?",
-      );
+      let should_exit = window
+        .run_on_main_thread(|| {
+          ask(
+            "Ready to Restart",
+            "The installation was successful, do you want to restart the application now?",
+          )
+        })
+        .await?;
       match should_exit {
         AskResponse::Yes => {
-          restart_application(None);
+          window.app_handle().restart()?;
           // safely exit even if the process
           // should be killed
           return Ok(());
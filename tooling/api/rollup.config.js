@@ -27,7 +27,9 @@ export default [
       window: './src/window.ts',
       cli: './src/cli.ts',
       notification: './src/notification.ts',
-      globalShortcut: './src/globalShortcut.ts'
+      globalShortcut: './src/globalShortcut.ts',
+      clipboard: './src/clipboard.ts',
+      log: './src/log.ts'
     },
     treeshake: true,
     perf: true,
@@ -50,7 +50,8 @@ async function getTauriVersion(): Promise<string> {
 }
 
 /**
- * Exits immediately with the given `exitCode`.
+ * Exits the app with the given `exitCode`.
+ * The `Exit` run event is emitted on the Rust side before the process ends.
  *
  * @param exitCode The exit code to use
  * @returns
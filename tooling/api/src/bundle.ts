@@ -16,6 +16,8 @@ import * as tauri from './tauri'
 import * as window from './window'
 import * as notification from './notification'
 import * as globalShortcut from './globalShortcut'
+import * as clipboard from './clipboard'
+import * as log from './log'
 
 export {
   app,
@@ -30,5 +32,7 @@ export {
   tauri,
   window,
   notification,
-  globalShortcut
+  globalShortcut,
+  clipboard,
+  log
 }
@@ -0,0 +1,37 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+import { invokeTauriCommand } from './helpers/tauri'
+
+/**
+ * Reads the clipboard text.
+ *
+ * @returns A promise resolving to the clipboard text, or `null` if the clipboard doesn't hold text.
+ */
+async function readText(): Promise<string | null> {
+  return invokeTauriCommand<string | null>({
+    __tauriModule: 'Clipboard',
+    message: {
+      cmd: 'readText'
+    }
+  })
+}
+
+/**
+ * Writes text to the clipboard.
+ *
+ * @param text The text to write
+ * @returns
+ */
+async function writeText(text: string): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Clipboard',
+    message: {
+      cmd: 'writeText',
+      text
+    }
+  })
+}
+
+export { readText, writeText }
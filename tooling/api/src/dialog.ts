@@ -9,20 +9,47 @@ export interface DialogFilter {
   extensions: string[]
 }
 
+/**
+ * How long the fs API can access the paths selected on a dialog:
+ * until the window is closed, or for all windows across restarts.
+ */
+export type FsAccess = 'window' | 'persisted'
+
 export interface OpenDialogOptions {
+  /** The title of the dialog window. */
+  title?: string
   filters?: DialogFilter[]
   defaultPath?: string
   multiple?: boolean
   directory?: boolean
+  /** Grants the fs API access to the selected paths, with everything under them for directories. */
+  fsAccess?: FsAccess
 }
 
 export interface SaveDialogOptions {
+  /** The title of the dialog window. */
+  title?: string
   filters?: DialogFilter[]
+  /** Initial directory, or file path whose name is proposed. */
   defaultPath?: string
+  /** The proposed file name, overriding the one of `defaultPath`. */
+  fileName?: string
+  /** Grants the fs API access to the selected path. */
+  fsAccess?: FsAccess
+}
+
+export type MessageKind = 'info' | 'warning' | 'error'
+
+export interface MessageDialogOptions {
+  /** The title of the dialog. Defaults to the app name. */
+  title?: string
+  /** The kind of the message, usually shown with its icon. Defaults to `info`. */
+  kind?: MessageKind
 }
 
 /**
- * Open a file/directory selection dialog
+ * Open a file/directory selection dialog.
+ * The dialog is modal to the current window when the runtime supports it.
  *
  * @returns A promise resolving to the selected path(s)
  */
@@ -35,7 +62,6 @@ async function open(
 
   return invokeTauriCommand<string | string[]>({
     __tauriModule: 'Dialog',
-    mainThread: true,
     message: {
       cmd: 'openDialog',
       options
@@ -55,7 +81,6 @@ async function save(options: SaveDialogOptions = {}): Promise<string> {
 
   return invokeTauriCommand<string>({
     __tauriModule: 'Dialog',
-    mainThread: true,
     message: {
       cmd: 'saveDialog',
       options
@@ -63,4 +88,82 @@ async function save(options: SaveDialogOptions = {}): Promise<string> {
   })
 }
 
-export { open, save }
+/**
+ * Shows a message dialog with an `Ok` button.
+ *
+ * @example
+ * ```typescript
+ * import { message } from '@tauri-apps/api/dialog'
+ * await message('File not found', { title: 'Tauri', kind: 'error' })
+ * ```
+ *
+ * @param message The message to show.
+ * @param options The dialog options.
+ * @returns A promise resolving when the dialog is closed.
+ */
+async function message(
+  message: string,
+  options: MessageDialogOptions = {}
+): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Dialog',
+    message: {
+      cmd: 'messageDialog',
+      message,
+      title: options.title,
+      kind: options.kind
+    }
+  })
+}
+
+/**
+ * Shows a question dialog with `Yes` and `No` buttons.
+ *
+ * @param message The message to show.
+ * @param options The dialog options.
+ * @returns A promise resolving to `true` if the user answered `Yes`.
+ */
+async function ask(
+  message: string,
+  options: MessageDialogOptions = {}
+): Promise<boolean> {
+  return invokeTauriCommand({
+    __tauriModule: 'Dialog',
+    message: {
+      cmd: 'askDialog',
+      message,
+      title: options.title,
+      kind: options.kind
+    }
+  })
+}
+
+/**
+ * Shows a question dialog with `Ok` and `Cancel` buttons.
+ *
+ * @example
+ * ```typescript
+ * import { confirm } from '@tauri-apps/api/dialog'
+ * const confirmed = await confirm('Delete the file?', { kind: 'warning' })
+ * ```
+ *
+ * @param message The message to show.
+ * @param options The dialog options.
+ * @returns A promise resolving to `true` if the user confirmed.
+ */
+async function confirm(
+  message: string,
+  options: MessageDialogOptions = {}
+): Promise<boolean> {
+  return invokeTauriCommand({
+    __tauriModule: 'Dialog',
+    message: {
+      cmd: 'confirmDialog',
+      message,
+      title: options.title,
+      kind: options.kind
+    }
+  })
+}
+
+export { open, save, message, ask, confirm }
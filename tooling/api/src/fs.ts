@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: MIT
 
 import { invokeTauriCommand } from './helpers/tauri'
+import { listen } from './event'
+import { transformCallback } from './tauri'
 
 export enum BaseDirectory {
   Audio = 1,
@@ -23,13 +25,33 @@ export enum BaseDirectory {
   Video,
   Resource,
   App,
-  Current
+  Current,
+  Log
 }
 
 export interface FsOptions {
   dir?: BaseDirectory
 }
 
+export interface FsReadOptions extends FsOptions {
+  /** The byte offset to start reading from */
+  offset?: number
+  /** The maximum number of bytes to read; reads until the end of the file if not set */
+  length?: number
+}
+
+export interface FsWriteOptions extends FsOptions {
+  /** Whether the contents should be appended to the file instead of replacing it */
+  append?: boolean
+}
+
+export interface FsWatchOptions extends FsOptions {
+  /** Whether the subdirectories should also be watched */
+  recursive?: boolean
+  /** The debounce delay in milliseconds; defaults to 500 */
+  delayMs?: number
+}
+
 export interface FsDirOptions {
   dir?: BaseDirectory
   recursive?: boolean
@@ -56,6 +78,78 @@ export interface FileEntry {
   children?: FileEntry[]
 }
 
+export interface FsWalkOptions extends FsOptions {
+  /** The maximum depth to descend into; the root directory entries have depth 1 */
+  maxDepth?: number
+  /** Whether hidden files and directories should be included */
+  hidden?: boolean
+  /** Whether the `.gitignore` and `.ignore` rules should be respected; defaults to true */
+  gitignore?: boolean
+  /** Whether symbolic links should be followed */
+  followLinks?: boolean
+  /** Glob patterns of the files to include; matching files are included even if hidden or ignored */
+  include?: string[]
+  /** Glob patterns of the files and directories to exclude */
+  exclude?: string[]
+  /** Whether the metadata of each entry should be read */
+  metadata?: boolean
+  /** The maximum number of entries passed to the handler at once; defaults to 500 */
+  batchSize?: number
+}
+
+export interface WalkEntry {
+  path: string
+  name?: string
+  /** The depth of the entry relative to the root directory */
+  depth: number
+  isDir: boolean
+  isSymlink: boolean
+  /** Only set if requested with the `metadata` option */
+  metadata?: Metadata
+}
+
+type WalkEvent =
+  | { type: 'entries'; payload: WalkEntry[] }
+  | { type: 'error'; payload: string }
+  | { type: 'done' }
+
+export interface Metadata {
+  isDir: boolean
+  isFile: boolean
+  /** Whether the path itself is a symbolic link */
+  isSymlink: boolean
+  /** The size of the file in bytes */
+  size: number
+  readonly: boolean
+  /** The Unix permission bits; null on Windows */
+  mode?: number
+  /** Milliseconds since the Unix epoch */
+  modifiedAt?: number
+  /** Milliseconds since the Unix epoch */
+  accessedAt?: number
+  /** Milliseconds since the Unix epoch; not available on every platform */
+  createdAt?: number
+}
+
+export type WatchEvent =
+  | { type: 'create' | 'write' | 'chmod' | 'remove'; payload: string }
+  | { type: 'rename'; payload: [string, string] }
+  | { type: 'rescan' }
+  | { type: 'error'; payload: string }
+
+interface WatchEventPayload {
+  id: number
+  event: WatchEvent
+}
+
+export type UnwatchFn = () => Promise<void>
+
+/** The mode used to open a file, matching the `fopen` modes */
+export type OpenMode = 'r' | 'r+' | 'w' | 'w+' | 'a' | 'a+'
+
+export type SeekMode = 'start' | 'current' | 'end'
+
+
 /**
  * Reads a file as text.
  *
@@ -79,16 +173,17 @@ async function readTextFile(
 
 /**
  * Reads a file as binary.
+ * The bytes are transferred without JSON encoding.
  *
  * @param filePath Path to the file
  * @param [options]
- * @returns A promise resolving to an array of the file bytes.
+ * @returns A promise resolving to the file bytes.
  */
 async function readBinaryFile(
   filePath: string,
-  options: FsOptions = {}
-): Promise<number[]> {
-  return invokeTauriCommand<number[]>({
+  options: FsReadOptions = {}
+): Promise<Uint8Array> {
+  return invokeTauriCommand<Uint8Array>({
     __tauriModule: 'Fs',
     message: {
       cmd: 'readBinaryFile',
@@ -107,7 +202,7 @@ async function readBinaryFile(
  */
 async function writeFile(
   file: FsTextFileOption,
-  options: FsOptions = {}
+  options: FsWriteOptions = {}
 ): Promise<void> {
   if (typeof options === 'object') {
     Object.freeze(options)
@@ -155,13 +250,16 @@ function uint8ArrayToString(arr: Uint8Array): string {
  * @param buffer
  * @returns A base64 encoded string.
  */
-function arrayBufferToBase64(buffer: ArrayBuffer): string {
-  const str = uint8ArrayToString(new Uint8Array(buffer))
+function arrayBufferToBase64(buffer: ArrayBuffer | Uint8Array): string {
+  const str = uint8ArrayToString(
+    buffer instanceof Uint8Array ? buffer : new Uint8Array(buffer)
+  )
   return btoa(str)
 }
 
 /**
  * Writes a binary file
+ * The bytes are sent base64 encoded, since requests to the core can only carry JSON.
  *
  * @param file File configuration object
  * @param [options]
@@ -169,7 +267,7 @@ function arrayBufferToBase64(buffer: ArrayBuffer): string {
  */
 async function writeBinaryFile(
   file: FsBinaryFileOption,
-  options: FsOptions = {}
+  options: FsWriteOptions = {}
 ): Promise<void> {
   if (typeof options === 'object') {
     Object.freeze(options)
@@ -210,6 +308,42 @@ async function readDir(
   })
 }
 
+/**
+ * Walks a directory tree lazily, passing its entries to the handler in batches.
+ *
+ * @param dir Path to the directory to walk
+ * @param handler Callback invoked with each batch of entries
+ * @param [options] Configuration object
+ * @param [onError] Callback invoked with the entries that could not be read
+ * @returns A promise resolving once the whole tree was walked.
+ */
+async function walk(
+  dir: string,
+  handler: (entries: WalkEntry[]) => void,
+  options: FsWalkOptions = {},
+  onError?: (error: string) => void
+): Promise<void> {
+  return new Promise((resolve, reject) => {
+    invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'walk',
+        path: dir,
+        options,
+        onEventFn: transformCallback((event: WalkEvent) => {
+          if (event.type === 'entries') {
+            handler(event.payload)
+          } else if (event.type === 'error') {
+            onError?.(event.payload)
+          } else {
+            resolve()
+          }
+        })
+      }
+    }).catch(reject)
+  })
+}
+
 /**
  * Creates a directory.
  * If one of the path's parent components doesn't exist
@@ -324,8 +458,219 @@ async function renameFile(
   })
 }
 
+/**
+ * Truncates or extends a file to the given length.
+ *
+ * @param file Path to the file
+ * @param len The new length of the file in bytes
+ * @param [options] Configuration object
+ * @returns
+ */
+async function truncateFile(
+  file: string,
+  len: number,
+  options: FsOptions = {}
+): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'truncateFile',
+      path: file,
+      len,
+      options
+    }
+  })
+}
+
+/**
+ * Checks whether a path exists.
+ *
+ * @param path Path to check
+ * @param [options] Configuration object
+ * @returns A promise resolving to true if the path exists.
+ */
+async function exists(path: string, options: FsOptions = {}): Promise<boolean> {
+  return invokeTauriCommand<boolean>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'exists',
+      path,
+      options
+    }
+  })
+}
+
+/**
+ * Reads the metadata of a file or directory, following symbolic links.
+ *
+ * @param path Path to the file or directory
+ * @param [options] Configuration object
+ * @returns A promise resolving to the path metadata.
+ */
+async function metadata(
+  path: string,
+  options: FsOptions = {}
+): Promise<Metadata> {
+  return invokeTauriCommand<Metadata>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'metadata',
+      path,
+      options
+    }
+  })
+}
+
+/**
+ * Watches a file or directory for changes.
+ * Events are debounced and the watcher is removed when the window is closed.
+ *
+ * @param path Path to watch
+ * @param handler Callback invoked with each change event
+ * @param [options] Configuration object
+ * @returns A promise resolving to a function to stop watching.
+ */
+async function watch(
+  path: string,
+  handler: (event: WatchEvent) => void,
+  options: FsWatchOptions = {}
+): Promise<UnwatchFn> {
+  const id = await invokeTauriCommand<number>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'watch',
+      path,
+      options
+    }
+  })
+  const unlisten = await listen<WatchEventPayload>(
+    'tauri://fs-watch',
+    (event) => {
+      if (event.payload.id === id) {
+        handler(event.payload.event)
+      }
+    }
+  )
+  return async () => {
+    unlisten()
+    return invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'unwatch',
+        id
+      }
+    })
+  }
+}
+
+/**
+ * A handle to an open file, used to read and write large files in chunks.
+ * The handle is closed when the window that opened it is closed.
+ */
+class FileHandle {
+  /** The resource id of the handle */
+  id: number
+
+  constructor(id: number) {
+    this.id = id
+  }
+
+  /**
+   * Reads up to `len` bytes from the current position.
+   *
+   * @param len The maximum number of bytes to read
+   * @returns A promise resolving to the bytes read; empty once the end of the file is reached.
+   */
+  async read(len: number): Promise<Uint8Array> {
+    return invokeTauriCommand<Uint8Array>({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'read',
+        id: this.id,
+        len
+      }
+    })
+  }
+
+  /**
+   * Writes the bytes at the current position.
+   *
+   * @param contents The bytes to write
+   * @returns A promise resolving to the number of bytes written.
+   */
+  async write(contents: ArrayBuffer | Uint8Array): Promise<number> {
+    return invokeTauriCommand<number>({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'write',
+        id: this.id,
+        contents: arrayBufferToBase64(contents)
+      }
+    })
+  }
+
+  /**
+   * Moves the position of the handle.
+   *
+   * @param offset The offset in bytes, relative to `whence`
+   * @param whence The reference position; defaults to the start of the file
+   * @returns A promise resolving to the new position from the start of the file.
+   */
+  async seek(offset: number, whence: SeekMode = 'start'): Promise<number> {
+    return invokeTauriCommand<number>({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'seek',
+        id: this.id,
+        offset,
+        whence
+      }
+    })
+  }
+
+  /**
+   * Closes the handle.
+   *
+   * @returns
+   */
+  async close(): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Fs',
+      message: {
+        cmd: 'close',
+        id: this.id
+      }
+    })
+  }
+}
+
+/**
+ * Opens a file to read or write it in chunks.
+ *
+ * @param path Path to the file
+ * @param [mode] The open mode; defaults to `r`
+ * @param [options] Configuration object
+ * @returns A promise resolving to the file handle.
+ */
+async function open(
+  path: string,
+  mode: OpenMode = 'r',
+  options: FsOptions = {}
+): Promise<FileHandle> {
+  return invokeTauriCommand<number>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'open',
+      path,
+      mode,
+      options
+    }
+  }).then((id) => new FileHandle(id))
+}
+
 export {
   BaseDirectory as Dir,
+  FileHandle,
   readTextFile,
   readBinaryFile,
   writeFile,
@@ -335,5 +680,11 @@ export {
   removeDir,
   copyFile,
   removeFile,
-  renameFile
+  renameFile,
+  truncateFile,
+  exists,
+  metadata,
+  watch,
+  open,
+  walk
 }
@@ -3,12 +3,40 @@
 // SPDX-License-Identifier: MIT
 
 import { invokeTauriCommand } from './helpers/tauri'
-import { transformCallback } from './tauri'
+import { UnlistenFn, listen } from './event'
 
 export type ShortcutHandler = (shortcut: string) => void
 
+// the shortcut events listened to by this window
+const unlisteners: { [shortcut: string]: UnlistenFn } = {}
+
+async function listenShortcuts(
+  shortcuts: string[],
+  handler: ShortcutHandler
+): Promise<void> {
+  for (const shortcut of shortcuts) {
+    unlisteners[shortcut]?.()
+    unlisteners[shortcut] = await listen<string>(
+      'tauri://global-shortcut',
+      (event) => {
+        if (event.payload === shortcut) {
+          handler(shortcut)
+        }
+      }
+    )
+  }
+}
+
+function unlistenShortcuts(shortcuts: string[]): void {
+  for (const shortcut of shortcuts) {
+    unlisteners[shortcut]?.()
+    // eslint-disable-next-line @typescript-eslint/no-dynamic-delete
+    delete unlisteners[shortcut]
+  }
+}
+
 /**
- * Register a global shortcut.
+ * Register a global shortcut, owned by the current window: it is unregistered once the window closes.
  *
  * @param shortcut Shortcut definition, modifiers and key separated by "+" e.g. CmdOrControl+Q
  * @param handler Shortcut handler callback - takes the triggered shortcut as argument
@@ -18,14 +46,14 @@ async function register(
   shortcut: string,
   handler: ShortcutHandler
 ): Promise<void> {
-  return invokeTauriCommand({
+  await invokeTauriCommand({
     __tauriModule: 'GlobalShortcut',
     message: {
       cmd: 'register',
-      shortcut,
-      handler: transformCallback(handler)
+      shortcut
     }
   })
+  return listenShortcuts([shortcut], handler)
 }
 
 /**
@@ -39,14 +67,14 @@ async function registerAll(
   shortcuts: string[],
   handler: ShortcutHandler
 ): Promise<void> {
-  return invokeTauriCommand({
+  await invokeTauriCommand({
     __tauriModule: 'GlobalShortcut',
     message: {
       cmd: 'registerAll',
-      shortcuts,
-      handler: transformCallback(handler)
+      shortcuts
     }
   })
+  return listenShortcuts(shortcuts, handler)
 }
 
 /**
@@ -66,12 +94,13 @@ async function isRegistered(shortcut: string): Promise<boolean> {
 }
 
 /**
- * Unregister a global shortcut.
+ * Unregister a global shortcut registered by the current window.
  *
  * @param shortcut shortcut definition, modifiers and key separated by "+" e.g. CmdOrControl+Q
  * @returns
  */
 async function unregister(shortcut: string): Promise<void> {
+  unlistenShortcuts([shortcut])
   return invokeTauriCommand({
     __tauriModule: 'GlobalShortcut',
     message: {
@@ -82,11 +111,12 @@ async function unregister(shortcut: string): Promise<void> {
 }
 
 /**
- * Unregisters all shortcuts registered by the application.
+ * Unregisters all shortcuts registered by the current window.
  *
  * @returns
  */
 async function unregisterAll(): Promise<void> {
+  unlistenShortcuts(Object.keys(unlisteners))
   return invokeTauriCommand({
     __tauriModule: 'GlobalShortcut',
     message: {
@@ -16,6 +16,8 @@ export type TauriModule =
   | 'Notification'
   | 'Http'
   | 'GlobalShortcut'
+  | 'Clipboard'
+  | 'Log'
 
 export interface TauriCommand {
   __tauriModule: TauriModule
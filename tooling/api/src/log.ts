@@ -0,0 +1,137 @@
+// Copyright 2019-2021 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+/**
+ * Access the application log.
+ * Requires the built-in logger to be installed with `tauri::Builder::logger`.
+ * The API must be allowlisted on `tauri.conf.json` with `tauri > allowlist > log > all`.
+ *
+ * Messages logged from the webview use the `webview:<window label>` target.
+ * When the logger forwards the console, the `console.*` calls are logged too.
+ * @packageDocumentation
+ */
+
+import { invokeTauriCommand } from './helpers/tauri'
+import { listen, UnlistenFn } from './event'
+
+export enum LogLevel {
+  Error = 1,
+  Warn,
+  Info,
+  Debug,
+  Trace
+}
+
+/** A log record of the Rust side of the application. */
+export interface LogRecord {
+  level: LogLevel
+  target: string
+  message: string
+  /** Milliseconds since the UNIX epoch. */
+  timestamp: number
+}
+
+async function log(level: LogLevel, message: string): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Log',
+    message: {
+      cmd: 'log',
+      level,
+      message
+    }
+  })
+}
+
+/**
+ * Logs a message at the error level.
+ *
+ * @param message The message to log.
+ * @returns
+ */
+async function error(message: string): Promise<void> {
+  return log(LogLevel.Error, message)
+}
+
+/**
+ * Logs a message at the warn level.
+ *
+ * @param message The message to log.
+ * @returns
+ */
+async function warn(message: string): Promise<void> {
+  return log(LogLevel.Warn, message)
+}
+
+/**
+ * Logs a message at the info level.
+ *
+ * @param message The message to log.
+ * @returns
+ */
+async function info(message: string): Promise<void> {
+  return log(LogLevel.Info, message)
+}
+
+/**
+ * Logs a message at the debug level.
+ *
+ * @param message The message to log.
+ * @returns
+ */
+async function debug(message: string): Promise<void> {
+  return log(LogLevel.Debug, message)
+}
+
+/**
+ * Logs a message at the trace level.
+ *
+ * @param message The message to log.
+ * @returns
+ */
+async function trace(message: string): Promise<void> {
+  return log(LogLevel.Trace, message)
+}
+
+/**
+ * Subscribes to the log records of the Rust side of the application.
+ * The records logged by the webviews are not forwarded.
+ *
+ * @example
+ * ```typescript
+ * import { attachLogger, LogLevel } from '@tauri-apps/api/log'
+ * const detach = await attachLogger((record) => {
+ *   console.log(`[${record.target}] ${record.message}`)
+ * }, LogLevel.Info)
+ * ```
+ *
+ * @param handler Called with each log record.
+ * @param level The maximum level of the records to receive. Defaults to `LogLevel.Trace`.
+ * @returns A promise resolving to a function to stop receiving the records.
+ */
+async function attachLogger(
+  handler: (record: LogRecord) => void,
+  level?: LogLevel
+): Promise<UnlistenFn> {
+  const unlisten = await listen<LogRecord>('tauri://log', (event) =>
+    handler(event.payload)
+  )
+  await invokeTauriCommand({
+    __tauriModule: 'Log',
+    message: {
+      cmd: 'subscribe',
+      level
+    }
+  })
+  return async () => {
+    unlisten()
+    return invokeTauriCommand({
+      __tauriModule: 'Log',
+      message: {
+        cmd: 'unsubscribe'
+      }
+    })
+  }
+}
+
+export { error, warn, info, debug, trace, attachLogger }
@@ -3,25 +3,50 @@
 // SPDX-License-Identifier: MIT
 
 import { invokeTauriCommand } from './helpers/tauri'
+import { EventCallback, UnlistenFn, listen } from './event'
+
+export interface Action {
+  /** The action identifier, reported by the notification events. */
+  id: string
+  /** The button label. */
+  title: string
+}
+
+export type Urgency = 'low' | 'normal' | 'critical'
 
 export interface Options {
   title: string
   body?: string
   icon?: string
+  /** The notification id. Showing a notification replaces the one with the same id (Linux only). */
+  id?: number
+  /** The action buttons (Linux only). */
+  actions?: Action[]
+  /** The notification urgency (Linux only). */
+  urgency?: Urgency
+  /** The name of the sound played with the notification. */
+  sound?: string
+  /** How long the notification is shown, in milliseconds. */
+  timeout?: number
 }
 
 export type PartialOptions = Omit<Options, 'title'>
 export type Permission = 'granted' | 'denied' | 'default'
 
+export interface NotificationEvent {
+  /** The id of the notification, if it was set. */
+  id?: number
+  type: 'clicked' | 'action' | 'closed'
+  /** The identifier of the clicked action, for `action` events. */
+  action?: string
+}
+
 /**
  * Checks if the permission to send notifications is granted.
  *
- * @returns
+ * @returns A promise resolving to `null` if the user wasn't asked yet.
  */
 async function isPermissionGranted(): Promise<boolean | null> {
-  if (window.Notification.permission !== 'default') {
-    return Promise.resolve(window.Notification.permission === 'granted')
-  }
   return invokeTauriCommand({
     __tauriModule: 'Notification',
     message: {
@@ -31,28 +56,93 @@ async function isPermissionGranted(): Promise<boolean | null> {
 }
 
 /**
- * Requests the permission to send notifications.
+ * Requests the permission to send notifications, asking the user if they weren't asked yet.
  *
  * @returns A promise resolving to whether the user granted the permission or not.
  */
 async function requestPermission(): Promise<Permission> {
-  return window.Notification.requestPermission()
+  return invokeTauriCommand({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'requestNotificationPermission'
+    }
+  })
 }
 
 /**
  * Sends a notification to the user.
+ * Fails if the user didn't grant the notification permission.
  *
  * @param options Notification options
  * @returns
  */
-function sendNotification(options: Options | string): void {
-  if (typeof options === 'string') {
-    // eslint-disable-next-line no-new
-    new window.Notification(options)
-  } else {
-    // eslint-disable-next-line no-new
-    new window.Notification(options.title, options)
-  }
+async function sendNotification(options: Options | string): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'notification',
+      options: typeof options === 'string' ? { title: options } : options
+    }
+  })
 }
 
-export { sendNotification, requestPermission, isPermissionGranted }
+/**
+ * Schedules a notification. Scheduled notifications are shown even if the app restarts in between.
+ * Their user interactions are emitted to every window, since the one scheduling them may be gone.
+ *
+ * @param options Notification options
+ * @param at When to show the notification
+ * @returns A promise resolving to the notification id, used to cancel it.
+ */
+async function scheduleNotification(
+  options: Options | string,
+  at: Date
+): Promise<number> {
+  return invokeTauriCommand({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'notification',
+      options: {
+        ...(typeof options === 'string' ? { title: options } : options),
+        schedule: at.getTime()
+      }
+    }
+  })
+}
+
+/**
+ * Cancels a scheduled notification.
+ *
+ * @param id The id of the scheduled notification
+ * @returns
+ */
+async function cancelNotification(id: number): Promise<void> {
+  return invokeTauriCommand({
+    __tauriModule: 'Notification',
+    message: {
+      cmd: 'cancelNotification',
+      id
+    }
+  })
+}
+
+/**
+ * Listens to the user interactions with the notifications sent by this window and with every scheduled notification.
+ *
+ * @param handler Event handler
+ * @returns A promise resolving to a function to unlisten to the event.
+ */
+async function onNotificationEvent(
+  handler: EventCallback<NotificationEvent>
+): Promise<UnlistenFn> {
+  return listen('tauri://notification', handler)
+}
+
+export {
+  sendNotification,
+  scheduleNotification,
+  cancelNotification,
+  onNotificationEvent,
+  requestPermission,
+  isPermissionGranted
+}
@@ -309,6 +309,23 @@ async function currentDir(): Promise<string> {
   })
 }
 
+/**
+ * Returns the path to the suggested log directory.
+ * Resolves to `${HOME}/Library/Logs/${APP_NAME}` on macOS and `${configDir}/${APP_NAME}/logs` elsewhere.
+ *
+ * @returns
+ */
+async function logDir(): Promise<string> {
+  return invokeTauriCommand<string>({
+    __tauriModule: 'Fs',
+    message: {
+      cmd: 'resolvePath',
+      path: '',
+      directory: BaseDirectory.Log
+    }
+  })
+}
+
 /**
  * Resolves the path with the optional base directory.
  *
@@ -350,5 +367,6 @@ export {
   templateDir,
   videoDir,
   currentDir,
+  logDir,
   resolve as resolvePath
 }
@@ -5,20 +5,40 @@
 import { invokeTauriCommand } from './helpers/tauri'
 import { transformCallback } from './tauri'
 
+export interface SpawnOptions {
+  /**
+   * Environment variables of the process.
+   * Each variable must be listed on the `env` of the command on the shell scope.
+   */
+  env?: { [name: string]: string }
+  /**
+   * The encoding of the output lines, e.g. `windows-1252`. Defaults to UTF-8.
+   * Encodings that are not ASCII compatible, such as UTF-16, are rejected.
+   */
+  encoding?: string
+  /**
+   * Reads the output as raw chunks of bytes instead of lines.
+   * The `data` events then receive arrays of bytes.
+   */
+  raw?: boolean
+}
+
 /**
  * Spawns a process.
  *
- * @param program The name of the program to execute e.g. 'mkdir' or 'node'
+ * @param program The name of the command on the `tauri > allowlist > shell > scope` config
  * @param sidecar Whether the program is a sidecar or a system program
  * @param onEvent
  * @param [args] Command args
+ * @param [options] Spawn options
  * @returns A promise resolving to the process id.
  */
 async function execute(
   program: string,
   sidecar: boolean,
   onEvent: (event: CommandEvent) => void,
-  args?: string | string[]
+  args?: string | string[],
+  options: SpawnOptions = {}
 ): Promise<number> {
   if (typeof args === 'object') {
     Object.freeze(args)
@@ -31,7 +51,8 @@ async function execute(
       program,
       sidecar,
       onEventFn: transformCallback(onEvent),
-      args: typeof args === 'string' ? [args] : args
+      args: typeof args === 'string' ? [args] : args,
+      options
     }
   })
 }
@@ -108,24 +129,43 @@ class Command extends EventEmitter<'close' | 'error'> {
   program: string
   args: string[]
   sidecar = false
+  options: SpawnOptions
   stdout = new EventEmitter<'data'>()
   stderr = new EventEmitter<'data'>()
   pid: number | null = null
 
-  constructor(program: string, args: string | string[] = []) {
+  /**
+   * Creates a command to execute a command of the shell scope.
+   *
+   * @param program The name of the command on the `tauri > allowlist > shell > scope` config
+   * @param args The command args, which must match the args of the scope
+   * @param options Spawn options
+   */
+  constructor(
+    program: string,
+    args: string | string[] = [],
+    options: SpawnOptions = {}
+  ) {
     super()
     this.program = program
     this.args = typeof args === 'string' ? [args] : args
+    this.options = options
   }
 
   /**
    * Creates a command to execute the given sidecar binary.
    *
-   * @param program Binary name
+   * @param program The name of the sidecar command on the shell scope
+   * @param args The command args, which must match the args of the scope
+   * @param options Spawn options
    * @returns
    */
-  static sidecar(program: string, args: string | string[] = []): Command {
-    const instance = new Command(program, args)
+  static sidecar(
+    program: string,
+    args: string | string[] = [],
+    options: SpawnOptions = {}
+  ): Command {
+    const instance = new Command(program, args, options)
     instance.sidecar = true
     return instance
   }
@@ -143,14 +183,17 @@ class Command extends EventEmitter<'close' | 'error'> {
             this._emit('close', event.payload)
             break
           case 'Stdout':
+          case 'StdoutRaw':
             this.stdout._emit('data', event.payload)
             break
           case 'Stderr':
+          case 'StderrRaw':
             this.stderr._emit('data', event.payload)
             break
         }
       },
-      this.args
+      this.args,
+      this.options
     ).then((pid) => new Child(pid))
   }
 
@@ -191,6 +234,8 @@ interface TerminatedPayload {
 type CommandEvent =
   | Event<'Stdout', string>
   | Event<'Stderr', string>
+  | Event<'StdoutRaw', number[]>
+  | Event<'StderrRaw', number[]>
   | Event<'Terminated', TerminatedPayload>
   | Event<'Error', string>
 
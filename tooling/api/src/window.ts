@@ -27,122 +27,70 @@ function getAll(): WindowDef[] {
   return window.__TAURI__.__windows
 }
 
-// events that are emitted right here instead of by the created webview
-const localTauriEvents = ['tauri://created', 'tauri://error']
-
-class WebviewWindowHandle {
+/**
+ * The information about a window.
+ * The runtime can't query the visibility, size, position or focus of a window, so they are not included.
+ */
+export interface WindowInfo {
   label: string
-  listeners: { [key: string]: Array<EventCallback<any>> }
-
-  constructor(label: string) {
-    this.label = label
-    // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
-    this.listeners = Object.create(null)
-  }
-
-  /**
-   * Listen to an event emitted by the webview.
-   *
-   * @param event Event name
-   * @param handler Event handler callback
-   * @returns A promise resolving to a function to unlisten to the event.
-   */
-  async listen<T>(
-    event: string,
-    handler: EventCallback<T>
-  ): Promise<UnlistenFn> {
-    if (this._handleTauriEvent(event, handler)) {
-      return Promise.resolve(() => {
-        // eslint-disable-next-line security/detect-object-injection
-        const listeners = this.listeners[event]
-        listeners.splice(listeners.indexOf(handler), 1)
-      })
-    }
-    return listen(event, handler)
-  }
+  /** Whether this is the current window */
+  current: boolean
+  /** The URL of the page loaded on the window, `null` if unknown */
+  url: string | null
+}
 
+/**
+ * The payload of the `tauri://file-drop-hover` and `tauri://file-drop` window events.
+ */
+export interface FileDropPayload {
+  paths: string[]
   /**
-   * Listen to an one-off event emitted by the webview.
-   *
-   * @param event Event name
-   * @param handler Event handler callback
-   * @returns A promise resolving to a function to unlisten to the event.
+   * The cursor position relative to the window, `null` if the platform doesn't report it.
+   * The wry runtime doesn't report it yet, so it is always `null`.
    */
-  async once<T>(event: string, handler: EventCallback<T>): Promise<UnlistenFn> {
-    if (this._handleTauriEvent(event, handler)) {
-      return Promise.resolve(() => {
-        // eslint-disable-next-line security/detect-object-injection
-        const listeners = this.listeners[event]
-        listeners.splice(listeners.indexOf(handler), 1)
-      })
-    }
-    return once(event, handler)
-  }
+  position: { x: number; y: number } | null
+}
 
-  /**
-   * Emits an event to the webview.
-   *
-   * @param event Event name
-   * @param [payload] Event payload
-   */
-  async emit(event: string, payload?: string): Promise<void> {
-    if (localTauriEvents.includes(event)) {
-      // eslint-disable-next-line
-      for (const handler of this.listeners[event] || []) {
-        handler({ event, id: -1, payload })
-      }
-      return Promise.resolve()
+/**
+ * Lists the open windows.
+ *
+ * @returns A promise resolving to the open windows, sorted by label.
+ */
+async function listWindows(): Promise<WindowInfo[]> {
+  return invokeTauriCommand<WindowInfo[]>({
+    __tauriModule: 'Window',
+    message: {
+      cmd: 'listWindows'
     }
-    return emit(event, this.label, payload)
-  }
+  })
+}
 
-  _handleTauriEvent<T>(event: string, handler: EventCallback<T>): boolean {
-    if (localTauriEvents.includes(event)) {
-      if (!(event in this.listeners)) {
-        // eslint-disable-next-line
-        this.listeners[event] = [handler]
-      } else {
-        // eslint-disable-next-line
-        this.listeners[event].push(handler)
-      }
-      return true
+/**
+ * Gets the information about the current window from the core,
+ * unlike `getCurrent` which uses the values injected when the window was created.
+ *
+ * @returns A promise resolving to the current window information.
+ */
+async function getCurrentInfo(): Promise<WindowInfo> {
+  return invokeTauriCommand<WindowInfo>({
+    __tauriModule: 'Window',
+    message: {
+      cmd: 'getCurrent'
     }
-    return false
-  }
+  })
 }
 
-class WebviewWindow extends WebviewWindowHandle {
-  constructor(label: string, options: WindowOptions = {}) {
-    super(label)
-    invokeTauriCommand({
-      __tauriModule: 'Window',
-      message: {
-        cmd: 'createWebview',
-        options: {
-          label,
-          ...options
-        }
-      }
-    })
-      .then(async () => this.emit('tauri://created'))
-      .catch(async (e) => this.emit('tauri://error', e))
-  }
+/**
+ * Manages a window. Operates on the current window if no label is given.
+ */
+class WindowManager {
+  /** The label of the managed window; the current window if not set */
+  label?: string
 
-  /**
-   * Gets the WebviewWindow handle for the webview associated with the given label.
-   *
-   * @param label The webview window label.
-   * @returns The handle to communicate with the webview or null if the webview doesn't exist.
-   */
-  static getByLabel(label: string): WebviewWindowHandle | null {
-    if (getAll().some((w) => w.label === label)) {
-      return new WebviewWindowHandle(label)
-    }
-    return null
+  constructor(label?: string) {
+    this.label = label
   }
-}
 
-export class WindowManager {
   /**
    * Updates the window resizable flag.
    *
@@ -153,6 +101,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setResizable',
         resizable
       }
@@ -169,6 +118,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setTitle',
         title
       }
@@ -184,6 +134,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'maximize'
       }
     })
@@ -198,6 +149,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'unmaximize'
       }
     })
@@ -212,6 +164,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'minimize'
       }
     })
@@ -226,6 +179,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'unminimize'
       }
     })
@@ -240,6 +194,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'show'
       }
     })
@@ -254,6 +209,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'hide'
       }
     })
@@ -268,6 +224,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'close'
       }
     })
@@ -283,6 +240,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setDecorations',
         decorations
       }
@@ -299,6 +257,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setAlwaysOnTop',
         alwaysOnTop
       }
@@ -315,6 +274,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setWidth',
         width
       }
@@ -331,6 +291,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setHeight',
         height
       }
@@ -348,6 +309,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'resize',
         width,
         height
@@ -366,6 +328,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setMinSize',
         minWidth,
         minHeight
@@ -384,6 +347,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setMaxSize',
         maxWidth,
         maxHeight
@@ -401,6 +365,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setX',
         x
       }
@@ -417,6 +382,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setY',
         y
       }
@@ -434,6 +400,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setPosition',
         x,
         y
@@ -451,6 +418,7 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setFullscreen',
         fullscreen
       }
@@ -467,11 +435,149 @@ export class WindowManager {
     return invokeTauriCommand({
       __tauriModule: 'Window',
       message: {
+        label: this.label,
         cmd: 'setIcon',
         icon
       }
     })
   }
+
+  /**
+   * Starts a native drag of the given files from the window to the OS.
+   *
+   * Not supported by the wry runtime yet: the promise is always rejected there.
+   *
+   * @param paths The paths of the dragged files
+   * @param icon Icon bytes or path to the icon file representing the dragged files
+   * @returns
+   */
+  async startFileDrag(paths: string[], icon?: string | number[]): Promise<void> {
+    return invokeTauriCommand({
+      __tauriModule: 'Window',
+      message: {
+        label: this.label,
+        cmd: 'startFileDrag',
+        paths,
+        icon
+      }
+    })
+  }
+}
+
+// events that are emitted right here instead of by the created webview
+const localTauriEvents = ['tauri://created', 'tauri://error']
+
+class WebviewWindowHandle extends WindowManager {
+  label: string
+  listeners: { [key: string]: Array<EventCallback<any>> }
+
+  constructor(label: string) {
+    super(label)
+    this.label = label
+    // eslint-disable-next-line @typescript-eslint/no-unsafe-assignment
+    this.listeners = Object.create(null)
+  }
+
+  /**
+   * Listen to an event emitted by the webview.
+   *
+   * @param event Event name
+   * @param handler Event handler callback
+   * @returns A promise resolving to a function to unlisten to the event.
+   */
+  async listen<T>(
+    event: string,
+    handler: EventCallback<T>
+  ): Promise<UnlistenFn> {
+    if (this._handleTauriEvent(event, handler)) {
+      return Promise.resolve(() => {
+        // eslint-disable-next-line security/detect-object-injection
+        const listeners = this.listeners[event]
+        listeners.splice(listeners.indexOf(handler), 1)
+      })
+    }
+    return listen(event, handler)
+  }
+
+  /**
+   * Listen to an one-off event emitted by the webview.
+   *
+   * @param event Event name
+   * @param handler Event handler callback
+   * @returns A promise resolving to a function to unlisten to the event.
+   */
+  async once<T>(event: string, handler: EventCallback<T>): Promise<UnlistenFn> {
+    if (this._handleTauriEvent(event, handler)) {
+      return Promise.resolve(() => {
+        // eslint-disable-next-line security/detect-object-injection
+        const listeners = this.listeners[event]
+        listeners.splice(listeners.indexOf(handler), 1)
+      })
+    }
+    return once(event, handler)
+  }
+
+  /**
+   * Emits an event to the webview.
+   *
+   * @param event Event name
+   * @param [payload] Event payload
+   */
+  async emit(event: string, payload?: string): Promise<void> {
+    if (localTauriEvents.includes(event)) {
+      // eslint-disable-next-line
+      for (const handler of this.listeners[event] || []) {
+        handler({ event, id: -1, payload })
+      }
+      return Promise.resolve()
+    }
+    return emit(event, this.label, payload)
+  }
+
+  _handleTauriEvent<T>(event: string, handler: EventCallback<T>): boolean {
+    if (localTauriEvents.includes(event)) {
+      if (!(event in this.listeners)) {
+        // eslint-disable-next-line
+        this.listeners[event] = [handler]
+      } else {
+        // eslint-disable-next-line
+        this.listeners[event].push(handler)
+      }
+      return true
+    }
+    return false
+  }
+}
+
+class WebviewWindow extends WebviewWindowHandle {
+  constructor(label: string, options: WindowOptions = {}) {
+    super(label)
+    invokeTauriCommand({
+      __tauriModule: 'Window',
+      message: {
+        cmd: 'createWebview',
+        options: {
+          label,
+          ...options
+        }
+      }
+    })
+      .then(async () => this.emit('tauri://created'))
+      .catch(async (e) => this.emit('tauri://error', e))
+  }
+
+  /**
+   * Gets the WebviewWindow handle for the webview associated with the given label.
+   *
+   * @param label The webview window label.
+   * @returns The handle to communicate with and manage the webview or null if the webview doesn't exist.
+   */
+  static getByLabel(label: string): WebviewWindowHandle | null {
+    if (getAll().some((w) => w.label === label)) {
+      return new WebviewWindowHandle(label)
+    }
+    return null
+  }
 }
 
 const appWindow = new WindowManager()
@@ -493,6 +599,17 @@ export interface WindowOptions {
   visible?: boolean
   decorations?: boolean
   alwaysOnTop?: boolean
+  skipTaskbar?: boolean
+  contextMenu?: boolean
 }
 
-export { WebviewWindow, getCurrent, getAll, appWindow }
+export {
+  WindowManager,
+  WebviewWindow,
+  WebviewWindowHandle,
+  getCurrent,
+  getCurrentInfo,
+  getAll,
+  listWindows,
+  appWindow
+}
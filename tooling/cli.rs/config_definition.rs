@@ -260,12 +260,22 @@ pub struct WindowConfig {
   /// Whether the window should always be on top of other windows.
   #[serde(default)]
   pub always_on_top: bool,
+  /// Whether the window should be hidden from the taskbar.
+  #[serde(default)]
+  pub skip_taskbar: bool,
+  /// Whether the webview context menu is enabled or not.
+  #[serde(default = "default_context_menu")]
+  pub context_menu: bool,
 }
 
 fn default_visible() -> bool {
   true
 }
 
+fn default_context_menu() -> bool {
+  true
+}
+
 fn default_decorations() -> bool {
   true
 }
@@ -289,6 +299,7 @@ macro_rules! check_feature {
   };
 }
 
+#[skip_serializing_none]
 #[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct FsAllowlistConfig {
@@ -316,6 +327,22 @@ struct FsAllowlistConfig {
   rename_file: bool,
   #[serde(default)]
   path: bool,
+  #[serde(default)]
+  exists: bool,
+  #[serde(default)]
+  metadata: bool,
+  #[serde(default)]
+  truncate_file: bool,
+  #[serde(default)]
+  watch: bool,
+  #[serde(default)]
+  open_file: bool,
+  #[serde(default)]
+  walk: bool,
+  /// The paths the webview can access with the file system API, with everything under them.
+  /// A path can start with a base directory variable, e.g. `$APP/data` or `$DOCUMENT`.
+  /// All paths are accessible if not set.
+  scope: Option<Vec<String>>,
 }
 
 impl Allowlist for FsAllowlistConfig {
@@ -335,6 +362,12 @@ impl Allowlist for FsAllowlistConfig {
       check_feature!(self, features, remove_file, "fs-remove-file");
       check_feature!(self, features, rename_file, "fs-rename-file");
       check_feature!(self, features, path, "fs-path");
+      check_feature!(self, features, exists, "fs-exists");
+      check_feature!(self, features, metadata, "fs-metadata");
+      check_feature!(self, features, truncate_file, "fs-truncate-file");
+      check_feature!(self, features, watch, "fs-watch");
+      check_feature!(self, features, open_file, "fs-open-file");
+      check_feature!(self, features, walk, "fs-walk");
       features
     }
   }
@@ -361,6 +394,43 @@ impl Allowlist for WindowAllowlistConfig {
   }
 }
 
+/// An argument of a command allowed on the shell scope.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+enum ShellAllowedArg {
+  /// A fixed argument.
+  Fixed(String),
+  /// A variable argument, which must fully match the validator regex.
+  Var {
+    /// The regex validating the argument.
+    validator: String,
+  },
+}
+
+/// A command the webview is allowed to execute.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ShellAllowedCommand {
+  /// The name the webview refers to the command with.
+  name: String,
+  /// The program to execute, or the sidecar name if `sidecar` is set.
+  cmd: String,
+  /// The arguments the command must be executed with, in order.
+  #[serde(default)]
+  args: Vec<ShellAllowedArg>,
+  /// Whether the program is a sidecar or a system program.
+  #[serde(default)]
+  sidecar: bool,
+  /// The environment variables the webview can set on the command.
+  #[serde(default)]
+  env: Vec<String>,
+  /// Clears the environment variables inherited from the app.
+  #[serde(default)]
+  clear_env: bool,
+  /// The working directory of the command. Defaults to the app working directory.
+  cwd: Option<String>,
+}
+
 #[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct ShellAllowlistConfig {
@@ -370,6 +440,9 @@ struct ShellAllowlistConfig {
   execute: bool,
   #[serde(default)]
   open: bool,
+  /// The commands the webview is allowed to execute.
+  #[serde(default)]
+  scope: Vec<ShellAllowedCommand>,
 }
 
 impl Allowlist for ShellAllowlistConfig {
@@ -464,6 +537,47 @@ impl Allowlist for GlobalShortcutAllowlistConfig {
   }
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct LogAllowlistConfig {
+  #[serde(default)]
+  all: bool,
+}
+
+impl Allowlist for LogAllowlistConfig {
+  fn to_features(&self) -> Vec<&str> {
+    if self.all {
+      vec!["log-all"]
+    } else {
+      vec![]
+    }
+  }
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ClipboardAllowlistConfig {
+  #[serde(default)]
+  all: bool,
+  #[serde(default)]
+  read_text: bool,
+  #[serde(default)]
+  write_text: bool,
+}
+
+impl Allowlist for ClipboardAllowlistConfig {
+  fn to_features(&self) -> Vec<&str> {
+    if self.all {
+      vec!["clipboard-all"]
+    } else {
+      let mut features = Vec::new();
+      check_feature!(self, features, read_text, "clipboard-read-text");
+      check_feature!(self, features, write_text, "clipboard-write-text");
+      features
+    }
+  }
+}
+
 #[derive(Debug, Default, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct AllowlistConfig {
@@ -483,6 +597,10 @@ struct AllowlistConfig {
   notification: NotificationAllowlistConfig,
   #[serde(default)]
   global_shortcut: GlobalShortcutAllowlistConfig,
+  #[serde(default)]
+  clipboard: ClipboardAllowlistConfig,
+  #[serde(default)]
+  log: LogAllowlistConfig,
 }
 
 impl Allowlist for AllowlistConfig {
@@ -498,6 +616,8 @@ impl Allowlist for AllowlistConfig {
       features.extend(self.http.to_features());
       features.extend(self.notification.to_features());
       features.extend(self.global_shortcut.to_features());
+      features.extend(self.clipboard.to_features());
+      features.extend(self.log.to_features());
       features
     }
   }
@@ -522,6 +642,12 @@ pub struct TauriConfig {
   /// The updater configuration.
   #[serde(default = "default_updater")]
   pub updater: UpdaterConfig,
+  /// The local accelerators, triggered while a window of the app is focused.
+  #[serde(default)]
+  pub accelerators: Vec<AcceleratorConfig>,
+  /// The sidecars started with the app, in order.
+  #[serde(default)]
+  pub sidecars: Vec<SidecarConfig>,
 }
 
 impl TauriConfig {
@@ -529,6 +655,57 @@ impl TauriConfig {
   pub fn features(&self) -> Vec<&str> {
     self.allowlist.to_features()
   }
+
+  /// The validators of the shell scope, with the name of the command declaring them.
+  #[allow(dead_code)]
+  pub fn shell_validators(&self) -> Vec<(&str, &str)> {
+    self
+      .allowlist
+      .shell
+      .scope
+      .iter()
+      .flat_map(|command| {
+        command.args.iter().filter_map(move |arg| match arg {
+          ShellAllowedArg::Var { validator } => Some((command.name.as_str(), validator.as_str())),
+          ShellAllowedArg::Fixed(_) => None,
+        })
+      })
+      .collect()
+  }
+}
+
+/// A sidecar supervised by the app, started with it and restarted when it crashes.
+#[skip_serializing_none]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SidecarConfig {
+  /// The sidecar name, as declared on `tauri > bundle > externalBin`.
+  pub name: String,
+  /// The sidecar arguments.
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// Whether the sidecar is restarted when it exits or not.
+  #[serde(default = "default_sidecar_restart")]
+  pub restart: bool,
+  /// How many times the sidecar is restarted before giving up, unlimited if not set.
+  pub max_restarts: Option<u32>,
+}
+
+fn default_sidecar_restart() -> bool {
+  true
+}
+
+/// A local accelerator, triggered while a window of the app is focused.
+#[skip_serializing_none]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AcceleratorConfig {
+  /// The accelerator, modifiers and key separated by "+" e.g. `CmdOrControl+Shift+P`.
+  pub accelerator: String,
+  /// The event emitted to the focused window when the accelerator is triggered.
+  pub event: String,
+  /// The labels of the windows the accelerator is scoped to, all windows if not set.
+  pub windows: Option<Vec<String>>,
 }
 
 #[skip_serializing_none]
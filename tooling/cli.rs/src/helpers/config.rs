@@ -65,6 +65,21 @@ fn get_internal(merge_config: Option<&str>, reload: bool) -> crate::Result<Confi
 
   #[allow(unused_mut)]
   let mut config: Config = serde_json::from_value(config)?;
+
+  // the shell scope validators are compiled by the app, so fail early if they are invalid
+  let mut invalid_validator = false;
+  for (command, validator) in config.tauri.shell_validators() {
+    if let Err(e) = regex::Regex::new(&format!("^(?:{})$", validator)) {
+      eprintln!(
+        "`tauri.conf.json` error on `tauri > allowlist > shell > scope`: validator `{}` of command `{}` is not a valid regex: {}",
+        validator, command, e
+      );
+      invalid_validator = true;
+    }
+  }
+  if invalid_validator {
+    exit(1);
+  }
   #[cfg(target_os = "linux")]
   if let Some(product_name) = config.package.product_name.as_mut() {
     *product_name = product_name.to_kebab_case();